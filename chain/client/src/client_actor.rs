@@ -458,6 +458,7 @@ impl ClientActor {
                 tier1_connections: vec![],
                 num_connected_peers: 0,
                 peer_max_count: 0,
+                inbound_draining: false,
                 highest_height_peers: vec![],
                 received_bytes_per_sec: 0,
                 sent_bytes_per_sec: 0,
@@ -23,6 +23,8 @@ use futures::future::BoxFuture;
 use near_async::futures::{FutureSpawner, FutureSpawnerExt};
 use near_async::time;
 use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 
@@ -79,7 +81,12 @@ type Stream<Arg, Res> = mpsc::UnboundedSender<Call<Arg, Res>>;
 ///   callers may synchronize and select a leader to execute the handler. This will however make
 ///   the demux implementation way more complicated.
 #[derive(Clone)]
-pub struct Demux<Arg, Res>(Stream<Arg, Res>);
+pub struct Demux<Arg, Res> {
+    stream: Stream<Arg, Res>,
+    /// Number of calls which have been sent to the demux, but haven't been
+    /// passed to a handler yet.
+    queued: Arc<AtomicUsize>,
+}
 
 #[derive(thiserror::Error, Debug, PartialEq)]
 #[error("tokio::Runtime running the demux service has been stopped")]
@@ -92,22 +99,34 @@ impl<Arg: 'static + Send, Res: 'static + Send> Demux<Arg, Res> {
         f: F,
     ) -> impl std::future::Future<Output = Result<Res, ServiceStoppedError>> + use<Arg, Res, F>
     {
-        let stream = self.0.clone();
+        let stream = self.stream.clone();
+        let queued = self.queued.clone();
         async move {
             let (send, recv) = oneshot::channel();
+            // The counter is incremented before sending, so that it never underflows
+            // when the demux subroutine picks up the call.
+            queued.fetch_add(1, Ordering::Relaxed);
             // ok().unwrap(), because DemuxCall doesn't implement Debug.
-            stream
-                .send(Call { arg, out: send, handler: f.wrap() })
-                .map_err(|_| ServiceStoppedError)?;
+            if stream.send(Call { arg, out: send, handler: f.wrap() }).is_err() {
+                queued.fetch_sub(1, Ordering::Relaxed);
+                return Err(ServiceStoppedError);
+            }
             recv.await.map_err(|_| ServiceStoppedError)
         }
     }
 
+    /// Number of calls waiting to be passed to a handler.
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
     // Spawns a subroutine performing the demultiplexing.
     // Panics if rl is not valid.
     pub fn new(rl: rate::Limit, future_spawner: &dyn FutureSpawner) -> Demux<Arg, Res> {
         rl.validate().unwrap();
         let (send, mut recv): (Stream<Arg, Res>, _) = mpsc::unbounded_channel();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let queued_in_loop = queued.clone();
         // TODO(gprusak): this task should be running as long as Demux object exists.
         // "Current" runtime can have a totally different lifespan, so we shouldn't spawn on it.
         // Find a way to express "runtime lifetime > Demux lifetime".
@@ -157,6 +176,7 @@ impl<Arg: 'static + Send, Res: 'static + Send> Demux<Arg, Res> {
                     // cancellation. Once we add cancellation support, this task could accept a context sum:
                     // the sum is valid iff any context is valid.
                     let calls = std::mem::take(&mut calls);
+                    queued_in_loop.fetch_sub(calls.len(), Ordering::Relaxed);
                     let mut args = vec![];
                     let mut outs = vec![];
                     let mut handlers = vec![];
@@ -189,6 +209,6 @@ impl<Arg: 'static + Send, Res: 'static + Send> Demux<Arg, Res> {
                 }
             }
         });
-        Demux(send)
+        Demux { stream: send, queued }
     }
 }
@@ -25,6 +25,42 @@ async fn test_demux() {
     }
 }
 
+#[tokio::test]
+async fn demux_queue_depth() {
+    let demux =
+        demux::Demux::new(rate::Limit { qps: 50., burst: 1 }, &DirectTokioFutureSpawnerForTest);
+    assert_eq!(0, demux.queue_depth());
+
+    // Block the demux on the first call.
+    let (started_send, started_recv) = tokio::sync::oneshot::channel();
+    let (release_send, release_recv) = tokio::sync::oneshot::channel::<()>();
+    let first = tokio::spawn(demux.call(0, move |is: Vec<u64>| async move {
+        started_send.send(()).unwrap();
+        release_recv.await.unwrap();
+        is
+    }));
+    started_recv.await.unwrap();
+    assert_eq!(0, demux.queue_depth());
+
+    // Enqueue more calls while the demux is busy.
+    let mut handles = vec![];
+    for i in 1..=3 {
+        handles.push(tokio::spawn(demux.call(i, |is: Vec<u64>| async { is })));
+        while demux.queue_depth() < i as usize {
+            tokio::task::yield_now().await;
+        }
+    }
+    assert_eq!(3, demux.queue_depth());
+
+    // Unblock the demux and wait for the queue to drain.
+    release_send.send(()).unwrap();
+    assert_eq!(0, first.await.unwrap().unwrap());
+    for (i, h) in handles.into_iter().enumerate() {
+        assert_eq!(i as u64 + 1, h.await.unwrap().unwrap());
+    }
+    assert_eq!(0, demux.queue_depth());
+}
+
 #[test]
 fn demux_runtime_dropped_before_call() {
     let r1 = tokio::runtime::Runtime::new().unwrap();
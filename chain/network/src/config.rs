@@ -1,15 +1,17 @@
 use crate::blacklist;
 use crate::concurrency::rate;
-use crate::config_json::Tier1Config;
+use crate::config_json::{Tier1Config, Tier1ProxySelection};
 use crate::network_protocol::PeerAddr;
 use crate::network_protocol::PeerInfo;
 use crate::peer_manager::peer_store;
 use crate::rate_limits::messages_limits;
+use crate::rate_limits::token_bucket::TokenBucket;
 use crate::snapshot_hosts;
 use crate::stun;
 use crate::tcp;
-use crate::types::ROUTED_MESSAGE_TTL;
+use crate::types::{ROUTED_MESSAGE_TTL, RoutedMessageDroppedEvent};
 use anyhow::Context;
+use near_async::messaging::Sender;
 use near_async::time;
 use near_chain_configs::MutableConfigValue;
 use near_chain_configs::MutableValidatorSigner;
@@ -34,6 +36,32 @@ pub const DEFAULT_ROUTING_GRAPH_MAX_EDGES_PER_SOURCE: usize = 50_000;
 pub const DEFAULT_ROUTING_GRAPH_MAX_PEERS: usize = 100_000;
 pub const DEFAULT_ROUTING_GRAPH_MAX_EDGES: usize = 1_000_000;
 
+/// Default size of the LRU cache of recent routed messages.
+/// It should be large enough to detect duplicates (i.e. all messages received during
+/// production of 1 block should fit).
+pub const DEFAULT_RECENT_ROUTED_MESSAGES_CACHE_SIZE: usize = 10_000;
+
+/// Default maximal number of entries in each of the route back caches.
+pub const DEFAULT_ROUTE_BACK_CACHE_CAPACITY: usize = 100_000;
+/// Default time after which a route back entry may be evicted from the cache.
+pub const DEFAULT_ROUTE_BACK_CACHE_EVICT_TIMEOUT: time::Duration = time::Duration::minutes(2);
+
+/// Default maximal number of peers to which new accounts data is broadcast concurrently.
+pub const DEFAULT_ACCOUNTS_DATA_BROADCAST_BATCH_SIZE: usize = 64;
+
+/// Default per-peer limit on the rate of incoming PartialEncodedChunkForward messages.
+/// A chunk producer forwards parts of every chunk it receives to the other validators,
+/// so the limit needs to leave plenty of headroom for the honest traffic.
+pub const DEFAULT_CHUNK_FORWARD_RATE_LIMIT: rate::Limit = rate::Limit { qps: 1000., burst: 2000 };
+
+/// Default delay before the second attempt of a reconnect loop.
+/// The delay doubles with every subsequent attempt, up to `DEFAULT_RECONNECT_BACKOFF_MAX`.
+pub const DEFAULT_RECONNECT_BACKOFF_BASE: time::Duration = time::Duration::seconds(10);
+/// Default upper bound on the delay between reconnect attempts.
+pub const DEFAULT_RECONNECT_BACKOFF_MAX: time::Duration = time::Duration::minutes(5);
+/// Default age after which a queued reconnect attempt is dropped instead of being started.
+pub const DEFAULT_PENDING_RECONNECT_MAX_AGE: time::Duration = time::Duration::minutes(5);
+
 /// Maximum number of PeerAddrs in the ValidatorConfig::endpoints field.
 pub const MAX_PEER_ADDRS: usize = 10;
 
@@ -112,6 +140,8 @@ pub struct Tier1 {
     /// - a node will try to start outbound TIER1 connections iff `enable_outbound` is true.
     pub enable_inbound: bool,
     pub enable_outbound: bool,
+    /// Strategy for picking a proxy to route a TIER1 message through.
+    pub proxy_selection: Tier1ProxySelection,
 }
 
 impl From<Tier1Config> for Tier1 {
@@ -122,6 +152,7 @@ impl From<Tier1Config> for Tier1 {
             advertise_proxies_interval: cfg.advertise_proxies_interval,
             enable_inbound: cfg.enable_inbound,
             enable_outbound: cfg.enable_outbound,
+            proxy_selection: cfg.proxy_selection,
         }
     }
 }
@@ -151,6 +182,14 @@ pub struct NetworkConfig {
     pub snapshot_hosts: snapshot_hosts::Config,
     pub whitelist_nodes: Vec<PeerInfo>,
     pub handshake_timeout: time::Duration,
+    /// Initial delay between reconnect attempts to the same peer.
+    /// The delay grows exponentially (with jitter) with every failed attempt.
+    pub reconnect_backoff_base: time::Duration,
+    /// Upper bound on the delay between reconnect attempts to the same peer.
+    pub reconnect_backoff_max: time::Duration,
+    /// Reconnect attempts queued for longer than that are dropped instead of being started,
+    /// as the peer has likely reconnected in the meantime.
+    pub pending_reconnect_max_age: time::Duration,
 
     /// Whether to re-establish connection to known reliable peers from previous neard run(s).
     /// See near_network::peer_manager::connection_store for details.
@@ -204,10 +243,15 @@ pub struct NetworkConfig {
     pub archive: bool,
     /// Maximal rate at which SyncAccountsData can be broadcasted.
     pub accounts_data_broadcast_rate_limit: rate::Limit,
+    /// Maximal number of peers to which new accounts data is broadcast concurrently.
+    pub accounts_data_broadcast_batch_size: usize,
     /// Maximal rate at which SyncSnapshotHosts can be broadcasted.
     pub snapshot_hosts_broadcast_rate_limit: rate::Limit,
     /// Maximal rate at which RoutingTable can be recomputed.
     pub routing_table_update_rate_limit: rate::Limit,
+    /// Maximal rate at which a single peer can deliver PartialEncodedChunkForward messages.
+    /// Excess messages are dropped.
+    pub chunk_forward_rate_limit: rate::Limit,
     /// Config of the TIER1 network.
     pub tier1: Tier1,
 
@@ -218,6 +262,10 @@ pub struct NetworkConfig {
     //   * ignoring received deleted edges as well
     pub skip_tombstones: Option<time::Duration>,
 
+    /// Whether to reject edges with an endpoint which is known to be on a different genesis.
+    /// The genesis of a peer is learned from the handshake and kept in the peer store.
+    pub reject_edges_from_other_genesis: bool,
+
     /// Configuration of rate limits for incoming messages.
     pub received_messages_rate_limits: messages_limits::Config,
 
@@ -229,6 +277,17 @@ pub struct NetworkConfig {
     pub routing_graph_max_peers: usize,
     /// Maximum total number of edges stored in the routing graph.
     pub routing_graph_max_edges: usize,
+    /// Size of the LRU cache of recent routed messages, used to detect duplicates.
+    pub recent_routed_messages_cache_size: usize,
+    /// Maximal number of entries in each of the TIER1 and TIER2 route back caches.
+    pub route_back_cache_capacity: usize,
+    /// Time after which a route back entry may be evicted from the cache,
+    /// if the cache is full.
+    pub route_back_cache_evict_timeout: time::Duration,
+
+    /// If set, an event is sent to this sink for every routed message dropped because
+    /// no route to its target was found. Allows tests to assert on specific drops.
+    pub routed_message_dropped_sink: Option<Sender<RoutedMessageDroppedEvent>>,
 
     #[cfg(test)]
     pub(crate) event_sink:
@@ -269,12 +328,20 @@ impl NetworkConfig {
         ) {
             self.accounts_data_broadcast_rate_limit = rate::Limit { qps, burst }
         }
+        if let Some(v) = overrides.accounts_data_broadcast_batch_size {
+            self.accounts_data_broadcast_batch_size = v;
+        }
         if let (Some(qps), Some(burst)) = (
             overrides.routing_table_update_rate_limit_qps,
             overrides.routing_table_update_rate_limit_burst,
         ) {
             self.routing_table_update_rate_limit = rate::Limit { qps, burst }
         }
+        if let (Some(qps), Some(burst)) =
+            (overrides.chunk_forward_rate_limit_qps, overrides.chunk_forward_rate_limit_burst)
+        {
+            self.chunk_forward_rate_limit = rate::Limit { qps, burst }
+        }
         if let Some(rate_limits) = overrides.received_messages_rate_limits {
             self.received_messages_rate_limits.apply_overrides(rate_limits);
         }
@@ -290,6 +357,21 @@ impl NetworkConfig {
         if let Some(v) = overrides.routing_graph_max_edges {
             self.routing_graph_max_edges = v;
         }
+        if let Some(v) = overrides.recent_routed_messages_cache_size {
+            self.recent_routed_messages_cache_size = v;
+        }
+        if let Some(v) = overrides.route_back_cache_capacity {
+            self.route_back_cache_capacity = v;
+        }
+        if let Some(millis) = overrides.route_back_cache_evict_timeout_millis {
+            self.route_back_cache_evict_timeout = time::Duration::milliseconds(millis);
+        }
+        if let Some(millis) = overrides.reconnect_backoff_base_millis {
+            self.reconnect_backoff_base = time::Duration::milliseconds(millis);
+        }
+        if let Some(millis) = overrides.reconnect_backoff_max_millis {
+            self.reconnect_backoff_max = time::Duration::milliseconds(millis);
+        }
     }
 
     pub fn new(
@@ -387,6 +469,7 @@ impl NetworkConfig {
             },
             snapshot_hosts: snapshot_hosts::Config {
                 snapshot_hosts_cache_size: cfg.snapshot_hosts_cache_size,
+                max_snapshot_hosts_per_shard: cfg.max_snapshot_hosts_per_shard,
                 part_selection_cache_batch_size: 10,
             },
             whitelist_nodes: if cfg.whitelist_nodes.is_empty() {
@@ -395,9 +478,11 @@ impl NetworkConfig {
                 cfg.whitelist_nodes
                     .split(',')
                     .map(|peer| match peer.parse::<PeerInfo>() {
-                        Ok(peer) if peer.addr.is_none() => anyhow::bail!(
-                            "whitelist_nodes are required to specify both PeerId and IP:port"
-                        ),
+                        Ok(peer) if peer.addr.is_none() && peer.account_id.is_none() => {
+                            anyhow::bail!(
+                                "whitelist_nodes are required to specify PeerId and either IP:port or account_id"
+                            )
+                        }
                         Ok(peer) => Ok(peer),
                         Err(err) => Err(err.into()),
                     })
@@ -405,6 +490,9 @@ impl NetworkConfig {
                     .context("whitelist_nodes")?
             },
             handshake_timeout: cfg.handshake_timeout.try_into()?,
+            reconnect_backoff_base: DEFAULT_RECONNECT_BACKOFF_BASE,
+            reconnect_backoff_max: DEFAULT_RECONNECT_BACKOFF_MAX,
+            pending_reconnect_max_age: DEFAULT_PENDING_RECONNECT_MAX_AGE,
             connect_to_reliable_peers_on_startup: true,
             monitor_peers_max_period: cfg.monitor_peers_max_period.try_into()?,
             max_num_peers: cfg.max_num_peers,
@@ -428,8 +516,10 @@ impl NetworkConfig {
             outbound_disabled: false,
             archive,
             accounts_data_broadcast_rate_limit: rate::Limit { qps: 0.1, burst: 1 },
+            accounts_data_broadcast_batch_size: DEFAULT_ACCOUNTS_DATA_BROADCAST_BATCH_SIZE,
             snapshot_hosts_broadcast_rate_limit: rate::Limit { qps: 0.1, burst: 1 },
             routing_table_update_rate_limit: rate::Limit { qps: 1., burst: 1 },
+            chunk_forward_rate_limit: DEFAULT_CHUNK_FORWARD_RATE_LIMIT,
             tier1,
             inbound_disabled: cfg.experimental.inbound_disabled,
             skip_tombstones: if cfg.experimental.skip_sending_tombstones_seconds > 0 {
@@ -437,11 +527,16 @@ impl NetworkConfig {
             } else {
                 None
             },
+            reject_edges_from_other_genesis: false,
             received_messages_rate_limits: messages_limits::Config::standard_preset(),
             routing_graph_max_edges_per_message: DEFAULT_ROUTING_GRAPH_MAX_EDGES_PER_MESSAGE,
             routing_graph_max_edges_per_source: DEFAULT_ROUTING_GRAPH_MAX_EDGES_PER_SOURCE,
             routing_graph_max_peers: DEFAULT_ROUTING_GRAPH_MAX_PEERS,
             routing_graph_max_edges: DEFAULT_ROUTING_GRAPH_MAX_EDGES,
+            recent_routed_messages_cache_size: DEFAULT_RECENT_ROUTED_MESSAGES_CACHE_SIZE,
+            route_back_cache_capacity: DEFAULT_ROUTE_BACK_CACHE_CAPACITY,
+            route_back_cache_evict_timeout: DEFAULT_ROUTE_BACK_CACHE_EVICT_TIMEOUT,
+            routed_message_dropped_sink: None,
             #[cfg(test)]
             event_sink: near_async::messaging::IntoSender::into_sender(
                 near_async::messaging::noop(),
@@ -484,10 +579,14 @@ impl NetworkConfig {
             },
             snapshot_hosts: snapshot_hosts::Config {
                 snapshot_hosts_cache_size: 1000,
+                max_snapshot_hosts_per_shard: 1000,
                 part_selection_cache_batch_size: 10,
             },
             whitelist_nodes: vec![],
             handshake_timeout: time::Duration::seconds(5),
+            reconnect_backoff_base: DEFAULT_RECONNECT_BACKOFF_BASE,
+            reconnect_backoff_max: DEFAULT_RECONNECT_BACKOFF_MAX,
+            pending_reconnect_max_age: DEFAULT_PENDING_RECONNECT_MAX_AGE,
             connect_to_reliable_peers_on_startup: true,
             monitor_peers_max_period: time::Duration::seconds(100),
             max_num_peers: 40,
@@ -509,8 +608,10 @@ impl NetworkConfig {
             inbound_disabled: false,
             archive: false,
             accounts_data_broadcast_rate_limit: rate::Limit { qps: 100., burst: 1000000 },
+            accounts_data_broadcast_batch_size: DEFAULT_ACCOUNTS_DATA_BROADCAST_BATCH_SIZE,
             snapshot_hosts_broadcast_rate_limit: rate::Limit { qps: 100., burst: 1000000 },
             routing_table_update_rate_limit: rate::Limit { qps: 10., burst: 1 },
+            chunk_forward_rate_limit: DEFAULT_CHUNK_FORWARD_RATE_LIMIT,
             tier1: Tier1 {
                 // Interval is very large, so that it doesn't happen spontaneously in tests.
                 // It should rather be triggered manually in tests.
@@ -519,13 +620,19 @@ impl NetworkConfig {
                 advertise_proxies_interval: time::Duration::hours(1000),
                 enable_inbound: true,
                 enable_outbound: true,
+                proxy_selection: Tier1ProxySelection::LowestPeerId,
             },
             skip_tombstones: None,
+            reject_edges_from_other_genesis: false,
             received_messages_rate_limits: messages_limits::Config::default(),
             routing_graph_max_edges_per_message: DEFAULT_ROUTING_GRAPH_MAX_EDGES_PER_MESSAGE,
             routing_graph_max_edges_per_source: DEFAULT_ROUTING_GRAPH_MAX_EDGES_PER_SOURCE,
             routing_graph_max_peers: DEFAULT_ROUTING_GRAPH_MAX_PEERS,
             routing_graph_max_edges: DEFAULT_ROUTING_GRAPH_MAX_EDGES,
+            recent_routed_messages_cache_size: DEFAULT_RECENT_ROUTED_MESSAGES_CACHE_SIZE,
+            route_back_cache_capacity: DEFAULT_ROUTE_BACK_CACHE_CAPACITY,
+            route_back_cache_evict_timeout: DEFAULT_ROUTE_BACK_CACHE_EVICT_TIMEOUT,
+            routed_message_dropped_sink: None,
             #[cfg(test)]
             event_sink: near_async::messaging::IntoSender::into_sender(
                 near_async::messaging::noop(),
@@ -577,9 +684,16 @@ impl NetworkConfig {
         self.accounts_data_broadcast_rate_limit
             .validate()
             .context("accounts_Data_broadcast_rate_limit")?;
+        anyhow::ensure!(
+            self.accounts_data_broadcast_batch_size > 0,
+            "accounts_data_broadcast_batch_size must be > 0"
+        );
         self.routing_table_update_rate_limit
             .validate()
             .context("routing_table_update_rate_limit")?;
+        self.chunk_forward_rate_limit.validate().context("chunk_forward_rate_limit")?;
+        TokenBucket::validate_refill_rate(self.chunk_forward_rate_limit.qps as f32)
+            .context("chunk_forward_rate_limit")?;
 
         if let Err(err) = self.received_messages_rate_limits.validate() {
             anyhow::bail!("One or more invalid rate limits: {err:?}");
@@ -599,6 +713,34 @@ impl NetworkConfig {
             self.routing_graph_max_edges_per_source <= self.routing_graph_max_edges,
             "routing_graph_max_edges_per_source must be <= routing_graph_max_edges"
         );
+        anyhow::ensure!(
+            self.recent_routed_messages_cache_size > 0,
+            "recent_routed_messages_cache_size must be > 0"
+        );
+        anyhow::ensure!(
+            self.route_back_cache_capacity > 0,
+            "route_back_cache_capacity must be > 0"
+        );
+        anyhow::ensure!(
+            !self.route_back_cache_evict_timeout.is_negative(),
+            "route_back_cache_evict_timeout must be >= 0"
+        );
+        anyhow::ensure!(
+            self.snapshot_hosts.max_snapshot_hosts_per_shard > 0,
+            "max_snapshot_hosts_per_shard must be > 0"
+        );
+        anyhow::ensure!(
+            self.reconnect_backoff_base.is_positive(),
+            "reconnect_backoff_base must be > 0"
+        );
+        anyhow::ensure!(
+            self.reconnect_backoff_base <= self.reconnect_backoff_max,
+            "reconnect_backoff_base must be <= reconnect_backoff_max"
+        );
+        anyhow::ensure!(
+            self.pending_reconnect_max_age.is_positive(),
+            "pending_reconnect_max_age must be > 0"
+        );
 
         Ok(VerifiedConfig { node_id: self.node_id(), inner: self })
     }
@@ -633,6 +775,7 @@ impl std::ops::Deref for VerifiedConfig {
 #[cfg(test)]
 mod test {
     use super::UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE;
+    use crate::concurrency::rate;
     use crate::config;
     use crate::config_json::NetworkConfigOverrides;
     use crate::network_protocol;
@@ -730,6 +873,21 @@ mod test {
                 &after.accounts_data_broadcast_rate_limit.qps,
                 &overrides.accounts_data_broadcast_rate_limit_qps
             ));
+            assert!(check_override_field(
+                &before.accounts_data_broadcast_batch_size,
+                &after.accounts_data_broadcast_batch_size,
+                &overrides.accounts_data_broadcast_batch_size
+            ));
+            assert!(check_override_field(
+                &before.chunk_forward_rate_limit.burst,
+                &after.chunk_forward_rate_limit.burst,
+                &overrides.chunk_forward_rate_limit_burst
+            ));
+            assert!(check_override_field(
+                &before.chunk_forward_rate_limit.qps,
+                &after.chunk_forward_rate_limit.qps,
+                &overrides.chunk_forward_rate_limit_qps
+            ));
             assert!(check_override_field(
                 &before.routing_graph_max_edges_per_message,
                 &after.routing_graph_max_edges_per_message,
@@ -750,6 +908,37 @@ mod test {
                 &after.routing_graph_max_edges,
                 &overrides.routing_graph_max_edges
             ));
+            assert!(check_override_field(
+                &before.recent_routed_messages_cache_size,
+                &after.recent_routed_messages_cache_size,
+                &overrides.recent_routed_messages_cache_size
+            ));
+            assert!(check_override_field(
+                &before.route_back_cache_capacity,
+                &after.route_back_cache_capacity,
+                &overrides.route_back_cache_capacity
+            ));
+            assert!(check_override_field(
+                &before.route_back_cache_evict_timeout,
+                &after.route_back_cache_evict_timeout,
+                &overrides
+                    .route_back_cache_evict_timeout_millis
+                    .map(|millis| time::Duration::milliseconds(millis))
+            ));
+            assert!(check_override_field(
+                &before.reconnect_backoff_base,
+                &after.reconnect_backoff_base,
+                &overrides
+                    .reconnect_backoff_base_millis
+                    .map(|millis| time::Duration::milliseconds(millis))
+            ));
+            assert!(check_override_field(
+                &before.reconnect_backoff_max,
+                &after.reconnect_backoff_max,
+                &overrides
+                    .reconnect_backoff_max_millis
+                    .map(|millis| time::Duration::milliseconds(millis))
+            ));
         };
         let no_overrides = NetworkConfigOverrides::default();
         let mut overrides = NetworkConfigOverrides::default();
@@ -758,10 +947,18 @@ mod test {
         overrides.routed_message_ttl = Some(43);
         overrides.accounts_data_broadcast_rate_limit_burst = Some(44);
         overrides.accounts_data_broadcast_rate_limit_qps = Some(45.0);
+        overrides.accounts_data_broadcast_batch_size = Some(48);
+        overrides.chunk_forward_rate_limit_burst = Some(46);
+        overrides.chunk_forward_rate_limit_qps = Some(47.0);
         overrides.routing_graph_max_edges_per_message = Some(10_000);
         overrides.routing_graph_max_edges_per_source = Some(20_000);
         overrides.routing_graph_max_peers = Some(30_000);
         overrides.routing_graph_max_edges = Some(40_000);
+        overrides.recent_routed_messages_cache_size = Some(50_000);
+        overrides.route_back_cache_capacity = Some(60_000);
+        overrides.route_back_cache_evict_timeout_millis = Some(70_000);
+        overrides.reconnect_backoff_base_millis = Some(80_000);
+        overrides.reconnect_backoff_max_millis = Some(90_000);
 
         let nc_before =
             config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
@@ -837,6 +1034,63 @@ mod test {
         assert!(nc.verify().is_ok());
     }
 
+    #[test]
+    fn test_recent_routed_messages_cache_size_validation() {
+        let mut nc = config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
+        nc.recent_routed_messages_cache_size = 0;
+        assert!(nc.verify().is_err());
+
+        nc.recent_routed_messages_cache_size = 1;
+        assert!(nc.verify().is_ok());
+    }
+
+    #[test]
+    fn test_route_back_cache_validation() {
+        let mut nc = config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
+        nc.route_back_cache_capacity = 0;
+        assert!(nc.verify().is_err());
+
+        nc.route_back_cache_capacity = 1;
+        nc.route_back_cache_evict_timeout = time::Duration::milliseconds(-1);
+        assert!(nc.verify().is_err());
+
+        nc.route_back_cache_evict_timeout = time::Duration::ZERO;
+        assert!(nc.verify().is_ok());
+    }
+
+    #[test]
+    fn test_max_snapshot_hosts_per_shard_validation() {
+        let mut nc = config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
+        nc.snapshot_hosts.max_snapshot_hosts_per_shard = 0;
+        assert!(nc.verify().is_err());
+
+        nc.snapshot_hosts.max_snapshot_hosts_per_shard = 1;
+        assert!(nc.verify().is_ok());
+    }
+
+    #[test]
+    fn test_accounts_data_broadcast_batch_size_validation() {
+        let mut nc = config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
+        nc.accounts_data_broadcast_batch_size = 0;
+        assert!(nc.verify().is_err());
+
+        nc.accounts_data_broadcast_batch_size = 1;
+        assert!(nc.verify().is_ok());
+    }
+
+    #[test]
+    fn test_chunk_forward_rate_limit_validation() {
+        let mut nc = config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
+        nc.chunk_forward_rate_limit = rate::Limit { qps: 0., burst: 10 };
+        assert!(nc.verify().is_err());
+
+        nc.chunk_forward_rate_limit = rate::Limit { qps: 1e300, burst: 10 };
+        assert!(nc.verify().is_err());
+
+        nc.chunk_forward_rate_limit = rate::Limit { qps: 10., burst: 10 };
+        assert!(nc.verify().is_ok());
+    }
+
     #[test]
     fn received_messages_rate_limits_error() {
         let mut nc = config::NetworkConfig::from_seed("123", tcp::ListenerAddr::reserve_for_test());
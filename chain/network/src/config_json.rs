@@ -64,6 +64,10 @@ fn default_peer_states_cache_size() -> u32 {
 fn default_snapshot_hosts_cache_size() -> u32 {
     1000
 }
+/// Maximum number of snapshot hosts to keep in memory for any single shard.
+fn default_max_snapshot_hosts_per_shard() -> u32 {
+    1000
+}
 /// Remove peers that we didn't hear about for this amount of time.
 fn default_peer_expiration_duration() -> Duration {
     Duration::seconds(7 * 24 * 60 * 60)
@@ -95,10 +99,13 @@ pub struct Config {
     pub boot_nodes: String,
     /// Comma separated list of whitelisted nodes. Inbound connections from the nodes on
     /// the whitelist are accepted even if the limit of the inbound connection has been reached.
-    /// For each whitelisted node specifying both PeerId and one of IP:port or Host:port is required:
+    /// For each whitelisted node specifying PeerId and one of IP:port, Host:port or account_id is required.
+    /// Nodes specified by account_id only (e.g. validators behind NAT) are matched by
+    /// PeerId and the account_id they advertise.
     /// Examples:
     ///   ed25519:86EtEy7epneKyrcJwSWP7zsisTkfDRH5CFVszt4qiQYw@31.192.22.209:24567
     ///   ed25519:86EtEy7epneKyrcJwSWP7zsisTkfDRH5CFVszt4qiQYw@nearnode.com:24567
+    ///   ed25519:86EtEy7epneKyrcJwSWP7zsisTkfDRH5CFVszt4qiQYw@validator.near
     #[serde(default)]
     pub whitelist_nodes: String,
     /// Maximum number of active peers. Hard limit.
@@ -160,6 +167,10 @@ pub struct Config {
     /// Maximum number of snapshot hosts to keep in memory.
     #[serde(default = "default_snapshot_hosts_cache_size")]
     pub snapshot_hosts_cache_size: u32,
+    /// Maximum number of snapshot hosts to keep in memory for any single shard.
+    /// When exceeded, the least recently advertised hosts stop being hosts for that shard.
+    #[serde(default = "default_max_snapshot_hosts_per_shard")]
+    pub max_snapshot_hosts_per_shard: u32,
     // Remove peers that were not active for this amount of time.
     #[serde(default = "default_peer_expiration_duration")]
     #[serde(with = "near_async::time::serde_duration_as_std")]
@@ -243,6 +254,21 @@ fn default_tier1_advertise_proxies_interval() -> time::Duration {
     time::Duration::minutes(15)
 }
 
+/// Strategy for picking the proxy to route a TIER1 message through, when there is no
+/// direct connection to the target validator and several of its proxies are connected.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Tier1ProxySelection {
+    /// Pick the connected proxy with the lowest peer id, so that every node makes the
+    /// same choice for the same set of connected proxies.
+    #[default]
+    LowestPeerId,
+    /// Pick a connected proxy uniformly at random.
+    Random,
+    /// Pick the connected proxy which this node has used least recently.
+    LeastRecentlyUsed,
+}
+
 /// Configuration for Tier1 network
 ///
 /// Tier1 network is a special network between validator nodes that provides faster
@@ -272,6 +298,11 @@ pub struct Tier1Config {
     #[serde(default = "default_tier1_advertise_proxies_interval")]
     #[serde(with = "near_async::time::serde_duration_as_std")]
     pub advertise_proxies_interval: time::Duration,
+
+    /// Strategy for picking a proxy when several proxies of the target validator
+    /// are connected. Direct connections are always preferred over proxies.
+    #[serde(default)]
+    pub proxy_selection: Tier1ProxySelection,
 }
 
 impl Default for Tier1Config {
@@ -282,6 +313,7 @@ impl Default for Tier1Config {
             connect_interval: default_tier1_connect_interval(),
             new_connections_per_attempt: default_tier1_new_connections_per_attempt(),
             advertise_proxies_interval: default_tier1_advertise_proxies_interval(),
+            proxy_selection: Tier1ProxySelection::default(),
         }
     }
 }
@@ -327,8 +359,12 @@ pub struct NetworkConfigOverrides {
     pub outbound_disabled: Option<bool>,
     pub accounts_data_broadcast_rate_limit_burst: Option<u64>,
     pub accounts_data_broadcast_rate_limit_qps: Option<f64>,
+    /// Maximal number of peers to which new accounts data is broadcast concurrently.
+    pub accounts_data_broadcast_batch_size: Option<usize>,
     pub routing_table_update_rate_limit_burst: Option<u64>,
     pub routing_table_update_rate_limit_qps: Option<f64>,
+    pub chunk_forward_rate_limit_burst: Option<u64>,
+    pub chunk_forward_rate_limit_qps: Option<f64>,
     pub received_messages_rate_limits: Option<messages_limits::OverrideConfig>,
     /// Maximum number of edges allowed in a single SyncRoutingTable message.
     pub routing_graph_max_edges_per_message: Option<usize>,
@@ -338,6 +374,16 @@ pub struct NetworkConfigOverrides {
     pub routing_graph_max_peers: Option<usize>,
     /// Maximum total number of edges stored in the routing graph.
     pub routing_graph_max_edges: Option<usize>,
+    /// Size of the LRU cache of recent routed messages, used to detect duplicates.
+    pub recent_routed_messages_cache_size: Option<usize>,
+    /// Maximal number of entries in each of the route back caches.
+    pub route_back_cache_capacity: Option<usize>,
+    /// Time after which a route back entry may be evicted from a full cache.
+    pub route_back_cache_evict_timeout_millis: Option<i64>,
+    /// Initial delay between reconnect attempts to the same peer.
+    pub reconnect_backoff_base_millis: Option<i64>,
+    /// Upper bound on the delay between reconnect attempts to the same peer.
+    pub reconnect_backoff_max_millis: Option<i64>,
 }
 
 impl Default for Config {
@@ -364,6 +410,7 @@ impl Default for Config {
             monitor_peers_max_period: default_monitor_peers_max_period(),
             peer_states_cache_size: default_peer_states_cache_size(),
             snapshot_hosts_cache_size: default_snapshot_hosts_cache_size(),
+            max_snapshot_hosts_per_shard: default_max_snapshot_hosts_per_shard(),
             peer_expiration_duration: default_peer_expiration_duration(),
             public_addrs: vec![],
             allow_private_ip_in_public_addrs: false,
@@ -546,6 +546,18 @@ impl PeerActor {
                 let genesis_id = self.network_state.genesis_id.clone();
                 if handshake.sender_chain_info.genesis_id != genesis_id {
                     tracing::debug!(target: "network", "received connection from node with different genesis");
+                    // The address of an inbound connection is not the listen address of
+                    // the peer, so it is not recorded.
+                    let peer_info = PeerInfo {
+                        id: handshake.sender_peer_id.clone(),
+                        addr: None,
+                        account_id: None,
+                    };
+                    self.network_state.peer_store.peer_genesis_id(
+                        &self.clock,
+                        &peer_info,
+                        handshake.sender_chain_info.genesis_id.clone(),
+                    );
                     self.send_message(&PeerMessage::HandshakeFailure(
                         self.my_node_info.clone(),
                         HandshakeFailureReason::GenesisMismatch(genesis_id),
@@ -704,7 +716,7 @@ impl PeerActor {
                 let register_result: Result<(), RegisterPeerError> = async {
                     let info: crate::peer_manager::network_state::PeerConnectionInfo =
                         conn.as_ref().into();
-                    network_state.validate_new_connection(&info, &edge, &*tcp)?;
+                    network_state.validate_new_connection(&clock, &info, &edge, &*tcp)?;
                     tcp.pool_insert(conn.tier, conn.clone())?;
                     network_state.on_peer_connected(&clock, edge, info, tcp.clone()).await;
                     Ok(())
@@ -874,6 +886,11 @@ impl PeerActor {
                 match reason {
                     HandshakeFailureReason::GenesisMismatch(genesis) => {
                         tracing::warn!(target: "network", %peer_info, our_genesis = ?self.network_state.genesis_id, their_genesis = ?genesis, "attempting to connect to a node with a different genesis block");
+                        self.network_state.peer_store.peer_genesis_id(
+                            &self.clock,
+                            &peer_info,
+                            genesis,
+                        );
                         self.stop(ClosingReason::HandshakeFailed);
                     }
                     HandshakeFailureReason::ProtocolVersionMismatch {
@@ -1060,7 +1077,8 @@ impl PeerActor {
                 if let Some(max_peers) = max_peers {
                     num_peers = min(num_peers, max_peers);
                 }
-                let peers = self.network_state.peer_store.healthy_peers(num_peers as usize);
+                let peers =
+                    self.network_state.peer_store.healthy_peers(&self.clock, num_peers as usize);
 
                 let mut direct_peers = self.network_state.get_direct_peers();
                 if let Some(max_direct_peers) = max_direct_peers {
@@ -23,9 +23,12 @@ use crate::peer_manager::network_transport::NetworkTransport;
 use crate::peer_manager::peer_manager_actor::Event;
 use crate::peer_manager::peer_store;
 use crate::private_messages::RegisterPeerError;
-use crate::routing::route_back_cache::RouteBackCache;
+use crate::rate_limits::token_bucket::TokenBucket;
+use crate::routing::route_back_cache::{DEFAULT_REMOVE_BATCH_SIZE, RouteBackCache};
 use crate::shards_manager::ShardsManagerRequestFromNetwork;
-use crate::snapshot_hosts::{SnapshotHostInfoError, SnapshotHostsCache};
+use crate::snapshot_hosts::{
+    LocalSnapshotHostInfoError, SnapshotHostInfoError, SnapshotHostsCache,
+};
 use crate::spice::data_distribution::{
     SpiceChunkContractAccessesMessage, SpiceContractCodeRequestMessage,
     SpiceContractCodeResponseMessage, SpiceDataDistributorSenderForNetwork,
@@ -42,8 +45,8 @@ use crate::store;
 use crate::tcp;
 use crate::types::{
     BlockInfo, ChainInfo, PeerManagerSenderForNetwork, PeerType, ReasonForBan,
-    StateHeaderRequestBody, StatePartRequestBody, StateRequestSenderForNetwork, Tier3Request,
-    Tier3RequestBody,
+    RoutedMessageDroppedEvent, StateHeaderRequestBody, StatePartRequestBody,
+    StateRequestSenderForNetwork, Tier3Request, Tier3RequestBody,
 };
 use arc_swap::ArcSwap;
 use dashmap::DashMap;
@@ -54,55 +57,89 @@ use near_o11y::span_wrapped_msg::SpanWrappedMessageExt;
 use near_primitives::genesis::GenesisId;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
-use near_primitives::types::AccountId;
+use near_primitives::types::{AccountId, ShardId};
 use parking_lot::{Mutex, RwLock};
+use rand::Rng;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
 mod routing;
+#[cfg(test)]
+mod tests;
 mod tier1;
 
-/// Size of LRU cache size of recent routed messages.
-/// It should be large enough to detect duplicates (i.e. all messages received during
-/// production of 1 block should fit).
-const RECENT_ROUTED_MESSAGES_CACHE_SIZE: usize = 10000;
-
 /// How long a peer has to be unreachable, until we prune it from the in-memory graph.
 const PRUNE_UNREACHABLE_PEERS_AFTER: time::Duration = time::Duration::hours(1);
 
 /// Remove the edges that were created more that this duration ago.
 pub const PRUNE_EDGES_AFTER: time::Duration = time::Duration::minutes(30);
 
-/// How long to wait between reconnection attempts to the same peer
-pub(crate) const RECONNECT_ATTEMPT_INTERVAL: time::Duration = time::Duration::seconds(10);
-
 /// How long a pending Tier3 request remains valid. After sending a state sync request over
 /// Tier2, we expect the peer to open an inbound Tier3 connection within this window. Entries
 /// older than this are cleaned up periodically.
 pub(crate) const PENDING_TIER3_REQUEST_TIMEOUT: time::Duration = time::Duration::seconds(60);
 
+/// Exponential backoff with jitter between reconnect attempts to the same peer.
+struct ReconnectBackoff {
+    /// Upper bound of the next delay, before applying the jitter.
+    next: time::Duration,
+    max: time::Duration,
+}
+
+impl ReconnectBackoff {
+    fn new(base: time::Duration, max: time::Duration) -> Self {
+        Self { next: base, max }
+    }
+
+    /// Returns the delay before the next attempt, drawn uniformly from `[next/2, next]`,
+    /// so that nodes reconnecting to the same peer don't synchronize.
+    /// Doubles `next` (up to `max`) for the following attempt.
+    fn next_delay(&mut self, rng: &mut impl Rng) -> time::Duration {
+        let delay = self.next.mul_f64(rng.gen_range(0.5..=1.0));
+        self.next = std::cmp::min(self.next.saturating_mul(2), self.max);
+        delay
+    }
+}
+
 impl WhitelistNode {
     pub fn from_peer_info(pi: &PeerInfo) -> anyhow::Result<Self> {
-        Ok(Self {
-            id: pi.id.clone(),
-            addr: if let Some(addr) = pi.addr {
-                addr
-            } else {
-                anyhow::bail!("address is missing");
-            },
-            account_id: pi.account_id.clone(),
+        Ok(match (pi.addr, &pi.account_id) {
+            (Some(addr), _) => {
+                Self::Addr { id: pi.id.clone(), addr, account_id: pi.account_id.clone() }
+            }
+            (None, Some(account_id)) => {
+                Self::Account { id: pi.id.clone(), account_id: account_id.clone() }
+            }
+            (None, None) => anyhow::bail!("both address and account_id are missing"),
         })
     }
+
+    /// Checks whether the peer matches this whitelist entry.
+    fn matches(&self, peer_info: &PeerInfo) -> bool {
+        match self {
+            Self::Addr { id, addr, account_id } => {
+                id == &peer_info.id
+                    && Some(*addr) == peer_info.addr
+                    && (account_id.is_none() || account_id == &peer_info.account_id)
+            }
+            Self::Account { id, account_id } => {
+                id == &peer_info.id && Some(account_id) == peer_info.account_id.as_ref()
+            }
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq)]
-pub(crate) struct WhitelistNode {
-    id: PeerId,
-    addr: SocketAddr,
-    account_id: Option<AccountId>,
+pub(crate) enum WhitelistNode {
+    /// Matches peers by id and address, and by account_id if specified.
+    Addr { id: PeerId, addr: SocketAddr, account_id: Option<AccountId> },
+    /// Matches peers by id and account_id.
+    /// Useful for validators behind NAT, whose address is not known.
+    /// The account_id is not authenticated at handshake, so the peer id is required.
+    Account { id: PeerId, account_id: AccountId },
 }
 
 pub(crate) struct NetworkState {
@@ -140,20 +177,25 @@ pub(crate) struct NetworkState {
     /// AnnounceAccounts mapping TIER1 account ids to peer ids.
     pub account_announcements: Arc<AnnounceAccountCache>,
     /// The public IP of this node; available after connecting to any one peer.
-    pub my_public_addr: Arc<RwLock<Option<std::net::SocketAddr>>>,
+    pub my_public_addr: Arc<RwLock<Option<SocketAddr>>>,
     /// Peer store that provides read/write access to peers.
     pub peer_store: peer_store::PeerStore,
     /// Information about state snapshots hosted by network peers.
     pub snapshot_hosts: Arc<SnapshotHostsCache>,
     /// Connection store that provides read/write access to stored connections.
     pub connection_store: connection_store::ConnectionStore,
-    /// List of peers to which we should re-establish a connection
-    pub pending_reconnect: Mutex<Vec<PeerInfo>>,
+    /// List of peers to which we should re-establish a connection, along with the time at
+    /// which the reconnect attempt was queued.
+    pub pending_reconnect: Mutex<Vec<(PeerInfo, time::Instant)>>,
     /// A graph of the whole NEAR network.
     pub graph: Arc<crate::routing::Graph>,
     /// Hashes of the body of recently received routed messages.
     /// It allows us to determine whether messages arrived faster over TIER1 or TIER2 network.
-    pub recent_routed_messages: Mutex<lru::LruCache<CryptoHash, ()>>,
+    /// Each hash is mapped to the tier over which the message arrived first.
+    pub recent_routed_messages: Mutex<lru::LruCache<CryptoHash, tcp::Tier>>,
+    /// Hashes of recently forwarded routed messages, mapped to the TTL they were forwarded with.
+    /// It allows us to detect messages stuck in a routing loop.
+    pub recent_forwarded_messages: Mutex<lru::LruCache<CryptoHash, u8>>,
 
     /// Hash of messages that requires routing back to respective previous hop.
     pub tier2_route_back: Mutex<RouteBackCache>,
@@ -172,14 +214,42 @@ pub(crate) struct NetworkState {
     /// cleaned up after PENDING_TIER3_REQUEST_TIMEOUT.
     pub pending_tier3_requests: DashMap<PeerId, time::Instant>,
 
+    /// Per-peer rate limiters of the incoming `PartialEncodedChunkForward` messages,
+    /// keyed by the peer which delivered the message to us.
+    chunk_forward_rate_limiters: Mutex<HashMap<PeerId, TokenBucket>>,
+
+    /// Sequence number of the last use of each connected TIER1 proxy, used by
+    /// `Tier1ProxySelection::LeastRecentlyUsed`.
+    tier1_proxies_last_used: Mutex<HashMap<PeerId, u64>>,
+    /// Number of TIER1 proxy uses recorded in `tier1_proxies_last_used` so far.
+    tier1_proxy_uses: AtomicU64,
+
     /// Whitelisted nodes, which are allowed to connect even if the connection limit has been
     /// reached.
     whitelist_nodes: Vec<WhitelistNode>,
+    /// Whether new inbound TIER2 connections are being drained (rejected, unless whitelisted).
+    /// Unlike `config.inbound_disabled`, it can be toggled at runtime, e.g. for rolling restarts.
+    /// Existing connections are not affected.
+    inbound_draining: AtomicBool,
+
+    /// Number of account_id -> peer_id lookups (for routed messages) resolved via AccountData.
+    account_lookups_via_account_data: AtomicU64,
+    /// Number of account_id -> peer_id lookups (for routed messages) resolved via the
+    /// AnnounceAccount fallback.
+    account_lookups_via_announce_account: AtomicU64,
 
     /// Mutex which prevents overlapping calls to tier1_advertise_proxies.
     tier1_advertise_proxies_mutex: tokio::sync::Mutex<()>,
-    /// Demultiplexer aggregating calls to add_edges(), for V1 routing protocol
-    add_edges_demux: demux::Demux<EdgesWithSource, Result<(), ReasonForBan>>,
+    /// Demultiplexer aggregating calls to add_edges() and add_edges_no_broadcast(),
+    /// for V1 routing protocol. The flag tells whether the new edges should be broadcasted
+    /// right away.
+    add_edges_demux: demux::Demux<(EdgesWithSource, bool), Result<(), ReasonForBan>>,
+    /// New edges added by add_edges_no_broadcast(), to be broadcasted by flush_edge_broadcast().
+    /// Bounded by routing_graph_max_edges_per_message.
+    pending_edge_broadcast: Mutex<Vec<Edge>>,
+    /// Test-only hook observing the edges of every routing table update being broadcasted.
+    #[cfg(test)]
+    edges_broadcast_hook: Mutex<Option<Box<dyn Fn(&[Edge]) + Send + Sync>>>,
     /// Mutex serializing calls to set_chain_info(), which mutates a bunch of stuff non-atomically.
     /// TODO(gprusak): make it use synchronization primitives in some more canonical way.
     set_chain_info_mutex: Mutex<()>,
@@ -217,6 +287,73 @@ pub(crate) enum RoutedAction {
     Dropped,
 }
 
+/// Arrival of a routed message addressed to this node, as observed by
+/// `NetworkState::process_incoming_routed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutedMessageArrival {
+    /// Hash of the message body.
+    pub hash: CryptoHash,
+    /// Tier over which this copy of the message arrived.
+    pub tier: tcp::Tier,
+    /// Whether this copy is the first one to arrive.
+    pub fastest: bool,
+    /// Tier over which the first copy of the message arrived.
+    pub first_tier: tcp::Tier,
+}
+
+/// Outcome of `NetworkState::send_message_to_account_with_outcome`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccountSendOutcome {
+    /// Message was sent over TIER1, directly or via a proxy of the target.
+    SentTier1,
+    /// Message was routed over TIER2 to the peer owning the account.
+    SentTier2,
+    /// Message is addressed to our own account and has been handled locally.
+    SentToSelf,
+    /// Message was dropped, because no peer is known to own the account.
+    UnknownAccount,
+    /// Message was dropped, because no route to the peer owning the account was found.
+    NoRoute,
+}
+
+/// Where the owner of an account was found by `NetworkState::find_account_owner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountOwnerSource {
+    AccountData,
+    AnnounceAccount,
+}
+
+/// Health of the TIER2 routing. Returned by `NetworkState::routing_status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoutingStatus {
+    /// Number of active edges in the graph.
+    pub active_edges: usize,
+    /// Number of removed edges (tombstones) in the graph.
+    pub removed_edges: usize,
+    /// Number of peers reachable according to the routing table.
+    pub reachable_peers: usize,
+    /// Number of edges adjacent to this node.
+    pub local_edges: usize,
+}
+
+/// Counts of account_id -> peer_id lookups performed when routing messages to accounts,
+/// by the source which resolved the lookup. Returned by `NetworkState::account_lookup_stats`.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AccountLookupStats {
+    pub account_data: u64,
+    pub announce_account: u64,
+}
+
+impl AccountSendOutcome {
+    pub fn is_sent(self) -> bool {
+        match self {
+            Self::SentTier1 | Self::SentTier2 | Self::SentToSelf => true,
+            Self::UnknownAccount | Self::NoRoute => false,
+        }
+    }
+}
+
 /// Transport-agnostic per-connection metadata. The caller (PeerActor
 /// for TCP, TestLoopTransport for testloop) extracts these fields from
 /// whatever connection representation it owns and hands them to the
@@ -226,7 +363,7 @@ pub(crate) struct PeerConnectionInfo {
     pub tier: tcp::Tier,
     pub peer_type: PeerType,
     pub archival: bool,
-    pub tracked_shards: Vec<near_primitives::types::ShardId>,
+    pub tracked_shards: Vec<ShardId>,
     /// AccountKey ownership proof — only populated on TIER1 connections.
     pub owned_account: Option<SignedOwnedAccount>,
     pub established_time: time::Instant,
@@ -287,6 +424,9 @@ impl NetworkState {
         Self {
             ops_spawner,
             add_edges_demux,
+            pending_edge_broadcast: Mutex::new(vec![]),
+            #[cfg(test)]
+            edges_broadcast_hook: Mutex::new(None),
             graph: crate::routing::Graph::new(
                 clock.clone(),
                 crate::routing::GraphConfig {
@@ -312,17 +452,34 @@ impl NetworkState {
             peer_store,
             snapshot_hosts: Arc::new(SnapshotHostsCache::new(config.snapshot_hosts.clone())),
             connection_store: connection_store::ConnectionStore::new(store.clone()).unwrap(),
-            pending_reconnect: Mutex::new(Vec::<PeerInfo>::new()),
+            pending_reconnect: Mutex::new(vec![]),
             accounts_data: Arc::new(AccountDataCache::new()),
             account_announcements: Arc::new(AnnounceAccountCache::new(store)),
-            tier2_route_back: Mutex::new(RouteBackCache::default()),
-            tier1_route_back: Mutex::new(RouteBackCache::default()),
+            tier2_route_back: Mutex::new(RouteBackCache::new(
+                config.route_back_cache_capacity,
+                config.route_back_cache_evict_timeout,
+                DEFAULT_REMOVE_BATCH_SIZE,
+            )),
+            tier1_route_back: Mutex::new(RouteBackCache::new(
+                config.route_back_cache_capacity,
+                config.route_back_cache_evict_timeout,
+                DEFAULT_REMOVE_BATCH_SIZE,
+            )),
             recent_routed_messages: Mutex::new(lru::LruCache::new(
-                NonZeroUsize::new(RECENT_ROUTED_MESSAGES_CACHE_SIZE).unwrap(),
+                NonZeroUsize::new(config.recent_routed_messages_cache_size).unwrap(),
+            )),
+            recent_forwarded_messages: Mutex::new(lru::LruCache::new(
+                NonZeroUsize::new(config.recent_routed_messages_cache_size).unwrap(),
             )),
             txns_since_last_block: AtomicUsize::new(0),
             pending_tier3_requests: DashMap::new(),
+            chunk_forward_rate_limiters: Mutex::new(HashMap::new()),
+            tier1_proxies_last_used: Mutex::new(HashMap::new()),
+            tier1_proxy_uses: AtomicU64::new(0),
             whitelist_nodes,
+            inbound_draining: AtomicBool::new(false),
+            account_lookups_via_account_data: AtomicU64::new(0),
+            account_lookups_via_announce_account: AtomicU64::new(0),
             set_chain_info_mutex: Mutex::new(()),
             config,
             created_at: clock.now(),
@@ -332,6 +489,35 @@ impl NetworkState {
         }
     }
 
+    /// The public address of this node, if it has been configured or discovered
+    /// (i.e. reported back by any connected peer).
+    pub fn my_public_addr(&self) -> Option<SocketAddr> {
+        *self.my_public_addr.read()
+    }
+
+    /// PeerId of this node.
+    pub fn node_id(&self) -> PeerId {
+        self.config.node_id()
+    }
+
+    /// Fingerprint of the node key, for correlating logs of this node.
+    /// It is a hash of the public key, so it doesn't reveal any secret material.
+    pub fn node_key_fingerprint(&self) -> String {
+        CryptoHash::hash_borsh(self.config.node_key.public_key()).to_string()
+    }
+
+    /// Ages of the TIER2 connections, i.e. time elapsed since each connection was established.
+    /// Useful to tell long-lived peers from churny ones.
+    #[cfg(test)]
+    pub fn connection_ages(&self, clock: &time::Clock) -> Vec<(PeerId, time::Duration)> {
+        let now = clock.now();
+        self.peers
+            .tier2()
+            .into_iter()
+            .map(|(peer_id, s)| (peer_id, now.signed_duration_since(s.established_time)))
+            .collect()
+    }
+
     /// Spawn a future on the runtime which has the same lifetime as the NetworkState instance.
     /// In particular if the future contains the NetworkState handler, it will be run until
     /// completion. It is safe to self.spawn(...).await.unwrap(), since runtime will be kept alive
@@ -371,15 +557,41 @@ impl NetworkState {
         }
     }
 
+    /// Like `disconnect_and_ban`, but the ban expires after `duration`.
+    pub fn disconnect_and_ban_for(
+        &self,
+        clock: &time::Clock,
+        peer_id: &PeerId,
+        ban_reason: ReasonForBan,
+        duration: time::Duration,
+        transport: &dyn NetworkTransport,
+    ) {
+        // Record the ban before disconnecting, so that closing the connection keeps it
+        // temporary.
+        if let Err(err) = self.peer_store.peer_ban_for(clock, peer_id, ban_reason, duration) {
+            tracing::debug!(target: "network", ?err, "failed to save peer data");
+        }
+        if self.peers.is_connected_on_tier(peer_id, tcp::Tier::T2) {
+            transport.disconnect_peer(peer_id, Some(ban_reason));
+        }
+    }
+
     /// is_peer_whitelisted checks whether a peer is a whitelisted node.
     /// whitelisted nodes are allowed to connect, even if the inbound connections limit has
     /// been reached. This predicate should be evaluated AFTER the Handshake.
     pub fn is_peer_whitelisted(&self, peer_info: &PeerInfo) -> bool {
-        self.whitelist_nodes
-            .iter()
-            .filter(|wn| wn.id == peer_info.id)
-            .filter(|wn| Some(wn.addr) == peer_info.addr)
-            .any(|wn| wn.account_id.is_none() || wn.account_id == peer_info.account_id)
+        self.whitelist_nodes.iter().any(|wn| wn.matches(peer_info))
+    }
+
+    /// Starts or stops draining inbound TIER2 connections. While draining, new inbound
+    /// connections are rejected unless the peer is whitelisted, while the existing connections
+    /// are kept alive.
+    pub fn set_inbound_draining(&self, draining: bool) {
+        self.inbound_draining.store(draining, Ordering::Relaxed);
+    }
+
+    pub fn is_inbound_draining(&self) -> bool {
+        self.inbound_draining.load(Ordering::Relaxed)
     }
 
     /// predicate checking whether we should allow an inbound connection from peer_info.
@@ -389,6 +601,7 @@ impl NetworkState {
         let pending_outbound = transport.transport_info().pending_outbound.len();
         if t2_count + pending_outbound < self.config.max_num_peers as usize
             && !self.config.inbound_disabled
+            && !self.is_inbound_draining()
         {
             return true;
         }
@@ -405,6 +618,7 @@ impl NetworkState {
     /// written — no rollback needed.
     pub(crate) fn validate_new_connection(
         &self,
+        clock: &time::Clock,
         info: &PeerConnectionInfo,
         edge: &Edge,
         transport: &dyn NetworkTransport,
@@ -414,7 +628,7 @@ impl NetworkState {
             tracing::debug!(target: "network", peer_info = ?peer_info, "dropping connection from blacklisted peer or unknown address");
             return Err(RegisterPeerError::Blacklisted);
         }
-        if self.peer_store.is_banned(&peer_info.id) {
+        if self.peer_store.is_banned(clock, &peer_info.id) {
             tracing::debug!(target: "network", id = ?peer_info.id, "dropping connection from banned peer");
             return Err(RegisterPeerError::Banned);
         }
@@ -444,7 +658,8 @@ impl NetworkState {
                         tracing::debug!(target: "network",
                             tier2 = t2_count, outgoing_peers = pending_outbound,
                             max_num_peers = self.config.max_num_peers,
-                            "dropping handshake (network at max capacity)"
+                            inbound_draining = self.is_inbound_draining(),
+                            "dropping handshake (network at max capacity or draining inbound)"
                         );
                         return Err(RegisterPeerError::ConnectionLimitExceeded);
                     }
@@ -467,7 +682,7 @@ impl NetworkState {
                     //
                     // Edge verification is done first so that a spoofed peer_id with
                     // an invalid edge cannot influence the pending-request lookup.
-                    if !self.pending_tier3_requests.contains_key(&peer_info.id) {
+                    if !self.expect_tier3_connection(&peer_info.id, info.established_time) {
                         return Err(RegisterPeerError::UnexpectedTier3Connection);
                     }
                 }
@@ -476,6 +691,27 @@ impl NetworkState {
         Ok(())
     }
 
+    /// Records that we sent `peer_id` a request which it is expected to answer over an
+    /// inbound TIER3 connection.
+    pub fn record_tier3_request(&self, clock: &time::Clock, peer_id: PeerId) {
+        self.pending_tier3_requests.insert(peer_id, clock.now());
+    }
+
+    /// Returns whether an inbound TIER3 connection from `peer_id`, established at
+    /// `established_time`, answers a request recorded by `record_tier3_request`.
+    ///
+    /// Expired entries are removed only periodically, so the request age is checked here
+    /// as well.
+    pub fn expect_tier3_connection(
+        &self,
+        peer_id: &PeerId,
+        established_time: time::Instant,
+    ) -> bool {
+        self.pending_tier3_requests
+            .get(peer_id)
+            .is_some_and(|sent_at| established_time - *sent_at <= PENDING_TIER3_REQUEST_TIMEOUT)
+    }
+
     /// Post-registration business logic writes. Called AFTER pool_insert
     /// succeeds. Writes to connected_peers (ConnectedPeers handles the
     /// T1 `account_key → peer_id` index internally as a side effect of
@@ -535,7 +771,7 @@ impl NetworkState {
     /// (ConnectedPeers clears the T1 `account_key → peer_id` index
     /// internally, only when the removed peer was T1, with a defensive
     /// check against account-key reuse races). For T2: edge removal
-    /// broadcast, peer_store, connection_store, pending_reconnect.
+    /// broadcast, route_back, peer_store, connection_store, pending_reconnect.
     pub(crate) async fn on_peer_disconnected(
         self: &Arc<Self>,
         clock: &time::Clock,
@@ -545,10 +781,16 @@ impl NetworkState {
         transport: Arc<dyn NetworkTransport>,
     ) {
         self.peers.remove(info.tier, &info.peer_info.id);
+        self.chunk_forward_rate_limiters.lock().remove(&info.peer_info.id);
+        if info.tier == tcp::Tier::T1 {
+            self.tier1_proxies_last_used.lock().remove(&info.peer_info.id);
+        }
 
         if info.tier == tcp::Tier::T2 {
             self.accounts_data_demuxes.lock().remove(&info.peer_info.id);
             self.snapshot_hosts_demuxes.lock().remove(&info.peer_info.id);
+            // Responses can no longer be routed back to this peer.
+            self.tier2_route_back.lock().remove_peer(&info.peer_info.id);
 
             let peer_id = info.peer_info.id.clone();
 
@@ -570,6 +812,11 @@ impl NetworkState {
 
             // Save the fact that we are disconnecting to the PeerStore.
             let res = match &reason {
+                // `disconnect_and_ban_for` records the temporary ban before disconnecting,
+                // banning the peer again would make the ban permanent.
+                ClosingReason::Ban(_) if self.peer_store.is_banned(clock, &info.peer_info.id) => {
+                    Ok(())
+                }
                 ClosingReason::Ban(ban_reason) => {
                     self.peer_store.peer_ban(clock, &info.peer_info.id, *ban_reason)
                 }
@@ -582,7 +829,7 @@ impl NetworkState {
             // Save the fact that we are disconnecting to the ConnectionStore,
             // and push a reconnect attempt, if applicable
             if self.connection_store.connection_closed(&info.peer_info, &info.peer_type, &reason) {
-                self.pending_reconnect.lock().push(info.peer_info.clone());
+                self.push_pending_reconnect(clock, info.peer_info.clone());
             }
         }
 
@@ -597,7 +844,9 @@ impl NetworkState {
         );
     }
 
-    /// Attempt to connect to the given peer until successful, up to max_attempts times
+    /// Attempt to connect to the given peer until successful, up to max_attempts times.
+    /// The first attempt is made immediately, the delay between the subsequent attempts
+    /// grows exponentially, see `ReconnectBackoff`.
     pub async fn reconnect(
         self: &Arc<Self>,
         clock: time::Clock,
@@ -605,9 +854,23 @@ impl NetworkState {
         peer_info: PeerInfo,
         max_attempts: usize,
     ) {
-        let mut interval = time::Interval::new(clock.now(), RECONNECT_ATTEMPT_INTERVAL);
-        for _attempt in 0..max_attempts {
-            interval.tick(&clock).await;
+        let mut backoff = ReconnectBackoff::new(
+            self.config.reconnect_backoff_base,
+            self.config.reconnect_backoff_max,
+        );
+        for attempt in 0..max_attempts {
+            if attempt > 0 {
+                let delay = backoff.next_delay(&mut rand::thread_rng());
+                let deadline = clock.now() + delay;
+                #[cfg(test)]
+                self.config.event_sink.send(
+                    crate::peer_manager::peer_manager_actor::Event::ReconnectBackoff(
+                        peer_info.id.clone(),
+                        delay,
+                    ),
+                );
+                clock.sleep_until(deadline).await;
+            }
 
             let result = transport
                 .connect_to_peer(&clock, peer_info.clone(), tcp::Tier::T2)
@@ -628,6 +891,7 @@ impl NetworkState {
                 return;
             }
         }
+        tracing::debug!(target: "network", %peer_info, max_attempts, next_delay = %backoff.next, "giving up reconnecting");
     }
 
     /// Determine if the given target is referring to us.
@@ -740,10 +1004,17 @@ impl NetworkState {
                           account_id = ?self.config.validator.account_id(),
                           to = ?msg.target(),
                           reason = ?find_route_error,
-                          known_peers = ?self.graph.routing_table.reachable_peers(),
+                          known_peers = ?self.num_reachable_peers(),
                           msg = ?msg.body(),
                         "dropping signed message"
                     );
+                    if let Some(sink) = &self.config.routed_message_dropped_sink {
+                        sink.send(RoutedMessageDroppedEvent {
+                            target: msg.target().clone(),
+                            msg_type: msg.body().variant(),
+                            reason: find_route_error,
+                        });
+                    }
                     return false;
                 }
             },
@@ -766,7 +1037,7 @@ impl NetworkState {
 
     /// Send message to specific account.
     /// Return whether the message is sent or not.
-    /// The message might be sent over TIER1 or TIER2 connection depending on the message type.
+    /// See `send_message_to_account_with_outcome` for details.
     pub fn send_message_to_account(
         self: &Arc<Self>,
         clock: &time::Clock,
@@ -774,6 +1045,19 @@ impl NetworkState {
         msg: TieredMessageBody,
         transport: &dyn NetworkTransport,
     ) -> bool {
+        self.send_message_to_account_with_outcome(clock, account_id, msg, transport).is_sent()
+    }
+
+    /// Send message to specific account.
+    /// The message might be sent over TIER1 or TIER2 connection depending on the message type.
+    /// Returns how the message was sent, or why it was dropped.
+    pub fn send_message_to_account_with_outcome(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+        account_id: &AccountId,
+        msg: TieredMessageBody,
+        transport: &dyn NetworkTransport,
+    ) -> AccountSendOutcome {
         // If the message is allowed to be sent to self, we handle it directly.
         if self.config.validator.account_id().is_some_and(|id| &id == account_id) {
             // For now, we don't allow some types of messages to be sent to self.
@@ -796,50 +1080,34 @@ impl NetworkState {
                 )
                 .await;
             });
-            return true;
+            return AccountSendOutcome::SentToSelf;
         }
 
-        let accounts_data = self.accounts_data.load();
         if tcp::Tier::T1.is_allowed_send_routed(&msg) {
-            for key in accounts_data.keys_by_id.get(account_id).iter().flat_map(|keys| keys.iter())
-            {
-                let data = match accounts_data.data.get(key) {
-                    Some(data) => data,
-                    None => continue,
-                };
-                let peer_id = match self.get_tier1_proxy(data) {
-                    Some(peer_id) => peer_id,
-                    None => continue,
-                };
+            if let Some((target, proxy)) = self.find_tier1_route(account_id) {
                 // TODO(gprusak): in case of PartialEncodedChunk, consider stripping everything
                 // but the header. This will bound the message size
-                let raw = RawRoutedMessage {
-                    target: PeerIdOrHash::PeerId(data.peer_id.clone()),
-                    body: msg,
-                };
+                let raw = RawRoutedMessage { target: PeerIdOrHash::PeerId(target), body: msg };
                 let signed = self.sign_message(clock, raw);
                 let peer_msg = Arc::new(PeerMessage::Routed(signed));
-                transport.send_message(tcp::Tier::T1, peer_id, peer_msg);
-                return true;
+                if transport.send_message(tcp::Tier::T1, proxy.clone(), peer_msg) {
+                    self.record_tier1_proxy_use(&proxy);
+                }
+                return AccountSendOutcome::SentTier1;
             }
         }
 
-        let peer_id_from_account_data = accounts_data
-            .keys_by_id
-            .get(account_id)
-            .iter()
-            .flat_map(|keys| keys.iter())
-            .find_map(|key| accounts_data.data.get(key))
-            .map(|data| data.peer_id.clone());
-        // Find the target peer_id:
-        // - first look it up in self.accounts_data
-        // - if missing, fall back to lookup in self.graph.routing_table
-        // We want to deprecate self.graph.routing_table.account_owner in the next release.
-        let target = if let Some(peer_id) = peer_id_from_account_data {
-            metrics::ACCOUNT_TO_PEER_LOOKUPS.with_label_values(&["AccountData"]).inc();
-            peer_id
-        } else if let Some(peer_id) = self.account_announcements.get_account_owner(account_id) {
-            metrics::ACCOUNT_TO_PEER_LOOKUPS.with_label_values(&["AnnounceAccount"]).inc();
+        let target = if let Some((peer_id, source)) = self.find_account_owner(account_id) {
+            match source {
+                AccountOwnerSource::AccountData => {
+                    metrics::ACCOUNT_TO_PEER_LOOKUPS.with_label_values(&["AccountData"]).inc();
+                    self.account_lookups_via_account_data.fetch_add(1, Ordering::Relaxed);
+                }
+                AccountOwnerSource::AnnounceAccount => {
+                    metrics::ACCOUNT_TO_PEER_LOOKUPS.with_label_values(&["AnnounceAccount"]).inc();
+                    self.account_lookups_via_announce_account.fetch_add(1, Ordering::Relaxed);
+                }
+            }
             peer_id
         } else {
             // TODO(MarX, #1369): Message is dropped here. Define policy for this case.
@@ -851,7 +1119,7 @@ impl NetworkState {
                    err = "unknown account",
             );
             tracing::trace!(target: "network", known_peers = ?self.account_announcements.get_accounts_keys());
-            return false;
+            return AccountSendOutcome::UnknownAccount;
         };
 
         let mut success = false;
@@ -860,7 +1128,71 @@ impl NetworkState {
         for _ in 0..msg.body().message_resend_count() {
             success |= self.send_message_to_peer(clock, tcp::Tier::T2, msg.clone(), transport);
         }
-        success
+        // The message counts as sent if any of the copies was sent.
+        if success { AccountSendOutcome::SentTier2 } else { AccountSendOutcome::NoRoute }
+    }
+
+    /// Finds a TIER1 route to `account_id`: returns the peer owning the account and the
+    /// connected peer (the owner itself or one of its proxies) to send the message through.
+    fn find_tier1_route(&self, account_id: &AccountId) -> Option<(PeerId, PeerId)> {
+        let accounts_data = self.accounts_data.load();
+        // Visit the account's keys in peer id order, so that the choice of target does not
+        // depend on hash set iteration order.
+        let mut candidates: Vec<_> = accounts_data
+            .keys_by_id
+            .get(account_id)
+            .iter()
+            .flat_map(|keys| keys.iter())
+            .filter_map(|key| accounts_data.data.get(key))
+            .collect();
+        candidates.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        candidates
+            .into_iter()
+            .find_map(|data| Some((data.peer_id.clone(), self.get_tier1_proxy(data)?)))
+    }
+
+    /// Finds the peer owning `account_id`:
+    /// - first look it up in self.accounts_data
+    /// - if missing, fall back to lookup in self.account_announcements
+    /// We want to deprecate self.account_announcements in the next release.
+    fn find_account_owner(&self, account_id: &AccountId) -> Option<(PeerId, AccountOwnerSource)> {
+        let accounts_data = self.accounts_data.load();
+        let peer_id_from_account_data = accounts_data
+            .keys_by_id
+            .get(account_id)
+            .iter()
+            .flat_map(|keys| keys.iter())
+            .find_map(|key| accounts_data.data.get(key))
+            .map(|data| data.peer_id.clone());
+        if let Some(peer_id) = peer_id_from_account_data {
+            return Some((peer_id, AccountOwnerSource::AccountData));
+        }
+        let peer_id = self.account_announcements.get_account_owner(account_id)?;
+        Some((peer_id, AccountOwnerSource::AnnounceAccount))
+    }
+
+    /// Returns how many account_id -> peer_id lookups were resolved via AccountData and how
+    /// many had to fall back to AnnounceAccount. Mirrors `ACCOUNT_TO_PEER_LOOKUPS` metric.
+    #[cfg(test)]
+    pub fn account_lookup_stats(&self) -> AccountLookupStats {
+        AccountLookupStats {
+            account_data: self.account_lookups_via_account_data.load(Ordering::Relaxed),
+            announce_account: self.account_lookups_via_announce_account.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Checks whether `peer_id` may deliver one more `PartialEncodedChunkForward` message,
+    /// according to `config.chunk_forward_rate_limit`. Consumes a token if so.
+    pub(crate) fn allow_chunk_forward(&self, clock: &time::Clock, peer_id: &PeerId) -> bool {
+        let now = clock.now();
+        let mut limiters = self.chunk_forward_rate_limiters.lock();
+        let bucket = limiters.entry(peer_id.clone()).or_insert_with(|| {
+            let limit = self.config.chunk_forward_rate_limit;
+            let burst = u32::try_from(limit.burst).unwrap_or(u32::MAX);
+            // The limit has been validated in `NetworkConfig::verify`.
+            TokenBucket::new(burst, burst, limit.qps as f32, now).unwrap()
+        });
+        bucket.acquire(1, now)
     }
 
     pub async fn receive_routed_message(
@@ -886,6 +1218,13 @@ impl NetworkState {
                     None
                 }
                 T1MessageBody::PartialEncodedChunkForward(msg) => {
+                    if !self.allow_chunk_forward(clock, &prev_hop) {
+                        tracing::debug!(target: "network", ?prev_hop, chunk_hash = ?msg.chunk_hash, "dropping partial encoded chunk forward, peer exceeded the rate limit");
+                        metrics::PARTIAL_ENCODED_CHUNK_FORWARD_RATE_LIMITED.inc();
+                        #[cfg(test)]
+                        self.config.event_sink.send(Event::RoutedMessageDropped);
+                        return None;
+                    }
                     self.shards_manager_adapter.send(
                         ShardsManagerRequestFromNetwork::ProcessPartialEncodedChunkForward(msg),
                     );
@@ -1057,6 +1396,13 @@ impl NetworkState {
         }
     }
 
+    /// Returns whether `msg` came back after being forwarded by this node. Every hop decreases
+    /// the TTL, so a message which went around a loop returns with a lower TTL than it was
+    /// forwarded with.
+    fn is_routing_loop(&self, msg: &RoutedMessage) -> bool {
+        self.recent_forwarded_messages.lock().peek(&msg.hash()).is_some_and(|ttl| msg.ttl() < *ttl)
+    }
+
     /// Classifies an incoming routed message as for this node, to be
     /// forwarded, or dropped, after per-connection checks (signature
     /// dedup, ForwardTx rate limiting, signature verification) have
@@ -1080,8 +1426,36 @@ impl NetworkState {
             // Network-wide dedup: check if we already received this message
             // (could arrive via both T1 and T2).
             let new_hash = CryptoHash::hash_borsh(msg.body());
-            let fastest = self.recent_routed_messages.lock().put(new_hash, ()).is_none();
+            let first_tier = {
+                let mut recent = self.recent_routed_messages.lock();
+                match recent.get(&new_hash) {
+                    Some(first_tier) => Some(*first_tier),
+                    None => {
+                        recent.put(new_hash, tier);
+                        None
+                    }
+                }
+            };
+            let fastest = first_tier.is_none();
             metrics::record_routed_msg_metrics(clock, &msg, tier, fastest);
+            #[cfg(test)]
+            self.config.event_sink.send(Event::RoutedMessageArrival(RoutedMessageArrival {
+                hash: new_hash,
+                tier,
+                fastest,
+                first_tier: first_tier.unwrap_or(tier),
+            }));
+        }
+
+        // A message we have already forwarded came back to us, so it is circling in a routing
+        // loop. Drop it before it overwrites the route back entry recorded when it first passed
+        // through.
+        if !for_me && self.is_routing_loop(&msg) {
+            #[cfg(test)]
+            self.config.event_sink.send(Event::RoutedMessageDropped);
+            tracing::debug!(target: "network", ?msg, from = ?from, "message dropped because it came back after being forwarded");
+            metrics::NETWORK_ROUTING_LOOPS_DETECTED.inc();
+            return RoutedAction::Dropped;
         }
 
         self.add_route_back(clock, from, tier, &msg);
@@ -1092,6 +1466,7 @@ impl NetworkState {
             if msg.decrease_ttl() {
                 let num_hops = msg.num_hops_mut();
                 *num_hops = num_hops.saturating_add(1);
+                self.recent_forwarded_messages.lock().put(msg.hash(), msg.ttl());
                 RoutedAction::Forward(msg)
             } else {
                 #[cfg(test)]
@@ -1324,22 +1699,26 @@ impl NetworkState {
                 let guard = this.accounts_data_demuxes.lock();
                 guard.iter().map(|(id, demux)| (id.clone(), demux.clone())).collect()
             };
-            let tasks: Vec<_> = peers
-                .into_iter()
-                .map(|(peer_id, demux)| {
-                    this.spawn(
-                        "send_accounts_data",
-                        this.clone().gossip_accounts_data_to_peer(
-                            peer_id,
-                            demux,
-                            new_data.clone(),
-                            transport.clone(),
-                        ),
-                    )
-                })
-                .collect();
-            for t in tasks {
-                t.await.unwrap();
+            // Broadcast to at most `accounts_data_broadcast_batch_size` peers concurrently,
+            // to avoid spawning a task per peer at once in case of a large fanout.
+            for batch in peers.chunks(this.config.accounts_data_broadcast_batch_size) {
+                let tasks: Vec<_> = batch
+                    .iter()
+                    .map(|(peer_id, demux)| {
+                        this.spawn(
+                            "send_accounts_data",
+                            this.clone().gossip_accounts_data_to_peer(
+                                peer_id.clone(),
+                                demux.clone(),
+                                new_data.clone(),
+                                transport.clone(),
+                            ),
+                        )
+                    })
+                    .collect();
+                for t in tasks {
+                    t.await.unwrap();
+                }
             }
             err
         })
@@ -1390,6 +1769,43 @@ impl NetworkState {
         .unwrap()
     }
 
+    /// Advertises the local node's snapshot availability to all TIER2 peers.
+    /// `info` has to describe the local node and be signed with its node key.
+    /// An info which isn't newer (by epoch height) than the one already
+    /// advertised is not broadcast again, so repeated advertisements don't spam
+    /// peers. Returns whether `info` has been broadcast.
+    pub fn advertise_local_snapshot(
+        &self,
+        info: Arc<SnapshotHostInfo>,
+        transport: &dyn NetworkTransport,
+    ) -> Result<bool, LocalSnapshotHostInfoError> {
+        if info.peer_id != self.config.node_id() {
+            return Err(LocalSnapshotHostInfoError::NotLocalNode);
+        }
+        info.verify()?;
+        let Some(info) = self.snapshot_hosts.insert_skip_verify(info) else {
+            tracing::debug!(
+                target: "network",
+                "skipping snapshot host info broadcast: already advertised"
+            );
+            return Ok(false);
+        };
+        transport.broadcast_message(Arc::new(PeerMessage::SyncSnapshotHosts(SyncSnapshotHosts {
+            hosts: vec![info],
+        })));
+        Ok(true)
+    }
+
+    /// Returns the known peers advertising a snapshot which includes the given shard.
+    pub fn snapshot_hosts_for_shard(&self, shard_id: ShardId) -> Vec<PeerId> {
+        self.snapshot_hosts.hosts_for_shard(shard_id)
+    }
+
+    /// Returns the number of known snapshot hosts.
+    pub fn num_snapshot_hosts(&self) -> usize {
+        self.snapshot_hosts.num_hosts()
+    }
+
     /// a) there is a peer we should be connected to, but we aren't
     /// b) there is an edge indicating that we should be disconnected from a peer, but we are connected.
     /// Try to resolve the inconsistency.
@@ -1464,7 +1880,8 @@ impl NetworkState {
                             // Unwrap is safe, because new_edge is always valid.
                             let new_edge =
                                 edge.remove_edge(this.config.node_id(), &this.config.node_key);
-                            this.add_edges(
+                            // The tombstones are broadcasted together once all the tasks are done.
+                            this.add_edges_no_broadcast(
                                 &clock,
                                 EdgesWithSource::Local(vec![new_edge.clone()]),
                                 transport,
@@ -1480,6 +1897,7 @@ impl NetworkState {
             for t in tasks {
                 let _ = t.await;
             }
+            this.flush_edge_broadcast(&clock, transport.as_ref());
         })
         .await
         .unwrap()
@@ -1489,12 +1907,30 @@ impl NetworkState {
         self.connection_store.update(clock, &self.peers.tier2());
     }
 
+    /// Queues a reconnect attempt to `peer_info`, unless one is already pending.
+    pub fn push_pending_reconnect(&self, clock: &time::Clock, peer_info: PeerInfo) {
+        let mut pending_reconnect = self.pending_reconnect.lock();
+        if pending_reconnect.iter().any(|(info, _)| info == &peer_info) {
+            return;
+        }
+        pending_reconnect.push((peer_info, clock.now()));
+    }
+
+    /// Returns the peers with a pending reconnect attempt, without clearing pending_reconnect.
+    #[cfg(test)]
+    pub fn list_pending_reconnect(&self) -> Vec<PeerInfo> {
+        self.pending_reconnect.lock().iter().map(|(info, _)| info.clone()).collect()
+    }
+
+    /// Drops the reconnect attempts which have been pending for longer than `max_age`.
+    pub fn prune_pending_reconnect(&self, clock: &time::Clock, max_age: time::Duration) {
+        let now = clock.now();
+        self.pending_reconnect.lock().retain(|(_, queued_at)| now - *queued_at <= max_age);
+    }
+
     /// Clears pending_reconnect and returns the cleared values
     pub fn poll_pending_reconnect(&self) -> Vec<PeerInfo> {
-        let mut pending_reconnect = self.pending_reconnect.lock();
-        let polled = pending_reconnect.clone();
-        pending_reconnect.clear();
-        return polled;
+        self.pending_reconnect.lock().drain(..).map(|(info, _)| info).collect()
     }
 
     /// Collects and returns PeerInfos for all directly connected TIER2 peers.
@@ -1502,6 +1938,30 @@ impl NetworkState {
         self.peers.tier2().into_values().map(|s| s.peer_info).collect()
     }
 
+    /// Returns the peers currently reachable according to the TIER2 routing table.
+    #[cfg(test)]
+    pub fn reachable_peers(&self) -> Vec<PeerId> {
+        self.graph.routing_table.info().next_hops.keys().cloned().collect()
+    }
+
+    /// Returns the number of peers currently reachable according to the TIER2 routing table.
+    pub fn num_reachable_peers(&self) -> usize {
+        self.graph.routing_table.reachable_peers()
+    }
+
+    /// Returns a summary of the TIER2 routing health. It is O(1): the counters are maintained
+    /// by the graph as the edges are updated.
+    pub fn routing_status(&self) -> RoutingStatus {
+        let graph = self.graph.load();
+        let active_edges = graph.active_edges as usize;
+        RoutingStatus {
+            active_edges,
+            removed_edges: graph.edges.len() - active_edges,
+            reachable_peers: self.graph.routing_table.reachable_peers(),
+            local_edges: graph.local_edges.len(),
+        }
+    }
+
     /// Sets the chain info, and updates the set of TIER1 keys.
     /// Returns true iff the set of TIER1 keys has changed.
     pub fn set_chain_info(
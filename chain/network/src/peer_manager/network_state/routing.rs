@@ -25,11 +25,24 @@ impl NetworkState {
         if rtu == RoutingTableUpdate::default() {
             return;
         }
+        let edges_before = rtu.edges.len();
         rtu.edges = Edge::deduplicate(rtu.edges);
+        metrics::NETWORK_EDGES_DEDUPLICATED.inc_by((edges_before - rtu.edges.len()) as u64);
+        #[cfg(test)]
+        if let Some(hook) = &*self.edges_broadcast_hook.lock() {
+            hook(&rtu.edges);
+        }
         let msg = Arc::new(PeerMessage::SyncRoutingTable(rtu));
         transport.broadcast_message(msg);
     }
 
+    /// Registers a hook called with the final (deduplicated and sorted) edges of every
+    /// routing table update broadcasted by this node. Replaces any previously set hook.
+    #[cfg(test)]
+    pub fn on_edges_broadcast(&self, f: impl Fn(&[Edge]) + Send + Sync + 'static) {
+        *self.edges_broadcast_hook.lock() = Some(Box::new(f));
+    }
+
     /// Adds AnnounceAccounts (without validating them) to the routing table.
     /// Then it broadcasts all the AnnounceAccounts that haven't been seen before.
     pub async fn add_accounts(
@@ -93,6 +106,22 @@ impl NetworkState {
         Ok(edge)
     }
 
+    /// Drops the edges with an endpoint which is known to be on a different genesis.
+    fn drop_edges_from_other_genesis(&self, edges: &mut EdgesWithSource) {
+        let (EdgesWithSource::Local(edges) | EdgesWithSource::Remote { edges, .. }) = edges;
+        let total = edges.len();
+        edges.retain(|edge| {
+            let (peer0, peer1) = edge.key();
+            !self.peer_store.is_on_other_genesis(peer0, &self.genesis_id)
+                && !self.peer_store.is_on_other_genesis(peer1, &self.genesis_id)
+        });
+        let rejected = total - edges.len();
+        if rejected > 0 {
+            tracing::debug!(target: "network", rejected, "dropping edges from peers on a different genesis");
+            metrics::EDGES_FROM_OTHER_GENESIS_REJECTED.inc_by(rejected as u64);
+        }
+    }
+
     /// Validates edges, then adds them to the graph and then broadcasts all the edges that
     /// hasn't been observed before. Returns an error iff any edge was invalid. Even if an
     /// error was returned some of the valid input edges might have been added to the graph.
@@ -102,33 +131,64 @@ impl NetworkState {
         edges: EdgesWithSource,
         transport: Arc<dyn NetworkTransport>,
     ) -> Result<(), ReasonForBan> {
+        self.add_edges_with_broadcast(clock, edges, true, transport).await
+    }
+
+    /// Like `add_edges`, but the new edges are not broadcasted right away. Instead they are
+    /// accumulated until `flush_edge_broadcast` is called, so that adding many batches of edges
+    /// in a row (e.g. during bootstrap) results in a single SyncRoutingTable broadcast.
+    /// Once `routing_graph_max_edges_per_message` edges are accumulated, they are broadcasted
+    /// without waiting for `flush_edge_broadcast`.
+    pub async fn add_edges_no_broadcast(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+        edges: EdgesWithSource,
+        transport: Arc<dyn NetworkTransport>,
+    ) -> Result<(), ReasonForBan> {
+        self.add_edges_with_broadcast(clock, edges, false, transport).await
+    }
+
+    async fn add_edges_with_broadcast(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+        mut edges: EdgesWithSource,
+        broadcast: bool,
+        transport: Arc<dyn NetworkTransport>,
+    ) -> Result<(), ReasonForBan> {
+        if self.config.reject_edges_from_other_genesis {
+            self.drop_edges_from_other_genesis(&mut edges);
+        }
         if edges.is_empty() {
             return Ok(());
         }
         let this = self.clone();
         let clock = clock.clone();
         self.add_edges_demux
-            .call(edges, |edges: Vec<EdgesWithSource>| async move {
-                let (mut edges, oks) = this.graph.update(edges);
-                // Don't send tombstones during the initial time.
-                // Most of the network is created during this time, which results
-                // in us sending a lot of tombstones to peers.
-                // Later, the amount of new edges is a lot smaller.
-                if let Some(skip_tombstones_duration) = this.config.skip_tombstones {
-                    if clock.now() < this.created_at + skip_tombstones_duration {
-                        edges.retain(|edge| edge.edge_type() == EdgeState::Active);
-                        metrics::EDGE_TOMBSTONE_SENDING_SKIPPED.inc();
+            .call((edges, broadcast), |calls: Vec<(EdgesWithSource, bool)>| async move {
+                // Calls which arrived after this batch was picked up.
+                metrics::EDGE_ADD_QUEUE_DEPTH.set(this.add_edges_demux.queue_depth() as i64);
+                let deferred: HashSet<Edge> =
+                    calls
+                        .iter()
+                        .filter(|(_, broadcast)| !broadcast)
+                        .flat_map(|(edges, _)| {
+                            let (EdgesWithSource::Local(edges)
+                            | EdgesWithSource::Remote { edges, .. }) = edges;
+                            edges.iter().cloned()
+                        })
+                        .collect();
+                let (edges, oks) =
+                    this.graph.update(calls.into_iter().map(|(edges, _)| edges).collect());
+                let (deferred, mut edges): (Vec<_>, Vec<_>) =
+                    edges.into_iter().partition(|edge| deferred.contains(edge));
+                if !deferred.is_empty() {
+                    let mut pending = this.pending_edge_broadcast.lock();
+                    pending.extend(deferred);
+                    if pending.len() >= this.config.routing_graph_max_edges_per_message {
+                        edges.append(&mut pending);
                     }
                 }
-                // Broadcast new edges to all other peers.
-                #[cfg(test)]
-                this.config.event_sink.send(
-                    crate::peer_manager::peer_manager_actor::Event::EdgesAdded(edges.clone()),
-                );
-                this.broadcast_routing_table_update(
-                    RoutingTableUpdate::from_edges(edges),
-                    &*transport,
-                );
+                this.broadcast_new_edges(&clock, edges, &*transport);
                 oks.iter()
                     .map(|ok| match ok {
                         true => Ok(()),
@@ -140,6 +200,43 @@ impl NetworkState {
             .unwrap_or(Ok(()))
     }
 
+    /// Number of edge batches waiting in `add_edges_demux` to be added to the graph.
+    /// A persistently high value indicates that the routing graph can't keep up.
+    #[cfg(test)]
+    pub fn add_edges_queue_depth(&self) -> usize {
+        self.add_edges_demux.queue_depth()
+    }
+
+    /// Broadcasts the edges accumulated by `add_edges_no_broadcast` in a single update.
+    pub fn flush_edge_broadcast(&self, clock: &time::Clock, transport: &dyn NetworkTransport) {
+        let edges = std::mem::take(&mut *self.pending_edge_broadcast.lock());
+        self.broadcast_new_edges(clock, edges, transport);
+    }
+
+    /// Broadcasts the edges newly added to the graph to all peers.
+    fn broadcast_new_edges(
+        &self,
+        clock: &time::Clock,
+        mut edges: Vec<Edge>,
+        transport: &dyn NetworkTransport,
+    ) {
+        // Don't send tombstones during the initial time.
+        // Most of the network is created during this time, which results
+        // in us sending a lot of tombstones to peers.
+        // Later, the amount of new edges is a lot smaller.
+        if let Some(skip_tombstones_duration) = self.config.skip_tombstones {
+            if clock.now() < self.created_at + skip_tombstones_duration {
+                edges.retain(|edge| edge.edge_type() == EdgeState::Active);
+                metrics::EDGE_TOMBSTONE_SENDING_SKIPPED.inc();
+            }
+        }
+        #[cfg(test)]
+        self.config
+            .event_sink
+            .send(crate::peer_manager::peer_manager_actor::Event::EdgesAdded(edges.clone()));
+        self.broadcast_routing_table_update(RoutingTableUpdate::from_edges(edges), transport);
+    }
+
     pub(crate) fn tier2_find_route(
         &self,
         clock: &time::Clock,
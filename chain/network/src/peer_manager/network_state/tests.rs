@@ -0,0 +1,229 @@
+use super::tier1::select_tier1_proxy;
+use super::{NetworkState, PENDING_TIER3_REQUEST_TIMEOUT, RoutedAction, WhitelistNode};
+use crate::config_json::Tier1ProxySelection;
+use crate::network_protocol::testonly as data;
+use crate::network_protocol::{PeerAddr, PeerIdOrHash, RawRoutedMessage, T2MessageBody};
+use crate::peer_manager::peer_store;
+use crate::stats::metrics;
+use crate::store;
+use crate::tcp;
+use crate::testonly::{Rng, make_rng};
+use crate::types::{PartialEncodedChunkRequestMsg, PeerInfo};
+use near_async::messaging::{IntoMultiSender, IntoSender, noop};
+use near_async::time;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::ChunkHash;
+use std::collections::{HashMap, HashSet};
+
+#[test]
+fn whitelist_addr_entry() {
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let peer = data::make_peer_info(rng);
+    let wn = WhitelistNode::from_peer_info(&peer).unwrap();
+    assert!(wn.matches(&peer));
+    // Address is required to match.
+    assert!(!wn.matches(&PeerInfo { addr: Some(data::make_addr(rng)), ..peer.clone() }));
+    assert!(!wn.matches(&PeerInfo { addr: None, ..peer.clone() }));
+    // Account id is required to match, if specified.
+    assert!(
+        !wn.matches(&PeerInfo { account_id: Some(data::make_account_id(rng)), ..peer.clone() })
+    );
+
+    // Entry without an account id matches any account id.
+    let wn = WhitelistNode::from_peer_info(&PeerInfo { account_id: None, ..peer.clone() }).unwrap();
+    assert!(wn.matches(&PeerInfo { account_id: Some(data::make_account_id(rng)), ..peer.clone() }));
+    assert!(wn.matches(&PeerInfo { account_id: None, ..peer }));
+}
+
+#[test]
+fn whitelist_account_entry() {
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let peer = data::make_peer_info(rng);
+    let wn = WhitelistNode::from_peer_info(&PeerInfo { addr: None, ..peer.clone() }).unwrap();
+    assert!(wn.matches(&peer));
+    // Address is not checked.
+    assert!(wn.matches(&PeerInfo { addr: Some(data::make_addr(rng)), ..peer.clone() }));
+    assert!(wn.matches(&PeerInfo { addr: None, ..peer.clone() }));
+    // Peer id is checked.
+    assert!(!wn.matches(&PeerInfo { id: data::make_peer_id(rng), ..peer.clone() }));
+    // Peer which doesn't advertise an account id doesn't match.
+    assert!(!wn.matches(&PeerInfo { account_id: None, ..peer.clone() }));
+    assert!(!wn.matches(&PeerInfo { account_id: Some(data::make_account_id(rng)), ..peer }));
+}
+
+#[test]
+fn whitelist_entry_requires_addr_or_account_id() {
+    let mut rng = make_rng(921853233);
+    let peer = data::make_peer_info(&mut rng);
+    assert!(
+        WhitelistNode::from_peer_info(&PeerInfo { addr: None, account_id: None, ..peer }).is_err()
+    );
+}
+
+#[test]
+fn tier1_proxy_selection_is_deterministic() {
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let ip = data::make_ipv4(rng);
+    let mut proxies = vec![data::make_peer_addr(rng, ip), data::make_peer_addr(rng, ip)];
+    let smaller = proxies.iter().map(|proxy| proxy.peer_id.clone()).min().unwrap();
+
+    let select = |proxies: &[PeerAddr], is_connected: &dyn Fn(&PeerId) -> bool| {
+        select_tier1_proxy(
+            proxies,
+            is_connected,
+            Tier1ProxySelection::LowestPeerId,
+            |_| None,
+            &mut make_rng(1),
+        )
+    };
+
+    // The proxy with the smaller peer id wins regardless of advertisement order.
+    for _ in 0..3 {
+        assert_eq!(select(&proxies, &|_| true), Some(smaller.clone()));
+        proxies.reverse();
+        assert_eq!(select(&proxies, &|_| true), Some(smaller.clone()));
+    }
+
+    // Proxies we are not connected to are skipped.
+    let larger = proxies.iter().map(|proxy| proxy.peer_id.clone()).max().unwrap();
+    assert_eq!(select(&proxies, &|peer_id| peer_id != &smaller), Some(larger));
+    assert_eq!(select(&proxies, &|_| false), None);
+}
+
+#[test]
+fn tier1_proxy_selection_strategies() {
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let ip = data::make_ipv4(rng);
+    let proxies = (0..3).map(|_| data::make_peer_addr(rng, ip)).collect::<Vec<_>>();
+    let mut peer_ids = proxies.iter().map(|proxy| proxy.peer_id.clone()).collect::<Vec<_>>();
+    peer_ids.sort();
+    let disconnected = peer_ids[1].clone();
+    let is_connected = |peer_id: &PeerId| peer_id != &disconnected;
+
+    // Random selection only ever picks connected proxies, and eventually picks each of them.
+    let mut picked = HashSet::new();
+    for _ in 0..100 {
+        let proxy =
+            select_tier1_proxy(&proxies, is_connected, Tier1ProxySelection::Random, |_| None, rng)
+                .unwrap();
+        picked.insert(proxy);
+    }
+    assert_eq!(picked, HashSet::from([peer_ids[0].clone(), peer_ids[2].clone()]));
+
+    // Least recently used selection prefers proxies which were never used, then the ones
+    // used the longest time ago.
+    let mut last_used = HashMap::new();
+    let mut select_lru = |last_used: &HashMap<PeerId, u64>| {
+        select_tier1_proxy(
+            &proxies,
+            is_connected,
+            Tier1ProxySelection::LeastRecentlyUsed,
+            |peer_id| last_used.get(peer_id).copied(),
+            rng,
+        )
+        .unwrap()
+    };
+    assert_eq!(select_lru(&last_used), peer_ids[0]);
+    last_used.insert(peer_ids[0].clone(), 1);
+    assert_eq!(select_lru(&last_used), peer_ids[2]);
+    last_used.insert(peer_ids[2].clone(), 2);
+    assert_eq!(select_lru(&last_used), peer_ids[0]);
+    last_used.insert(peer_ids[0].clone(), 3);
+    assert_eq!(select_lru(&last_used), peer_ids[2]);
+}
+
+fn make_network_state(clock: &time::FakeClock, rng: &mut Rng) -> NetworkState {
+    let chain = data::Chain::make(clock, rng, 3);
+    let network_cfg = chain.make_config(rng);
+    NetworkState::new(
+        &clock.clock(),
+        store::Store::from(near_store::db::TestDB::new()),
+        peer_store::PeerStore::new(&clock.clock(), network_cfg.peer_store.clone()).unwrap(),
+        network_cfg.verify().unwrap(),
+        chain.genesis_id.clone(),
+        noop().into_multi_sender(),
+        noop().into_multi_sender(),
+        noop().into_multi_sender(),
+        noop().into_sender(),
+        noop().into_multi_sender(),
+        vec![],
+        noop().into_multi_sender(),
+        noop().into_sender(),
+    )
+}
+
+#[test]
+fn routing_loop_is_detected_and_dropped() {
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let clock = time::FakeClock::default();
+    let state = make_network_state(&clock, rng);
+
+    let body = T2MessageBody::PartialEncodedChunkRequest(PartialEncodedChunkRequestMsg {
+        chunk_hash: ChunkHash(data::make_hash(rng)),
+        part_ords: vec![],
+        tracking_shards: Default::default(),
+    })
+    .into();
+    let target = PeerIdOrHash::PeerId(data::make_peer_id(rng));
+    let msg = RawRoutedMessage { target, body }.sign(&data::make_secret_key(rng), 5, None);
+    let hash = msg.hash();
+    let prev_hop = data::make_peer_id(rng);
+    let loops_before = metrics::NETWORK_ROUTING_LOOPS_DETECTED.get();
+
+    let forwarded = match state.process_incoming_routed(
+        &clock.clock(),
+        &prev_hop,
+        tcp::Tier::T2,
+        Box::new(msg),
+    ) {
+        RoutedAction::Forward(msg) => msg,
+        _ => panic!("expected the message to be forwarded"),
+    };
+    assert_eq!(metrics::NETWORK_ROUTING_LOOPS_DETECTED.get(), loops_before);
+
+    // The forwarded message travels around the loop and arrives back from another peer,
+    // with its TTL decreased by the hops on the way.
+    let mut looped = forwarded.clone();
+    assert!(looped.decrease_ttl());
+    let next_hop = data::make_peer_id(rng);
+    assert!(matches!(
+        state.process_incoming_routed(&clock.clock(), &next_hop, tcp::Tier::T2, looped),
+        RoutedAction::Dropped
+    ));
+    assert_eq!(metrics::NETWORK_ROUTING_LOOPS_DETECTED.get(), loops_before + 1);
+    // The route back still points at the peer the message first came from.
+    assert_eq!(state.tier2_route_back.lock().get(&hash), Some(&prev_hop));
+
+    // A copy arriving with the TTL it was forwarded with didn't go around a loop.
+    let other_peer = data::make_peer_id(rng);
+    assert!(matches!(
+        state.process_incoming_routed(&clock.clock(), &other_peer, tcp::Tier::T2, forwarded),
+        RoutedAction::Forward(_)
+    ));
+    assert_eq!(metrics::NETWORK_ROUTING_LOOPS_DETECTED.get(), loops_before + 1);
+}
+
+#[test]
+fn unexpected_tier3_connections_are_rejected() {
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let clock = time::FakeClock::default();
+    let state = make_network_state(&clock, rng);
+    let peer_id = data::make_peer_id(rng);
+
+    // No request was sent to the peer.
+    assert!(!state.expect_tier3_connection(&peer_id, clock.now()));
+
+    state.record_tier3_request(&clock.clock(), peer_id.clone());
+    assert!(state.expect_tier3_connection(&peer_id, clock.now()));
+    assert!(!state.expect_tier3_connection(&data::make_peer_id(rng), clock.now()));
+
+    // The request expires after the timeout, even if its entry was not cleaned up yet.
+    clock.advance(PENDING_TIER3_REQUEST_TIMEOUT + time::Duration::seconds(1));
+    assert!(!state.expect_tier3_connection(&peer_id, clock.now()));
+}
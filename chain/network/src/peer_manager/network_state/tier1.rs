@@ -1,5 +1,6 @@
 use crate::accounts_data::{AccountDataCacheSnapshot, LocalAccountData};
 use crate::config::{self, FrozenValidatorConfig};
+use crate::config_json::Tier1ProxySelection;
 use crate::network_protocol::{
     AccountData, PeerAddr, PeerInfo, PeerMessage, SignedAccountData, SyncAccountsData,
 };
@@ -12,10 +13,12 @@ use near_crypto::PublicKey;
 use near_o11y::log_assert;
 use near_primitives::network::PeerId;
 use near_primitives::types::AccountId;
+use rand::Rng;
 use rand::seq::IteratorRandom as _;
 use rand::seq::SliceRandom as _;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
 impl super::NetworkState {
     // Returns a snapshot of ValidatorConfig of this node iff it belongs to TIER1 according to `accounts_data`.
@@ -356,12 +359,34 @@ impl super::NetworkState {
         // In case there is no direct connection and our node is a TIER1 validator, use a proxy.
         // TODO(gprusak): add a check that our node is actually a TIER1 validator.
         let tier1 = self.peers.tier1();
-        for proxy in &data.proxies {
-            if tier1.contains_key(&proxy.peer_id) {
-                return Some(proxy.peer_id.clone());
+        let is_connected = |peer_id: &PeerId| tier1.contains_key(peer_id);
+        let selection = self.config.tier1.proxy_selection;
+        let rng = &mut rand::thread_rng();
+        match selection {
+            Tier1ProxySelection::LeastRecentlyUsed => {
+                let last_used = self.tier1_proxies_last_used.lock();
+                select_tier1_proxy(
+                    &data.proxies,
+                    is_connected,
+                    selection,
+                    |peer_id| last_used.get(peer_id).copied(),
+                    rng,
+                )
+            }
+            Tier1ProxySelection::LowestPeerId | Tier1ProxySelection::Random => {
+                select_tier1_proxy(&data.proxies, is_connected, selection, |_| None, rng)
             }
         }
-        None
+    }
+
+    /// Records that a TIER1 message has been sent through `peer_id`, which was picked
+    /// by `get_tier1_proxy`. Only `Tier1ProxySelection::LeastRecentlyUsed` makes use of it.
+    pub fn record_tier1_proxy_use(&self, peer_id: &PeerId) {
+        if self.config.tier1.proxy_selection != Tier1ProxySelection::LeastRecentlyUsed {
+            return;
+        }
+        let use_id = self.tier1_proxy_uses.fetch_add(1, Ordering::Relaxed) + 1;
+        self.tier1_proxies_last_used.lock().insert(peer_id.clone(), use_id);
     }
 
     /// Finds a TIER1 peer for the given AccountId. Currently used only for OptimisticBlock,
@@ -381,3 +406,31 @@ impl super::NetworkState {
         None
     }
 }
+
+/// Picks the proxy to route a TIER1 message through among the connected ones, according
+/// to `selection`. Candidates are ordered by peer id rather than by advertisement order,
+/// so that `LowestPeerId` makes the same choice on every node for the same set of
+/// connected proxies. `last_used` returns the sequence number of the last use of a proxy
+/// (None if never used); ties are broken by peer id.
+pub(super) fn select_tier1_proxy(
+    proxies: &[PeerAddr],
+    is_connected: impl Fn(&PeerId) -> bool,
+    selection: Tier1ProxySelection,
+    last_used: impl Fn(&PeerId) -> Option<u64>,
+    rng: &mut impl Rng,
+) -> Option<PeerId> {
+    let mut candidates: Vec<&PeerId> = proxies
+        .iter()
+        .map(|proxy| &proxy.peer_id)
+        .filter(|peer_id| is_connected(peer_id))
+        .collect();
+    candidates.sort();
+    let proxy = match selection {
+        Tier1ProxySelection::LowestPeerId => candidates.first(),
+        Tier1ProxySelection::Random => candidates.choose(rng),
+        Tier1ProxySelection::LeastRecentlyUsed => {
+            candidates.iter().min_by_key(|peer_id| last_used(peer_id))
+        }
+    };
+    proxy.map(|peer_id| (*peer_id).clone())
+}
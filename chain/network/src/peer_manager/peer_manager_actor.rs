@@ -4,15 +4,15 @@ use crate::client::{
 };
 use crate::config;
 use crate::debug::{DebugStatus, GetDebugStatus};
+use crate::network_protocol::T1MessageBody;
 use crate::network_protocol::{self, T2MessageBody};
 use crate::network_protocol::{
     Disconnect, Edge, PeerIdOrHash, PeerMessage, Ping, Pong, RawRoutedMessage, StateHeaderRequest,
     StatePartRequest, StateRequestAck,
 };
-use crate::network_protocol::{SyncSnapshotHosts, T1MessageBody};
 use crate::peer_manager::connected_peers::ConnectedPeerState;
 use crate::peer_manager::network_state::{
-    NetworkState, PENDING_TIER3_REQUEST_TIMEOUT, WhitelistNode,
+    NetworkState, PENDING_TIER3_REQUEST_TIMEOUT, RoutedMessageArrival, WhitelistNode,
 };
 use crate::peer_manager::network_transport::{NetworkTransport, PeerTransportStats};
 use crate::peer_manager::peer_store;
@@ -27,7 +27,7 @@ use crate::tcp;
 use crate::types::{
     ConnectedPeerInfo, FullPeerInfo, HighestHeightPeerInfo, KnownProducer, NetworkInfo,
     NetworkRequests, NetworkResponses, PeerChainInfo, PeerInfo, PeerManagerMessageRequest,
-    PeerManagerMessageResponse, PeerManagerSenderForNetwork, PeerType, SetChainInfo,
+    PeerManagerMessageResponse, PeerManagerSenderForNetwork, PeerType, ReasonForBan, SetChainInfo,
     SnapshotHostEvent, SnapshotHostInfo, StateHeaderRequestBody, StatePartRequestBody,
     StateRequestSenderForNetwork, StateSyncEvent, Tier3Request, Tier3RequestBody,
 };
@@ -44,8 +44,8 @@ use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::state_sync::{PartIdOrHeader, StateRequestAckBody};
 use near_primitives::stateless_validation::partial_witness::VersionedPartialEncodedStateWitness;
 use near_primitives::views::{
-    ConnectionInfoView, EdgeView, KnownPeerStateView, NetworkGraphView, PeerStoreView,
-    RecentOutboundConnectionsView, SnapshotHostInfoView, SnapshotHostsView,
+    BanView, ConnectionInfoView, EdgeView, KnownPeerStateView, NetworkGraphView, PeerStoreView,
+    RecentOutboundConnectionsView, RoutingStatusView, SnapshotHostInfoView, SnapshotHostsView,
 };
 use network_protocol::MAX_SHARDS_PER_SNAPSHOT_HOST_INFO;
 use rand::Rng;
@@ -101,6 +101,10 @@ pub(crate) const POLL_CONNECTION_STORE_INTERVAL: time::Duration = time::Duration
 /// The length of time that a Tier3 connection is allowed to idle before it is stopped
 const TIER3_IDLE_TIMEOUT: time::Duration = time::Duration::seconds(15);
 
+/// How long a peer which stalled our header sync stays banned. Stalling is often caused by
+/// a slow or overloaded peer rather than a malicious one, so the ban is kept short.
+const TRANSIENT_BAN_DURATION: time::Duration = time::Duration::minutes(5);
+
 /// Actor that manages peers connections.
 pub struct PeerManagerActor {
     pub(crate) clock: time::Clock,
@@ -128,6 +132,9 @@ pub enum Event {
     PeerManagerStarted,
     ServerStarted,
     RoutedMessageDropped,
+    // Reported when a routed message addressed to this node arrives, together with
+    // the tier over which the first copy of that message arrived.
+    RoutedMessageArrival(RoutedMessageArrival),
     AccountsAdded(Vec<AnnounceAccount>),
     EdgesAdded(Vec<Edge>),
     Ping(Ping),
@@ -146,6 +153,8 @@ pub enum Event {
     MessageProcessed(tcp::Tier, PeerMessage),
     // Reported when a reconnect loop is spawned.
     ReconnectLoopSpawned(PeerInfo),
+    // Reported when a reconnect loop waits before the next attempt to connect to the peer.
+    ReconnectBackoff(PeerId, time::Duration),
     // Reported when a handshake has been started.
     HandshakeStarted(crate::peer::peer_actor::HandshakeStartedEvent),
     // Reported when a handshake has been successfully completed.
@@ -243,6 +252,9 @@ impl messaging::Actor for PeerManagerActor {
             async move {
                 loop {
                     interval.tick(&clock).await;
+                    // Drop the attempts which waited for too long, the peers have likely
+                    // reconnected in the meantime.
+                    state.prune_pending_reconnect(&clock, state.config.pending_reconnect_max_age);
                     // Poll the NetworkState for all pending reconnect attempts
                     let pending_reconnect = state.poll_pending_reconnect();
                     // Spawn a separate reconnect loop for each pending reconnect attempt
@@ -352,7 +364,7 @@ impl PeerManagerActor {
         tracing::debug!(target: "network",
                len = peer_store.len(),
                boot_nodes = config.peer_store.boot_nodes.len(),
-               banned = peer_store.count_banned(),
+               banned = peer_store.count_banned(&clock),
                "found known peers");
         tracing::debug!(target: "network", blacklist = ?config.peer_store.blacklist);
         let whitelist_nodes = {
@@ -786,6 +798,7 @@ impl PeerManagerActor {
             tier1_connections: t1_infos,
             num_connected_peers: num_connected,
             peer_max_count: self.state.config.max_num_peers,
+            inbound_draining: self.state.is_inbound_draining(),
             highest_height_peers: self.highest_height_peers(),
             sent_bytes_per_sec: sent_total,
             received_bytes_per_sec: recv_total,
@@ -854,7 +867,10 @@ impl PeerManagerActor {
                     if let Some(peer_id) =
                         self.state.get_tier1_proxy_for_account_id(&target_account)
                     {
-                        self.transport.send_message(tcp::Tier::T1, peer_id, msg.clone());
+                        if self.transport.send_message(tcp::Tier::T1, peer_id.clone(), msg.clone())
+                        {
+                            self.state.record_tier1_proxy_use(&peer_id);
+                        }
                     }
                 }
                 NetworkResponses::NoResponse
@@ -893,7 +909,7 @@ impl PeerManagerActor {
             NetworkRequests::StateRequestHeader { shard_id, sync_hash, sync_prev_prev_hash } => {
                 // The node needs to include its own public address in the request
                 // so that the response can be sent over a direct Tier3 connection.
-                let Some(addr) = *self.state.my_public_addr.read() else {
+                let Some(addr) = self.state.my_public_addr() else {
                     return NetworkResponses::MyPublicAddrNotKnown;
                 };
 
@@ -921,7 +937,7 @@ impl PeerManagerActor {
                     },
                 );
 
-                self.state.pending_tier3_requests.insert(peer_id.clone(), self.clock.now());
+                self.state.record_tier3_request(&self.clock, peer_id.clone());
                 if !self.state.send_message_to_peer(
                     &self.clock,
                     tcp::Tier::T2,
@@ -942,7 +958,7 @@ impl PeerManagerActor {
             } => {
                 // The node needs to include its own public address in the request
                 // so that the response can be sent over a direct Tier3 connection.
-                let Some(addr) = *self.state.my_public_addr.read() else {
+                let Some(addr) = self.state.my_public_addr() else {
                     return NetworkResponses::MyPublicAddrNotKnown;
                 };
 
@@ -953,7 +969,15 @@ impl PeerManagerActor {
                     shard_id,
                     part_id,
                 ) else {
-                    tracing::debug!(target: "network", %shard_id, ?sync_hash, ?part_id, "no snapshot hosts available");
+                    tracing::debug!(
+                        target: "network",
+                        %shard_id,
+                        ?sync_hash,
+                        ?part_id,
+                        num_hosts = self.state.num_snapshot_hosts(),
+                        num_shard_hosts = self.state.snapshot_hosts_for_shard(shard_id).len(),
+                        "no snapshot hosts available"
+                    );
                     return NetworkResponses::NoDestinationsAvailable;
                 };
 
@@ -971,7 +995,7 @@ impl PeerManagerActor {
                     },
                 );
 
-                self.state.pending_tier3_requests.insert(peer_id.clone(), self.clock.now());
+                self.state.record_tier3_request(&self.clock, peer_id.clone());
                 if !self.state.send_message_to_peer(
                     &self.clock,
                     tcp::Tier::T2,
@@ -1078,16 +1102,30 @@ impl PeerManagerActor {
                     &self.state.config.node_key,
                 ));
 
-                // Insert our info to our own cache.
-                self.state.snapshot_hosts.insert_skip_verify(snapshot_host_info.clone());
-
-                self.transport.broadcast_message(Arc::new(PeerMessage::SyncSnapshotHosts(
-                    SyncSnapshotHosts { hosts: vec![snapshot_host_info] },
-                )));
+                // Insert our info to our own cache and broadcast it, unless already advertised.
+                if let Err(err) =
+                    self.state.advertise_local_snapshot(snapshot_host_info, &*self.transport)
+                {
+                    tracing::error!(target: "network", ?err, "failed to advertise the local snapshot");
+                }
                 NetworkResponses::NoResponse
             }
             NetworkRequests::BanPeer { peer_id, ban_reason } => {
-                self.state.disconnect_and_ban(&self.clock, &peer_id, ban_reason, &*self.transport);
+                match ban_reason {
+                    ReasonForBan::ProvidedNotEnoughHeaders => self.state.disconnect_and_ban_for(
+                        &self.clock,
+                        &peer_id,
+                        ban_reason,
+                        TRANSIENT_BAN_DURATION,
+                        &*self.transport,
+                    ),
+                    _ => self.state.disconnect_and_ban(
+                        &self.clock,
+                        &peer_id,
+                        ban_reason,
+                        &*self.transport,
+                    ),
+                }
                 NetworkResponses::NoResponse
             }
             NetworkRequests::AnnounceAccount(announce_account) => {
@@ -1469,6 +1507,10 @@ impl PeerManagerActor {
                 });
                 PeerManagerMessageResponse::AdvertiseTier1Proxies
             }
+            PeerManagerMessageRequest::SetInboundDraining(draining) => {
+                self.state.set_inbound_draining(draining);
+                PeerManagerMessageResponse::SetInboundDraining
+            }
             // TEST-ONLY
             PeerManagerMessageRequest::FetchRoutingTable => {
                 PeerManagerMessageResponse::FetchRoutingTable(self.state.graph.routing_table.info())
@@ -1679,22 +1721,48 @@ impl messaging::Handler<GetDebugStatus, DebugStatus> for PeerManagerActor {
                         -a.last_seen,
                     )
                 });
-                DebugStatus::PeerStore(PeerStoreView { peer_states: peer_states_view })
-            }
-            GetDebugStatus::Graph => DebugStatus::Graph(NetworkGraphView {
-                edges: self
+                let bans = self
                     .state
-                    .graph
-                    .load()
-                    .edges
-                    .values()
-                    .map(|edge| {
-                        let key = edge.key();
-                        EdgeView { peer0: key.0.clone(), peer1: key.1.clone(), nonce: edge.nonce() }
+                    .peer_store
+                    .list_bans()
+                    .into_iter()
+                    .map(|(peer_id, reason, expires_at)| BanView {
+                        peer_id,
+                        reason: format!("{reason:?}"),
+                        expires_at: expires_at.unix_timestamp(),
                     })
-                    .collect(),
-                next_hops: (*self.state.graph.routing_table.info().next_hops).clone(),
-            }),
+                    .collect();
+                DebugStatus::PeerStore(PeerStoreView { peer_states: peer_states_view, bans })
+            }
+            GetDebugStatus::Graph => {
+                let routing_status = self.state.routing_status();
+                DebugStatus::Graph(NetworkGraphView {
+                    node_id: self.state.node_id(),
+                    node_key_fingerprint: self.state.node_key_fingerprint(),
+                    edges: self
+                        .state
+                        .graph
+                        .load()
+                        .edges
+                        .values()
+                        .map(|edge| {
+                            let key = edge.key();
+                            EdgeView {
+                                peer0: key.0.clone(),
+                                peer1: key.1.clone(),
+                                nonce: edge.nonce(),
+                            }
+                        })
+                        .collect(),
+                    next_hops: (*self.state.graph.routing_table.info().next_hops).clone(),
+                    routing_status: RoutingStatusView {
+                        active_edges: routing_status.active_edges,
+                        removed_edges: routing_status.removed_edges,
+                        reachable_peers: routing_status.reachable_peers,
+                        local_edges: routing_status.local_edges,
+                    },
+                })
+            }
             GetDebugStatus::RecentOutboundConnections => {
                 DebugStatus::RecentOutboundConnections(RecentOutboundConnectionsView {
                     recent_outbound_connections: self
@@ -15,13 +15,13 @@ use im::hashmap::Entry;
 use im::{HashMap, HashSet};
 use lru::LruCache;
 use near_async::time;
+use near_primitives::genesis::GenesisId;
 use near_primitives::network::PeerId;
 use parking_lot::Mutex;
 use rand::seq::IteratorRandom;
 use rand::thread_rng;
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
-use std::ops::Not;
 
 #[cfg(test)]
 mod testonly;
@@ -85,6 +85,9 @@ struct Inner {
     // It can happens that some peers don't have known address, so
     // they will not be present in this list, otherwise they will be present.
     addr_peers: HashMap<SocketAddr, VerifiedPeer>,
+    // Expiration times of the temporary bans, see `PeerStore::peer_ban_for`.
+    // Peers banned without an entry here are unbanned after config.ban_window.
+    ban_expiry: HashMap<PeerId, time::Utc>,
 }
 
 impl Inner {
@@ -148,8 +151,16 @@ impl Inner {
         }
     }
 
+    /// Checks whether the peer with the given status is banned. A temporary ban is not in
+    /// effect once it has expired, even if the peer hasn't been unbanned by `update` yet.
+    fn is_banned(&self, now: time::Utc, peer_id: &PeerId, status: &KnownPeerStatus) -> bool {
+        status.is_banned()
+            && self.ban_expiry.get(peer_id).is_none_or(|expires_at| now < *expires_at)
+    }
+
     // cspell:words unban unbans
     fn peer_unban(&mut self, peer_id: &PeerId) -> anyhow::Result<()> {
+        self.ban_expiry.remove(peer_id);
         if let Some(peer_state) = self.peer_states.get_mut(peer_id) {
             peer_state.status = KnownPeerStatus::NotConnected;
         } else {
@@ -161,6 +172,7 @@ impl Inner {
     /// Deletes peers from the internal cache
     fn delete_peers(&mut self, peer_ids: &[PeerId]) {
         for peer_id in peer_ids {
+            self.ban_expiry.remove(peer_id);
             if let Some(peer_state) = self.peer_states.pop(peer_id)
                 && let Some(addr) = peer_state.peer_info.addr
             {
@@ -239,11 +251,17 @@ impl Inner {
         self.delete_peers(&to_remove);
     }
 
+    /// Time at which the ban of the peer, banned at `ban_time`, expires.
+    fn ban_expires_at(&self, peer_id: &PeerId, ban_time: time::Utc) -> time::Utc {
+        self.ban_expiry.get(peer_id).copied().unwrap_or(ban_time + self.config.ban_window)
+    }
+
     fn unban(&mut self, now: time::Utc) {
         let mut to_unban = vec![];
         for (peer_id, peer_state) in &self.peer_states {
             if let KnownPeerStatus::Banned(_, ban_time) = peer_state.status {
-                if now < ban_time + self.config.ban_window {
+                let expires_at = self.ban_expires_at(peer_id, ban_time);
+                if now < expires_at {
                     continue;
                 }
                 tracing::info!(target: "network", unbanned = ?peer_id, ?ban_time, "unbanning a peer");
@@ -274,7 +292,7 @@ impl Inner {
     }
 
     /// Cleans up the state of the PeerStore, due to passing time.
-    /// * it unbans a peer if config.ban_window has passed
+    /// * it unbans a peer if config.ban_window (or the duration of a temporary ban) has passed
     /// * it updates KnownPeerStatus.last_seen of the connected peers
     /// * it removes peers which were not seen for config.peer_expiration_duration
     /// This function should be called periodically.
@@ -337,8 +355,13 @@ impl PeerStore {
             }
         }
 
-        let inner =
-            Inner { config, boot_nodes, peer_states: peer_id_2_state, addr_peers: addr_2_peer };
+        let inner = Inner {
+            config,
+            boot_nodes,
+            peer_states: peer_id_2_state,
+            addr_peers: addr_2_peer,
+            ban_expiry: HashMap::new(),
+        };
         Ok(PeerStore(Mutex::new(inner)))
     }
 
@@ -350,12 +373,35 @@ impl PeerStore {
         self.0.lock().peer_states.len()
     }
 
-    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
-        self.0.lock().peer_states.get(peer_id).is_some_and(|s| s.status.is_banned())
+    /// Checks whether the peer is banned. A temporary ban is not in effect once it has
+    /// expired, even if the peer hasn't been unbanned by `update` yet.
+    pub fn is_banned(&self, clock: &time::Clock, peer_id: &PeerId) -> bool {
+        let mut inner = self.0.lock();
+        let Some(status) = inner.peer_states.get(peer_id).map(|s| s.status.clone()) else {
+            return false;
+        };
+        inner.is_banned(clock.now_utc(), peer_id, &status)
+    }
+
+    pub fn count_banned(&self, clock: &time::Clock) -> usize {
+        let inner = self.0.lock();
+        let now = clock.now_utc();
+        inner.peer_states.iter().filter(|(id, st)| inner.is_banned(now, id, &st.status)).count()
     }
 
-    pub fn count_banned(&self) -> usize {
-        self.0.lock().peer_states.iter().filter(|(_, st)| st.status.is_banned()).count()
+    /// Lists the banned peers, together with the reason and the time the ban expires.
+    pub fn list_bans(&self) -> Vec<(PeerId, ReasonForBan, time::Utc)> {
+        let inner = self.0.lock();
+        inner
+            .peer_states
+            .iter()
+            .filter_map(|(peer_id, state)| match state.status {
+                KnownPeerStatus::Banned(reason, banned_at) => {
+                    Some((peer_id.clone(), reason, inner.ban_expires_at(peer_id, banned_at)))
+                }
+                _ => None,
+            })
+            .collect()
     }
 
     pub fn update(&self, clock: &time::Clock) {
@@ -416,6 +462,33 @@ impl PeerStore {
         peer_state.last_seen = clock.now_utc();
     }
 
+    /// Records the genesis reported by the peer during a handshake.
+    /// The peer is added to the store if it is not known yet, since an inbound peer
+    /// on a different genesis is rejected before it is ever stored as connected.
+    pub fn peer_genesis_id(
+        &self,
+        clock: &time::Clock,
+        peer_info: &PeerInfo,
+        genesis_id: GenesisId,
+    ) {
+        let mut inner = self.0.lock();
+        inner.add_peer(clock, peer_info.clone(), TrustLevel::Indirect);
+        if let Some(peer_state) = inner.peer_states.peek_mut(&peer_info.id) {
+            peer_state.genesis_id = Some(genesis_id);
+        }
+    }
+
+    /// Checks whether the peer is known to be on a genesis different from `genesis_id`.
+    /// Peers with unknown genesis are not considered to be on a different genesis.
+    pub fn is_on_other_genesis(&self, peer_id: &PeerId, genesis_id: &GenesisId) -> bool {
+        self.0
+            .lock()
+            .peer_states
+            .peek(peer_id)
+            .and_then(|s| s.genesis_id.as_ref())
+            .is_some_and(|peer_genesis_id| peer_genesis_id != genesis_id)
+    }
+
     pub fn peer_ban(
         &self,
         clock: &time::Clock,
@@ -424,13 +497,37 @@ impl PeerStore {
     ) -> anyhow::Result<()> {
         tracing::warn!(target: "network", %peer_id, ?ban_reason, "banning peer");
         let mut inner = self.0.lock();
+        let now = clock.now_utc();
         if let Some(peer_state) = inner.peer_states.get_mut(peer_id) {
-            let now = clock.now_utc();
             peer_state.last_seen = now;
             peer_state.status = KnownPeerStatus::Banned(ban_reason, now);
         } else {
             bail!("Peer {} is missing in the peer store", peer_id);
         }
+        // A permanent ban replaces a temporary one.
+        inner.ban_expiry.remove(peer_id);
+        Ok(())
+    }
+
+    /// Bans the peer temporarily: the ban expires after `duration`, instead of
+    /// after config.ban_window.
+    pub fn peer_ban_for(
+        &self,
+        clock: &time::Clock,
+        peer_id: &PeerId,
+        ban_reason: ReasonForBan,
+        duration: time::Duration,
+    ) -> anyhow::Result<()> {
+        tracing::warn!(target: "network", %peer_id, ?ban_reason, ?duration, "temporarily banning peer");
+        let mut inner = self.0.lock();
+        let now = clock.now_utc();
+        if let Some(peer_state) = inner.peer_states.get_mut(peer_id) {
+            peer_state.last_seen = now;
+            peer_state.status = KnownPeerStatus::Banned(ban_reason, now);
+        } else {
+            bail!("Peer {} is missing in the peer store", peer_id);
+        }
+        inner.ban_expiry.insert(peer_id.clone(), now + duration);
         Ok(())
     }
 
@@ -476,10 +573,10 @@ impl PeerStore {
     }
 
     /// Return healthy known peers up to given amount.
-    pub fn healthy_peers(&self, max_count: usize) -> Vec<PeerInfo> {
-        self.0
-            .lock()
-            .find_peers(|p| matches!(p.status, KnownPeerStatus::Banned(_, _)).not(), max_count)
+    pub fn healthy_peers(&self, clock: &time::Clock, max_count: usize) -> Vec<PeerInfo> {
+        let inner = self.0.lock();
+        let now = clock.now_utc();
+        inner.find_peers(|p| !inner.is_banned(now, &p.peer_info.id, &p.status), max_count)
     }
 
     /// Adds peers we’ve learned about from other peers.
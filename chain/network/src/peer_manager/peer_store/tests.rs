@@ -50,9 +50,96 @@ fn ban_store() {
     let peer_store =
         PeerStore::new(&clock.clock(), make_config(&boot_nodes, Blacklist::default(), false))
             .unwrap();
-    assert_eq!(peer_store.healthy_peers(3).len(), 2);
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 2);
     peer_store.peer_ban(&clock.clock(), &peer_info_to_ban.id, ReasonForBan::Abusive).unwrap();
-    assert_eq!(peer_store.healthy_peers(3).len(), 1);
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 1);
+}
+
+#[test]
+fn temporary_ban_expires() {
+    let clock = time::FakeClock::default();
+    let peer_info_a = gen_peer_info(0);
+    let peer_info_to_ban = gen_peer_info(1);
+    let boot_nodes = vec![peer_info_a, peer_info_to_ban.clone()];
+
+    let config = make_config(&boot_nodes, Blacklist::default(), false);
+    let ban_window = config.ban_window;
+    let peer_store = PeerStore::new(&clock.clock(), config).unwrap();
+    let id = &peer_info_to_ban.id;
+    peer_store.peer_ban_for(&clock.clock(), id, ReasonForBan::Abusive, ban_window * 10).unwrap();
+    assert!(peer_store.is_banned(&clock.clock(), id));
+
+    // The temporary ban outlives ban_window.
+    clock.advance(ban_window * 5);
+    peer_store.update(&clock.clock());
+    assert!(peer_store.is_banned(&clock.clock(), id));
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 1);
+
+    // The ban is not in effect once it expires, even before the peer store is updated.
+    clock.advance(ban_window * 5);
+    assert!(!peer_store.is_banned(&clock.clock(), id));
+    assert_eq!(peer_store.count_banned(&clock.clock()), 0);
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 2);
+    peer_store.update(&clock.clock());
+    assert!(!peer_store.is_banned(&clock.clock(), id));
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 2);
+}
+
+#[test]
+fn permanent_ban_replaces_temporary_ban() {
+    let clock = time::FakeClock::default();
+    let peer_info_a = gen_peer_info(0);
+    let peer_info_to_ban = gen_peer_info(1);
+    let boot_nodes = vec![peer_info_a, peer_info_to_ban.clone()];
+
+    let config = make_config(&boot_nodes, Blacklist::default(), false);
+    let ban_window = config.ban_window;
+    let peer_store = PeerStore::new(&clock.clock(), config).unwrap();
+    let id = &peer_info_to_ban.id;
+    peer_store.peer_ban_for(&clock.clock(), id, ReasonForBan::Abusive, ban_window / 2).unwrap();
+    peer_store.peer_ban(&clock.clock(), id, ReasonForBan::Abusive).unwrap();
+
+    // The temporary ban would have expired by now, the permanent one lasts ban_window.
+    clock.advance(ban_window / 2);
+    peer_store.update(&clock.clock());
+    assert!(peer_store.is_banned(&clock.clock(), id));
+    assert_eq!(peer_store.count_banned(&clock.clock()), 1);
+
+    clock.advance(ban_window / 2);
+    peer_store.update(&clock.clock());
+    assert!(!peer_store.is_banned(&clock.clock(), id));
+}
+
+#[test]
+fn list_bans() {
+    let clock = time::FakeClock::default();
+    let peer_infos: Vec<_> = (0..3).map(gen_peer_info).collect();
+    let config = make_config(&peer_infos, Blacklist::default(), false);
+    let ban_window = config.ban_window;
+    let peer_store = PeerStore::new(&clock.clock(), config).unwrap();
+    assert_eq!(peer_store.list_bans(), vec![]);
+
+    let banned_at = clock.now_utc();
+    peer_store.peer_ban(&clock.clock(), &peer_infos[0].id, ReasonForBan::Abusive).unwrap();
+    let ban_duration = ban_window / 2;
+    peer_store
+        .peer_ban_for(
+            &clock.clock(),
+            &peer_infos[1].id,
+            ReasonForBan::InvalidSignature,
+            ban_duration,
+        )
+        .unwrap();
+
+    let mut bans = peer_store.list_bans();
+    bans.sort_by_key(|(_, _, expires_at)| *expires_at);
+    assert_eq!(
+        bans,
+        vec![
+            (peer_infos[1].id.clone(), ReasonForBan::InvalidSignature, banned_at + ban_duration),
+            (peer_infos[0].id.clone(), ReasonForBan::Abusive, banned_at + ban_window),
+        ]
+    );
 }
 
 #[test]
@@ -433,6 +433,21 @@ impl ActorHandler {
         .await
     }
 
+    pub async fn disconnect_and_ban_for(
+        &self,
+        clock: &time::Clock,
+        peer_id: &PeerId,
+        reason: ReasonForBan,
+        duration: time::Duration,
+    ) {
+        let clock = clock.clone();
+        let peer_id = peer_id.clone();
+        self.with_state_and_transport(move |s, transport| async move {
+            s.disconnect_and_ban_for(&clock, &peer_id, reason, duration, transport.as_ref())
+        })
+        .await
+    }
+
     pub async fn peer_store_update(&self, clock: &time::Clock) {
         let clock = clock.clone();
         self.with_state(move |s| async move { s.peer_store.update(&clock) }).await;
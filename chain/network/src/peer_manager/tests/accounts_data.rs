@@ -157,6 +157,48 @@ async fn gradual_epoch_change() {
     }
 }
 
+// New accounts data should reach all the peers, even if it is broadcast in
+// batches smaller than the number of peers.
+#[tokio::test]
+async fn broadcast_in_batches() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut cfg = chain.make_config(rng);
+    cfg.accounts_data_broadcast_batch_size = 2;
+    let center = start_pm(clock.clock(), TestDB::new(), cfg, chain.clone()).await;
+    let mut leaves = vec![];
+    for _ in 0..5 {
+        leaves.push(
+            start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await,
+        );
+    }
+
+    tracing::info!(target:"test", "connect the leaves to the center");
+    for leaf in &leaves {
+        leaf.connect_to(&center.peer_info(), tcp::Tier::T2).await;
+    }
+
+    tracing::info!(target:"test", "advertise proxies of the center");
+    let mut cfgs = vec![&center.cfg];
+    cfgs.extend(leaves.iter().map(|pm| &pm.cfg));
+    let chain_info = testonly::make_chain_info(&chain, &cfgs);
+    center.set_chain_info(chain_info.clone()).await;
+    for leaf in &leaves {
+        leaf.set_chain_info(chain_info.clone()).await;
+    }
+    let want: HashSet<_> =
+        center.tier1_advertise_proxies(&clock.clock()).await.into_iter().collect();
+
+    tracing::info!(target:"test", "wait for the data to reach all the leaves");
+    for leaf in &leaves {
+        leaf.wait_for_accounts_data(&want).await;
+    }
+}
+
 // Test is expected to take ~5s.
 // Test with 20 peer managers connected in layers:
 // - 1st 5 and 2nd 5 are connected in full bipartite graph.
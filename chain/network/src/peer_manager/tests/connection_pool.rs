@@ -1,10 +1,11 @@
 use crate::config::SocketOptions;
 use crate::network_protocol::testonly as data;
-use crate::network_protocol::{Disconnect, PeerMessage};
+use crate::network_protocol::{Disconnect, HandshakeFailureReason, PeerMessage};
 use crate::network_protocol::{Handshake, OwnedAccount, PartialEdgeInfo};
 use crate::peer::peer_actor::ClosingReason;
 use crate::peer_manager;
 use crate::peer_manager::connection;
+use crate::peer_manager::network_state::PENDING_TIER3_REQUEST_TIMEOUT;
 use crate::peer_manager::peer_manager_actor::Event;
 use crate::peer_manager::tcp_transport::LIMIT_PENDING_PEERS;
 use crate::private_messages::RegisterPeerError;
@@ -14,6 +15,7 @@ use crate::testonly::stream::Stream;
 use crate::types::Edge;
 use near_async::time;
 use near_o11y::testonly::init_test_logger;
+use near_primitives::genesis::GenesisId;
 use near_primitives::version::PROTOCOL_VERSION;
 use std::sync::Arc;
 
@@ -140,6 +142,74 @@ async fn expected_tier3_accepted() {
         .await;
 }
 
+// Verify that an inbound Tier3 connection is rejected when the state sync request we sent
+// to that peer has already expired.
+#[tokio::test]
+async fn expired_tier3_rejected() {
+    init_test_logger();
+    let mut rng = make_rng(921853234);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    let cfg = chain.make_config(rng);
+    let peer_id = cfg.node_id();
+
+    // Simulate having sent a state sync request to this peer a while ago.
+    let now = clock.clock().now();
+    pm.with_state(move |s| async move {
+        s.pending_tier3_requests.insert(peer_id, now);
+    })
+    .await;
+    clock.advance(PENDING_TIER3_REQUEST_TIMEOUT + time::Duration::seconds(1));
+
+    let stream = tcp::Stream::connect(&pm.peer_info(), tcp::Tier::T3, &SocketOptions::default())
+        .await
+        .unwrap();
+    let stream_id = stream.id();
+    let port = stream.local_addr.port();
+    let mut events = pm.events.from_now();
+    let mut stream = Stream::new(stream);
+    stream
+        .write(&PeerMessage::Tier3Handshake(Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            oldest_supported_version: PROTOCOL_VERSION,
+            sender_peer_id: cfg.node_id(),
+            target_peer_id: pm.cfg.node_id(),
+            sender_listen_port: Some(port),
+            sender_chain_info: chain.get_peer_chain_info(),
+            partial_edge_info: PartialEdgeInfo::new(
+                &cfg.node_id(),
+                &pm.cfg.node_id(),
+                Edge::create_fresh_nonce(&clock.clock()),
+                &cfg.node_key,
+            ),
+            owned_account: None,
+        }))
+        .await;
+    let reason = events
+        .recv_until(|ev| match ev {
+            Event::ConnectionClosed(ev) if ev.stream_id == stream_id => Some(ev.reason),
+            Event::HandshakeCompleted(ev) if ev.stream_id == stream_id => {
+                panic!("PeerManager accepted Tier3 handshake for an expired request")
+            }
+            _ => None,
+        })
+        .await;
+    assert_eq!(
+        ClosingReason::RejectedByPeerManager(RegisterPeerError::UnexpectedTier3Connection),
+        reason,
+    );
+}
+
 // Verify that duplicate handshakes on established sessions close the connection immediately,
 // so the keepalive attempt itself terminates the session.
 #[tokio::test]
@@ -464,6 +534,63 @@ async fn owned_account_mismatch() {
     assert_eq!(ClosingReason::OwnedAccountMismatch, reason);
 }
 
+// Verify that the genesis of an inbound peer on a different chain is recorded,
+// even though the peer was not known to the peer store before.
+#[tokio::test]
+async fn inbound_genesis_mismatch_recorded() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    let stream = tcp::Stream::connect(&pm.peer_info(), tcp::Tier::T2, &SocketOptions::default())
+        .await
+        .unwrap();
+    let port = stream.local_addr.port();
+    let mut stream = Stream::new(stream);
+    let cfg = chain.make_config(rng);
+    let peer_id = cfg.node_id();
+    let mut chain_info = chain.get_peer_chain_info();
+    chain_info.genesis_id =
+        GenesisId { chain_id: "other".to_string(), hash: chain.genesis_id.hash };
+    stream
+        .write(&PeerMessage::Tier2Handshake(Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            oldest_supported_version: PROTOCOL_VERSION,
+            sender_peer_id: peer_id.clone(),
+            target_peer_id: pm.cfg.node_id(),
+            sender_listen_port: Some(port),
+            sender_chain_info: chain_info,
+            partial_edge_info: PartialEdgeInfo::new(
+                &peer_id,
+                &pm.cfg.node_id(),
+                Edge::create_fresh_nonce(&clock.clock()),
+                &cfg.node_key,
+            ),
+            owned_account: None,
+        }))
+        .await;
+    match stream.read().await.unwrap() {
+        PeerMessage::HandshakeFailure(_, HandshakeFailureReason::GenesisMismatch(_)) => {}
+        msg => panic!("unexpected message {msg:?}"),
+    }
+
+    let genesis_id = chain.genesis_id.clone();
+    let on_other_genesis = pm
+        .with_state(move |s| async move { s.peer_store.is_on_other_genesis(&peer_id, &genesis_id) })
+        .await;
+    assert!(on_other_genesis);
+}
+
 #[tokio::test]
 async fn owned_account_conflict() {
     init_test_logger();
@@ -653,3 +780,107 @@ async fn invalid_edge() {
         assert!(still_pending, "pending_tier3_requests entry was consumed by a failed edge check");
     }
 }
+
+// Verify that new inbound connections are rejected while draining inbound connections,
+// that the existing connections are kept, and that draining can be stopped at runtime.
+#[tokio::test]
+async fn inbound_draining() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    let conn1 = pm
+        .start_inbound(chain.clone(), chain.make_config(rng))
+        .await
+        .handshake(&clock.clock())
+        .await;
+
+    tracing::info!(target:"test", "start draining inbound connections");
+    pm.with_state(|s| async move { s.set_inbound_draining(true) }).await;
+    let reason = pm
+        .start_inbound(chain.clone(), chain.make_config(rng))
+        .await
+        .manager_fail_handshake(&clock.clock())
+        .await;
+    assert_eq!(
+        reason,
+        ClosingReason::RejectedByPeerManager(RegisterPeerError::ConnectionLimitExceeded)
+    );
+    let id1 = conn1.cfg.id();
+    assert!(
+        pm.with_state(move |s| async move { s.peers.is_connected_on_tier(&id1, tcp::Tier::T2) })
+            .await
+    );
+
+    tracing::info!(target:"test", "stop draining inbound connections");
+    pm.with_state(|s| async move { s.set_inbound_draining(false) }).await;
+    pm.start_inbound(chain.clone(), chain.make_config(rng)).await.handshake(&clock.clock()).await;
+}
+
+#[tokio::test]
+async fn my_public_addr() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    // The address is not known before discovery.
+    assert_eq!(None, pm.with_state(|s| async move { s.my_public_addr() }).await);
+
+    let addr = data::make_addr(rng);
+    let got = pm
+        .with_state(move |s| async move {
+            *s.my_public_addr.write() = Some(addr);
+            s.my_public_addr()
+        })
+        .await;
+    assert_eq!(Some(addr), got);
+}
+
+#[tokio::test]
+async fn connection_ages() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    let conn = pm
+        .start_inbound(chain.clone(), chain.make_config(rng))
+        .await
+        .handshake(&clock.clock())
+        .await;
+    let age = time::Duration::seconds(42);
+    clock.advance(age);
+
+    let ages_clock = clock.clock();
+    let ages = pm.with_state(move |s| async move { s.connection_ages(&ages_clock) }).await;
+    assert_eq!(ages, vec![(conn.cfg.id(), age)]);
+}
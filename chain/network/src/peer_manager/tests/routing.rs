@@ -3,20 +3,26 @@ use crate::broadcast;
 use crate::config::{NetworkConfig, SocketOptions};
 use crate::network_protocol::T2MessageBody;
 use crate::network_protocol::testonly as data;
+use crate::network_protocol::{EdgeState, PeerIdOrHash};
 use crate::network_protocol::{Ping, Pong, RoutingTableUpdate};
 use crate::peer;
 use crate::peer::peer_actor::{
     ClosingReason, ConnectionClosedEvent, DROP_DUPLICATED_MESSAGES_PERIOD,
 };
 use crate::peer_manager;
+use crate::peer_manager::network_state::{EdgesWithSource, RoutingStatus};
 use crate::peer_manager::peer_manager_actor::Event;
 use crate::peer_manager::testonly::start as start_pm;
 use crate::private_messages::RegisterPeerError;
+use crate::routing::routing_table_view::FindRouteError;
+use crate::stats::metrics;
 use crate::tcp;
 use crate::testonly::{Rng, abort_on_panic, make_rng};
 use crate::types::{Edge, PeerMessage};
-use crate::types::{PeerInfo, ReasonForBan};
+use crate::types::{PeerInfo, ReasonForBan, RoutedMessageDroppedEvent};
+use near_async::messaging::Sender;
 use near_async::{ActorSystem, time};
+use near_primitives::genesis::GenesisId;
 use near_primitives::network::PeerId;
 use near_store::db::TestDB;
 use pretty_assertions::assert_eq;
@@ -97,6 +103,44 @@ async fn three_nodes_path() {
     .await;
 }
 
+// test reachable peers for three nodes in a line
+#[tokio::test]
+async fn reachable_peers() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    tracing::info!(target:"test", "connect three nodes in a line");
+    let pm0 = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let pm1 = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let pm2 = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+
+    pm0.connect_to(&pm1.peer_info(), tcp::Tier::T2).await;
+    pm1.connect_to(&pm2.peer_info(), tcp::Tier::T2).await;
+
+    let id1 = pm1.cfg.node_id();
+    let id2 = pm2.cfg.node_id();
+    pm0.wait_for_routing_table(&[
+        (id1.clone(), vec![id1.clone()]),
+        (id2.clone(), vec![id1.clone()]),
+    ])
+    .await;
+
+    tracing::info!(target:"test", "both the direct and the indirect peer should be reachable");
+    let (got, count) =
+        pm0.with_state(|s| async move { (s.reachable_peers(), s.num_reachable_peers()) }).await;
+    assert_eq!(HashSet::from([id1, id2]), got.into_iter().collect());
+    assert_eq!(2, count);
+
+    tracing::info!(target:"test", "routing status should reflect the line topology");
+    assert_eq!(
+        RoutingStatus { active_edges: 2, removed_edges: 0, reachable_peers: 2, local_edges: 1 },
+        pm0.with_state(|s| async move { s.routing_status() }).await
+    );
+}
+
 // test routing for three nodes in a line, then test routing after completing the triangle
 #[tokio::test]
 async fn three_nodes_star() {
@@ -1117,6 +1161,62 @@ async fn fix_local_edges() {
     drop(conn);
 }
 
+/// The tombstones created by fix_local_edges() for the edges to peers we are not connected to
+/// should be broadcasted together in a single update.
+#[tokio::test]
+async fn fix_local_edges_broadcasts_tombstones_together() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let mut conn = pm
+        .start_inbound(chain.clone(), chain.make_config(rng))
+        .await
+        .handshake(&clock.clock())
+        .await;
+    let nonce = Edge::create_fresh_nonce(&clock.clock());
+    let edge0 = data::make_edge(&pm.cfg.node_key, &data::make_secret_key(rng), nonce);
+    let edge1 = data::make_edge(&pm.cfg.node_key, &data::make_secret_key(rng), nonce);
+    let msg = PeerMessage::SyncRoutingTable(RoutingTableUpdate::from_edges(vec![
+        edge0.clone(),
+        edge1.clone(),
+    ]));
+
+    tracing::info!(target:"test", "waiting for fake edges to be processed");
+    let mut events = pm.events.from_now();
+    conn.send(msg.clone()).await;
+    events
+        .recv_until(|ev| match ev {
+            Event::MessageProcessed(tcp::Tier::T2, got) if got == msg => Some(()),
+            _ => None,
+        })
+        .await;
+
+    tracing::info!(target:"test", "waiting for the tombstones to be broadcasted");
+    pm.fix_local_edges(&clock.clock(), time::Duration::ZERO).await;
+    let is_tombstone_of =
+        |got: &Edge, edge: &Edge| got.key() == edge.key() && got.edge_type() == EdgeState::Removed;
+    let got = conn
+        .events
+        .recv_until(|ev| match ev {
+            Event::MessageProcessed(tcp::Tier::T2, PeerMessage::SyncRoutingTable(rtu))
+                if rtu.edges.iter().any(|e| e.edge_type() == EdgeState::Removed) =>
+            {
+                Some(rtu.edges)
+            }
+            _ => None,
+        })
+        .await;
+    assert!(
+        got.iter().any(|e| is_tombstone_of(e, &edge0))
+            && got.iter().any(|e| is_tombstone_of(e, &edge1)),
+        "tombstones were broadcasted separately"
+    );
+}
+
 #[tokio::test]
 async fn do_not_block_announce_account_broadcast() {
     abort_on_panic();
@@ -1290,6 +1390,53 @@ async fn connect_to_unbanned_peer() {
     drop(pm1);
 }
 
+/// Check that a temporary ban outlives `ban_window` and that the banned peer is able
+/// to connect again once the ban expires.
+#[tokio::test]
+async fn connect_to_temporarily_banned_peer() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut pm0 =
+        start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let mut pm1 =
+        start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+
+    tracing::info!(target:"test", "pm0 connects to pm1");
+    let stream_id = pm0.connect_to(&pm1.peer_info(), tcp::Tier::T2).await;
+
+    tracing::info!(target:"test", "pm1 temporarily bans pm0");
+    let ban_reason = ReasonForBan::BadBlock;
+    let ban_window = pm1.cfg.peer_store.ban_window;
+    let ban_duration = ban_window * 2;
+    pm1.disconnect_and_ban_for(&clock.clock(), &pm0.cfg.node_id(), ban_reason, ban_duration).await;
+    wait_for_stream_closed(&mut pm0.events, stream_id).await;
+    assert_eq!(
+        ClosingReason::Ban(ban_reason),
+        wait_for_stream_closed(&mut pm1.events, stream_id).await
+    );
+
+    tracing::info!(target:"test", "pm0 fails to reconnect to pm1 after ban_window");
+    clock.advance(ban_window);
+    pm1.peer_store_update(&clock.clock()).await;
+    let got_reason = pm1
+        .start_inbound(chain.clone(), pm0.cfg.clone())
+        .await
+        .manager_fail_handshake(&clock.clock())
+        .await;
+    assert_eq!(ClosingReason::RejectedByPeerManager(RegisterPeerError::Banned), got_reason);
+
+    tracing::info!(target:"test", "pm0 reconnects to pm1 once the ban expires");
+    clock.advance(ban_duration - ban_window);
+    pm0.connect_to(&pm1.peer_info(), tcp::Tier::T2).await;
+
+    drop(pm0);
+    drop(pm1);
+}
+
 /// Oversized SyncRoutingTable messages should have their edges dropped,
 /// but the connection should stay alive and subsequent valid updates should work.
 #[tokio::test]
@@ -1411,3 +1558,292 @@ async fn oversized_sync_routing_table_still_processes_accounts() {
         })
         .await;
 }
+
+/// With `reject_edges_from_other_genesis` enabled, edges with an endpoint known
+/// to be on a different genesis should be dropped, while other edges are still added.
+#[tokio::test]
+async fn reject_edges_from_other_genesis() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut cfg0 = chain.make_config(rng);
+    cfg0.reject_edges_from_other_genesis = true;
+    let mut pm = start_pm(clock.clock(), TestDB::new(), cfg0, chain.clone()).await;
+    let cfg = peer::testonly::PeerConfig { network: chain.make_config(rng), chain: chain.clone() };
+    let stream = tcp::Stream::connect(&pm.peer_info(), tcp::Tier::T2, &SocketOptions::default())
+        .await
+        .unwrap();
+    let mut peer =
+        peer::testonly::PeerHandle::start_endpoint(clock.clock(), ActorSystem::new(), cfg, stream);
+    peer.complete_handshake().await;
+
+    let peer_id = peer.cfg.id();
+    pm.wait_for_routing_table(&[(peer_id.clone(), vec![peer_id.clone()])]).await;
+
+    // Make pm aware of a peer on a different genesis.
+    let other_genesis_key = data::make_secret_key(rng);
+    let other_genesis_peer = PeerInfo {
+        id: PeerId::new(other_genesis_key.public_key()),
+        addr: Some(data::make_addr(rng)),
+        account_id: None,
+    };
+    let other_genesis_id = GenesisId { chain_id: "other".to_string(), hash: chain.genesis_id.hash };
+    let store_clock = clock.clock();
+    pm.with_state(move |s| async move {
+        s.peer_store.peer_genesis_id(&store_clock, &other_genesis_peer, other_genesis_id);
+    })
+    .await;
+
+    let nonce = Edge::create_fresh_nonce(&clock.clock());
+    let mismatched_edge = data::make_edge(&peer.cfg.network.node_key, &other_genesis_key, nonce);
+    let valid_edge =
+        data::make_edge(&peer.cfg.network.node_key, &data::make_secret_key(rng), nonce);
+    peer.send(PeerMessage::SyncRoutingTable(RoutingTableUpdate {
+        edges: vec![mismatched_edge.clone(), valid_edge.clone()],
+        accounts: vec![],
+    }))
+    .await;
+
+    pm.events
+        .recv_until(|ev| match ev {
+            Event::EdgesAdded(edges) if edges.contains(&valid_edge) => Some(()),
+            _ => None,
+        })
+        .await;
+
+    let mismatched_key = mismatched_edge.key().clone();
+    let has_mismatched = pm
+        .with_state(move |s| async move { s.graph.load().edges.contains_key(&mismatched_key) })
+        .await;
+    assert!(!has_mismatched, "edge from a peer on a different genesis should have been rejected");
+}
+
+/// A routed message to an unreachable peer should be reported to the
+/// `routed_message_dropped_sink` together with the reason of the drop.
+#[tokio::test]
+async fn routed_message_dropped_sink() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let (send, mut dropped) = broadcast::unbounded_channel();
+    let mut cfg = chain.make_config(rng);
+    cfg.routed_message_dropped_sink = Some(Sender::from_fn(move |event| send.send(event)));
+    let pm = start_pm(clock.clock(), TestDB::new(), cfg, chain.clone()).await;
+
+    let target = data::make_peer_id(rng);
+    pm.send_ping(&clock.clock(), 0, target.clone()).await;
+    assert_eq!(
+        dropped.recv().await,
+        RoutedMessageDroppedEvent {
+            target: PeerIdOrHash::PeerId(target),
+            msg_type: "Ping",
+            reason: FindRouteError::PeerUnreachable,
+        }
+    );
+}
+
+/// Edges added with `add_edges_no_broadcast` should be broadcasted together,
+/// only once `flush_edge_broadcast` is called.
+#[tokio::test]
+async fn add_edges_no_broadcast() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let cfg = peer::testonly::PeerConfig { network: chain.make_config(rng), chain: chain.clone() };
+    let stream = tcp::Stream::connect(&pm.peer_info(), tcp::Tier::T2, &SocketOptions::default())
+        .await
+        .unwrap();
+    let mut peer =
+        peer::testonly::PeerHandle::start_endpoint(clock.clock(), ActorSystem::new(), cfg, stream);
+    peer.complete_handshake().await;
+
+    let peer_id = peer.cfg.id();
+    pm.wait_for_routing_table(&[(peer_id.clone(), vec![peer_id.clone()])]).await;
+
+    tracing::info!(target:"test", "add edges in 2 batches without broadcasting them");
+    let nonce = Edge::create_fresh_nonce(&clock.clock());
+    let edge0 = data::make_edge(&peer.cfg.network.node_key, &data::make_secret_key(rng), nonce);
+    let edge1 = data::make_edge(&peer.cfg.network.node_key, &data::make_secret_key(rng), nonce);
+    let flush_clock = clock.clock();
+    pm.with_state_and_transport({
+        let edges = vec![edge0.clone(), edge1.clone()];
+        let source = peer_id.clone();
+        move |s, transport| async move {
+            for edge in edges {
+                let edges = EdgesWithSource::Remote { edges: vec![edge], source: source.clone() };
+                s.add_edges_no_broadcast(&flush_clock, edges, transport.clone()).await.unwrap();
+            }
+            s.flush_edge_broadcast(&flush_clock, transport.as_ref());
+        }
+    })
+    .await;
+
+    tracing::info!(target:"test", "wait for the edges to be broadcasted in a single update");
+    let got = peer
+        .events
+        .recv_until(|ev| match ev {
+            Event::MessageProcessed(tcp::Tier::T2, PeerMessage::SyncRoutingTable(rtu))
+                if rtu.edges.contains(&edge0) || rtu.edges.contains(&edge1) =>
+            {
+                Some(rtu.edges)
+            }
+            _ => None,
+        })
+        .await;
+    assert!(got.contains(&edge0) && got.contains(&edge1), "edges were broadcasted separately");
+}
+
+/// Edges accumulated by `add_edges_no_broadcast` should be broadcasted without waiting for
+/// `flush_edge_broadcast`, once there are `routing_graph_max_edges_per_message` of them.
+#[tokio::test]
+async fn add_edges_no_broadcast_bounded() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut cfg = chain.make_config(rng);
+    cfg.routing_graph_max_edges_per_message = 2;
+    let pm = start_pm(clock.clock(), TestDB::new(), cfg, chain.clone()).await;
+    let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+    pm.with_state(move |s| async move {
+        s.on_edges_broadcast(move |edges| send.send(edges.to_vec()).unwrap());
+    })
+    .await;
+
+    tracing::info!(target:"test", "add 2 edges without broadcasting them and without flushing");
+    let nonce = Edge::create_fresh_nonce(&clock.clock());
+    let edges: Vec<_> = (0..2)
+        .map(|_| data::make_edge(&data::make_secret_key(rng), &data::make_secret_key(rng), nonce))
+        .collect();
+    let add_clock = clock.clock();
+    pm.with_state_and_transport({
+        let edges = edges.clone();
+        move |s, transport| async move {
+            for edge in edges {
+                let edges = EdgesWithSource::Local(vec![edge]);
+                s.add_edges_no_broadcast(&add_clock, edges, transport.clone()).await.unwrap();
+            }
+        }
+    })
+    .await;
+
+    tracing::info!(target:"test", "wait for the edges to be broadcasted");
+    let got = loop {
+        let got = recv.recv().await.unwrap();
+        if !got.is_empty() {
+            break got;
+        }
+    };
+    assert_eq!(Edge::deduplicate(edges), got);
+}
+
+/// Duplicate edges removed right before broadcasting should be counted in a metric.
+#[tokio::test]
+async fn edges_deduplicated_metric() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+    pm.with_state(move |s| async move {
+        s.on_edges_broadcast(move |edges| send.send(edges.to_vec()).unwrap());
+    })
+    .await;
+
+    tracing::info!(target:"test", "accumulate 2 versions of the same edge and flush them together");
+    let nonce = Edge::create_fresh_nonce(&clock.clock());
+    let key0 = data::make_secret_key(rng);
+    let key1 = data::make_secret_key(rng);
+    let old_edge = data::make_edge(&key0, &key1, nonce);
+    let new_edge = data::make_edge(&key0, &key1, nonce + 2);
+    let deduplicated_before = metrics::NETWORK_EDGES_DEDUPLICATED.get();
+    let flush_clock = clock.clock();
+    pm.with_state_and_transport({
+        let edges = vec![old_edge.clone(), new_edge.clone()];
+        move |s, transport| async move {
+            for edge in edges {
+                let edges = EdgesWithSource::Local(vec![edge]);
+                s.add_edges_no_broadcast(&flush_clock, edges, transport.clone()).await.unwrap();
+            }
+            s.flush_edge_broadcast(&flush_clock, transport.as_ref());
+        }
+    })
+    .await;
+
+    let got = loop {
+        let got = recv.recv().await.unwrap();
+        if !got.is_empty() {
+            break got;
+        }
+    };
+    assert_eq!(vec![new_edge], got);
+    // Other tests may bump the counter concurrently, hence only a lower bound.
+    assert!(metrics::NETWORK_EDGES_DEDUPLICATED.get() >= deduplicated_before + 1);
+}
+
+/// The edges of a broadcasted routing table update should be deduplicated and sorted.
+#[tokio::test]
+async fn edges_broadcast_deduplicated_and_sorted() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+    pm.with_state(move |s| async move {
+        s.on_edges_broadcast(move |edges| send.send(edges.to_vec()).unwrap());
+    })
+    .await;
+
+    tracing::info!(target:"test", "add a set of edges, containing 2 versions of the same edge");
+    let nonce = Edge::create_fresh_nonce(&clock.clock());
+    let key0 = data::make_secret_key(rng);
+    let key1 = data::make_secret_key(rng);
+    let old_edge = data::make_edge(&key0, &key1, nonce);
+    let new_edge = data::make_edge(&key0, &key1, nonce + 2);
+    let mut edges = vec![old_edge.clone()];
+    for _ in 0..5 {
+        edges.push(data::make_edge(
+            &data::make_secret_key(rng),
+            &data::make_secret_key(rng),
+            nonce,
+        ));
+    }
+    edges.push(new_edge.clone());
+    let add_clock = clock.clock();
+    pm.with_state_and_transport({
+        let edges = edges.clone();
+        move |s, transport| async move {
+            s.add_edges(&add_clock, EdgesWithSource::Local(edges), transport).await.unwrap();
+        }
+    })
+    .await;
+
+    tracing::info!(target:"test", "wait for the edges to be broadcasted");
+    let got = loop {
+        let got = recv.recv().await.unwrap();
+        if !got.is_empty() {
+            break got;
+        }
+    };
+    assert_eq!(Edge::deduplicate(edges), got);
+    assert!(got.contains(&new_edge) && !got.contains(&old_edge));
+    assert!(got.windows(2).all(|w| w[0].key() > w[1].key()), "edges are not sorted");
+}
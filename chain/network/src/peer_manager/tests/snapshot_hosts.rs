@@ -3,6 +3,7 @@ use crate::network_protocol::SnapshotHostInfo;
 use crate::network_protocol::SyncSnapshotHosts;
 use crate::peer_manager;
 use crate::peer_manager::peer_manager_actor::Event;
+use crate::snapshot_hosts::LocalSnapshotHostInfoError;
 use crate::tcp;
 use crate::testonly::{AsSet as _, make_rng};
 use crate::types::NetworkRequests;
@@ -147,6 +148,67 @@ async fn broadcast() {
     assert_eq!(peer5_sync_msg.hosts.as_set(), vec![info1, info2].as_set());
 }
 
+/// Test that the local node's snapshot advertisement is broadcast to peers, that
+/// repeated identical advertisements are not, and that other nodes' infos are rejected.
+#[tokio::test]
+async fn advertise_local_snapshot() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let clock = clock.clock();
+    let clock = &clock;
+
+    let pm = peer_manager::testonly::start(
+        clock.clone(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    tracing::info!(target:"test", "connect a peer, expect initial sync to be empty");
+    let peer1_config = chain.make_config(rng);
+    let mut peer1 =
+        pm.start_inbound(chain.clone(), peer1_config.clone()).await.handshake(clock).await;
+    let empty_sync_msg = peer1.events.recv_until(take_sync_snapshot_msg).await;
+    assert_eq!(empty_sync_msg.hosts, vec![]);
+
+    let advertise = |info: Arc<SnapshotHostInfo>| {
+        pm.with_state_and_transport(move |state, transport| async move {
+            state.advertise_local_snapshot(info, transport.as_ref())
+        })
+    };
+
+    tracing::info!(target:"test", "advertise the local snapshot, expect the peer to receive it");
+    let sync_hash = CryptoHash::hash_borsh(1u64);
+    let shards = vec![ShardId::new(0), ShardId::new(1)];
+    let info1 = Arc::new(SnapshotHostInfo::new(
+        pm.cfg.node_id(),
+        sync_hash,
+        1,
+        shards.clone(),
+        &pm.cfg.node_key,
+    ));
+    assert_eq!(advertise(info1.clone()).await, Ok(true));
+    let got = peer1.events.recv_until(take_sync_snapshot_msg).await;
+    assert_eq!(got.hosts, vec![info1.clone()]);
+
+    tracing::info!(target:"test", "advertise the same info again, then a newer one, expect only the newer one");
+    assert_eq!(advertise(info1).await, Ok(false));
+    let info2 =
+        Arc::new(SnapshotHostInfo::new(pm.cfg.node_id(), sync_hash, 2, shards, &pm.cfg.node_key));
+    assert_eq!(advertise(info2.clone()).await, Ok(true));
+    let got = peer1.events.recv_until(take_sync_snapshot_msg).await;
+    assert_eq!(got.hosts, vec![info2]);
+
+    tracing::info!(target:"test", "advertising another node's info is rejected");
+    let foreign_info =
+        make_snapshot_host_info(&peer1_config.node_id(), &peer1_config.node_key, rng);
+    assert_eq!(advertise(foreign_info).await, Err(LocalSnapshotHostInfoError::NotLocalNode));
+}
+
 /// Test that a SyncSnapshotHosts message with an invalid signature isn't broadcast by PeerManager.
 #[tokio::test]
 async fn invalid_signature_not_broadcast() {
@@ -347,6 +409,54 @@ async fn propagate() {
     pms[2].wait_for_snapshot_hosts(&want).await;
 }
 
+/// Test that a locally created snapshot which was already advertised is not broadcast again.
+#[tokio::test]
+async fn repeated_snapshot_not_broadcast() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let clock = clock.clock();
+    let clock = &clock;
+
+    tracing::info!(target:"test", "create a single peer manager");
+    let pm = peer_manager::testonly::start(
+        clock.clone(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    tracing::info!(target:"test", "connect a peer, expect initial sync message to be empty");
+    let mut peer1 =
+        pm.start_inbound(chain.clone(), chain.make_config(rng)).await.handshake(clock).await;
+    let empty_sync_msg = peer1.events.recv_until(take_sync_snapshot_msg).await;
+    assert_eq!(empty_sync_msg.hosts, vec![]);
+
+    let sync_hash = CryptoHash::hash_borsh(rng.r#gen::<u64>());
+    let shards = vec![ShardId::new(0), ShardId::new(1)];
+    let snapshot_created = |epoch_height: EpochHeight| {
+        PeerManagerMessageRequest::NetworkRequests(NetworkRequests::SnapshotHostEvent(
+            SnapshotHostEvent::SnapshotCreated { sync_hash, epoch_height, shards: shards.clone() },
+        ))
+    };
+
+    tracing::info!(target:"test", "advertise the same snapshot twice, then a newer one");
+    let _: () = pm.actor.send_async(snapshot_created(5)).await.unwrap();
+    let _: () = pm.actor.send_async(snapshot_created(5)).await.unwrap();
+    let _: () = pm.actor.send_async(snapshot_created(6)).await.unwrap();
+
+    tracing::info!(target:"test", "expect the peer to receive the first and the newer snapshot only");
+    let msg = peer1.events.recv_until(take_sync_snapshot_msg).await;
+    assert_eq!(msg.hosts.len(), 1);
+    assert_eq!(msg.hosts[0].epoch_height, 5);
+    let msg = peer1.events.recv_until(take_sync_snapshot_msg).await;
+    assert_eq!(msg.hosts.len(), 1);
+    assert_eq!(msg.hosts[0].epoch_height, 6);
+}
+
 /// Send a SyncSnapshotHosts message with very large shard ids.
 /// Makes sure that PeerManager processes large shard ids without any problems.
 #[tokio::test]
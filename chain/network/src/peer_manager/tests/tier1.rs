@@ -1,15 +1,28 @@
+use crate::broadcast;
+use crate::concurrency::rate;
 use crate::config;
 use crate::network_protocol::testonly as data;
-use crate::network_protocol::{PeerAddr, PeerMessage, T1MessageBody, TieredMessageBody};
+use crate::network_protocol::{
+    PartialEncodedChunkForwardMsg, PeerAddr, PeerIdOrHash, PeerMessage, RawRoutedMessage,
+    T1MessageBody, TieredMessageBody,
+};
 use crate::peer_manager;
+use crate::peer_manager::network_state::{
+    AccountLookupStats, AccountSendOutcome, RoutedMessageArrival,
+};
 use crate::peer_manager::peer_manager_actor::Event;
 use crate::peer_manager::testonly::start as start_pm;
 use crate::stun;
 use crate::tcp;
 use crate::testonly::{Rng, make_rng};
 use near_async::time;
+use near_crypto::{KeyType, Signature};
 use near_o11y::testonly::init_test_logger;
 use near_primitives::block_header::{Approval, ApprovalInner};
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::ChunkHash;
+use near_primitives::types::ShardId;
 use near_primitives::validator_signer::ValidatorSigner;
 use near_store::db::TestDB;
 use rand::Rng as _;
@@ -47,13 +60,13 @@ async fn establish_connections(clock: &time::Clock, pms: &[&peer_manager::teston
 }
 
 // Sends a routed TIER1 message from `from` to `to`.
-// Returns the message body that was sent, or None if the routing information was missing.
+// Returns the message body that was sent together with the outcome of sending it.
 async fn send_tier1_message(
     rng: &mut Rng,
     clock: &time::Clock,
     from: &peer_manager::testonly::ActorHandler,
     to: &peer_manager::testonly::ActorHandler,
-) -> Option<TieredMessageBody> {
+) -> (TieredMessageBody, AccountSendOutcome) {
     let from_signer = from.cfg.validator.signer.get().unwrap();
     let to_signer = to.cfg.validator.signer.get().unwrap();
     let target = to_signer.validator_id().clone();
@@ -61,11 +74,9 @@ async fn send_tier1_message(
         T1MessageBody::BlockApproval(make_block_approval(rng, from_signer.as_ref())).into();
     let clock = clock.clone();
     from.with_state_and_transport(move |s, transport| async move {
-        if s.send_message_to_account(&clock, &target, want.clone(), &*transport) {
-            Some(want)
-        } else {
-            None
-        }
+        let outcome =
+            s.send_message_to_account_with_outcome(&clock, &target, want.clone(), &*transport);
+        (want, outcome)
     })
     .await
 }
@@ -80,7 +91,12 @@ async fn send_and_recv_tier1_message(
     recv_tier: tcp::Tier,
 ) {
     let mut events = to.events.from_now();
-    let want = send_tier1_message(rng, clock, from, to).await.expect("routing info not available");
+    let (want, outcome) = send_tier1_message(rng, clock, from, to).await;
+    let want_outcome = match recv_tier {
+        tcp::Tier::T1 => AccountSendOutcome::SentTier1,
+        _ => AccountSendOutcome::SentTier2,
+    };
+    assert_eq!(want_outcome, outcome, "routing info not available");
     let got = events
         .recv_until(|ev| match ev {
             Event::MessageProcessed(tier, PeerMessage::Routed(got)) if tier == recv_tier => {
@@ -179,6 +195,152 @@ async fn direct_connections() {
     test_clique(rng, &clock.clock(), &pms[..]).await;
 }
 
+async fn recv_arrival(events: &mut broadcast::Receiver<Event>) -> RoutedMessageArrival {
+    events
+        .recv_until(|ev| match ev {
+            Event::RoutedMessageArrival(arrival) => Some(arrival),
+            _ => None,
+        })
+        .await
+}
+
+/// An endorsement sent over TIER1 and then again over TIER2 should be recorded
+/// as having arrived first over TIER1.
+#[tokio::test]
+async fn routed_message_first_arrival_tier() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let pm0 = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let pm1 = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let id1 = pm1.cfg.node_id();
+
+    tracing::info!(target:"test", "connect peers over TIER2");
+    pm0.connect_to(&pm1.peer_info(), tcp::Tier::T2).await;
+    pm0.wait_for_routing_table(&[(id1.clone(), vec![id1.clone()])]).await;
+
+    tracing::info!(target:"test", "establish TIER1 connections");
+    let chain_info = peer_manager::testonly::make_chain_info(&chain, &[&pm0.cfg, &pm1.cfg]);
+    for pm in [&pm0, &pm1] {
+        pm.set_chain_info(chain_info.clone()).await;
+    }
+    establish_connections(&clock.clock(), &[&pm0, &pm1]).await;
+
+    tracing::info!(target:"test", "send an endorsement over TIER1");
+    let mut events = pm1.events.from_now();
+    let (body, outcome) = send_tier1_message(rng, &clock.clock(), &pm0, &pm1).await;
+    assert_eq!(AccountSendOutcome::SentTier1, outcome);
+    let first = recv_arrival(&mut events).await;
+    assert_eq!(
+        RoutedMessageArrival {
+            hash: first.hash,
+            tier: tcp::Tier::T1,
+            fastest: true,
+            first_tier: tcp::Tier::T1
+        },
+        first
+    );
+
+    tracing::info!(target:"test", "send the same endorsement over TIER2");
+    let send_clock = clock.clock();
+    pm0.with_state_and_transport(move |s, transport| async move {
+        let msg = s.sign_message(
+            &send_clock,
+            RawRoutedMessage { target: PeerIdOrHash::PeerId(id1), body },
+        );
+        assert!(s.send_message_to_peer(&send_clock, tcp::Tier::T2, msg, transport.as_ref()));
+    })
+    .await;
+    let second = recv_arrival(&mut events).await;
+    assert_eq!(
+        RoutedMessageArrival {
+            hash: first.hash,
+            tier: tcp::Tier::T2,
+            fastest: false,
+            first_tier: tcp::Tier::T1
+        },
+        second
+    );
+}
+
+// Delivers `count` copies of `forward` from `peer` to `pm`.
+// Returns how many of them were dropped.
+async fn deliver_chunk_forwards(
+    clock: &time::Clock,
+    pm: &peer_manager::testonly::ActorHandler,
+    peer: &PeerId,
+    forward: &TieredMessageBody,
+    count: usize,
+) -> usize {
+    let mut events = pm.events.from_now();
+    let clock = clock.clone();
+    let peer = peer.clone();
+    let forward = forward.clone();
+    pm.with_state(move |s| async move {
+        for _ in 0..count {
+            s.receive_routed_message(
+                &clock,
+                peer.clone(),
+                peer.clone(),
+                CryptoHash::default(),
+                forward.clone(),
+            )
+            .await;
+        }
+    })
+    .await;
+    let mut dropped = 0;
+    while let Some(ev) = events.try_recv() {
+        if ev == Event::RoutedMessageDropped {
+            dropped += 1;
+        }
+    }
+    dropped
+}
+
+/// PartialEncodedChunkForward messages delivered by a single peer in excess of
+/// `chunk_forward_rate_limit` should be dropped, without affecting other peers.
+#[tokio::test]
+async fn chunk_forward_rate_limit() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut cfg = chain.make_config(rng);
+    cfg.chunk_forward_rate_limit = rate::Limit { qps: 1., burst: 3 };
+    let pm = start_pm(clock.clock(), TestDB::new(), cfg, chain.clone()).await;
+
+    let forward: TieredMessageBody =
+        T1MessageBody::PartialEncodedChunkForward(PartialEncodedChunkForwardMsg {
+            chunk_hash: ChunkHash(data::make_hash(rng)),
+            inner_header_hash: data::make_hash(rng),
+            merkle_root: data::make_hash(rng),
+            signature: Signature::empty(KeyType::ED25519),
+            prev_block_hash: data::make_hash(rng),
+            height_created: chain.height(),
+            shard_id: ShardId::new(0),
+            parts: vec![],
+        })
+        .into();
+    let flooder = data::make_peer_id(rng);
+    let other = data::make_peer_id(rng);
+
+    tracing::info!(target:"test", "flood chunk forwards from a single peer");
+    assert_eq!(2, deliver_chunk_forwards(&clock.clock(), &pm, &flooder, &forward, 5).await);
+
+    tracing::info!(target:"test", "other peers are not affected");
+    assert_eq!(0, deliver_chunk_forwards(&clock.clock(), &pm, &other, &forward, 3).await);
+
+    tracing::info!(target:"test", "the limit refills over time");
+    clock.advance(time::Duration::seconds(1));
+    assert_eq!(1, deliver_chunk_forwards(&clock.clock(), &pm, &flooder, &forward, 2).await);
+}
+
 /// Test which spawns N validators, each with 1 proxy.
 /// All the nodes are connected in TIER2 star topology.
 /// Then all validators connect to the proxy of each other validator.
@@ -373,7 +535,10 @@ async fn tier2_routing_using_accounts_data() {
     // broadcasted by default in tests.
     // TODO(gprusak): send_tier1_message sends an Approval message, which is not a valid message to
     // be sent from a non-TIER1 node. Make it more realistic by sending a Transaction message.
-    assert!(send_tier1_message(rng, &clock.clock(), &pm0, &pm1).await.is_none());
+    let (_, outcome) = send_tier1_message(rng, &clock.clock(), &pm0, &pm1).await;
+    assert_eq!(AccountSendOutcome::UnknownAccount, outcome);
+    let stats = pm0.with_state(|s| async move { s.account_lookup_stats() }).await;
+    assert_eq!(AccountLookupStats::default(), stats);
 
     tracing::info!(target:"test", "propagate accounts data");
     let chain_info = peer_manager::testonly::make_chain_info(&chain, &[&pm1.cfg]);
@@ -385,6 +550,10 @@ async fn tier2_routing_using_accounts_data() {
 
     tracing::info!(target:"test", "send a routed message pm0 -> pm1 over tier2");
     send_and_recv_tier1_message(rng, &clock.clock(), &pm0, &pm1, tcp::Tier::T2).await;
+
+    tracing::info!(target:"test", "the target should have been resolved via AccountData");
+    let stats = pm0.with_state(|s| async move { s.account_lookup_stats() }).await;
+    assert_eq!(AccountLookupStats { account_data: 1, announce_account: 0 }, stats);
 }
 
 #[tokio::test]
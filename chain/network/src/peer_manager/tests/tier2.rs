@@ -1,7 +1,8 @@
 use crate::broadcast;
+use crate::config::DEFAULT_RECONNECT_BACKOFF_BASE;
+use crate::network_protocol::PeerInfo;
 use crate::network_protocol::testonly as data;
 use crate::peer_manager::connection_store::STORED_CONNECTIONS_MIN_DURATION;
-use crate::peer_manager::network_state::RECONNECT_ATTEMPT_INTERVAL;
 use crate::peer_manager::peer_manager_actor::Event;
 use crate::peer_manager::peer_manager_actor::POLL_CONNECTION_STORE_INTERVAL;
 use crate::peer_manager::testonly::ActorHandler;
@@ -11,6 +12,7 @@ use crate::testonly::AsSet;
 use crate::testonly::make_rng;
 use near_async::time;
 use near_o11y::testonly::init_test_logger;
+use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
 use near_store::db::TestDB;
 use std::sync::Arc;
@@ -207,7 +209,7 @@ async fn test_reconnect_after_restart_inbound_side() {
 
     tracing::info!(target:"test", "start pm1 again with the same config, check that pm0 reconnects");
     let _pm1 = start_pm(clock.clock(), TestDB::new(), pm1_cfg.clone(), chain.clone()).await;
-    clock.advance(POLL_CONNECTION_STORE_INTERVAL + RECONNECT_ATTEMPT_INTERVAL);
+    clock.advance(POLL_CONNECTION_STORE_INTERVAL + DEFAULT_RECONNECT_BACKOFF_BASE);
     pm0.wait_for_direct_connection(id1.clone()).await;
 }
 
@@ -241,7 +243,7 @@ async fn test_reconnect_after_disconnect_inbound_side() {
     wait_for_connection_closed(&mut pm0_ev).await;
 
     tracing::info!(target:"test", "check that pm0 reconnects");
-    clock.advance(POLL_CONNECTION_STORE_INTERVAL + RECONNECT_ATTEMPT_INTERVAL);
+    clock.advance(POLL_CONNECTION_STORE_INTERVAL + DEFAULT_RECONNECT_BACKOFF_BASE);
     pm0.wait_for_direct_connection(id1.clone()).await;
 }
 
@@ -294,3 +296,106 @@ async fn test_reconnect_after_restart_outbound_side_multi() {
     pm0.wait_for_direct_connection(id3.clone()).await;
     pm0.wait_for_direct_connection(id4.clone()).await;
 }
+
+#[tokio::test]
+async fn test_reconnect_backoff() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let base = time::Duration::seconds(1);
+    let max = time::Duration::seconds(4);
+    let mut cfg = chain.make_config(rng);
+    cfg.reconnect_backoff_base = base;
+    cfg.reconnect_backoff_max = max;
+    let pm = start_pm(clock.clock(), TestDB::new(), cfg, chain.clone()).await;
+
+    // Connecting to a peer without an address fails immediately.
+    let peer_info = PeerInfo { addr: None, ..data::make_peer_info(rng) };
+    let peer_id = peer_info.id.clone();
+    const MAX_ATTEMPTS: usize = 5;
+
+    tracing::info!(target:"test", "start a reconnect loop to an unreachable peer");
+    let mut events = pm.events.from_now();
+    let reconnect_clock = clock.clock();
+    let done = pm
+        .with_state_and_transport(move |s, t| async move {
+            s.spawn("reconnect", {
+                let s = s.clone();
+                async move { s.reconnect(reconnect_clock, t, peer_info, MAX_ATTEMPTS).await }
+            })
+        })
+        .await;
+
+    tracing::info!(target:"test", "check that the delays between attempts grow exponentially");
+    let mut want = base;
+    for _ in 1..MAX_ATTEMPTS {
+        let delay = events
+            .recv_until(|ev| match ev {
+                Event::ReconnectBackoff(id, delay) if id == peer_id => Some(delay),
+                _ => None,
+            })
+            .await;
+        assert!(want / 2 <= delay && delay <= want, "delay {delay} out of range for {want}");
+        clock.advance(delay);
+        want = std::cmp::min(want * 2, max);
+    }
+    done.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_prune_pending_reconnect() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let pm = start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+
+    let old_peer = data::make_peer_info(rng);
+    let new_peer = data::make_peer_info(rng);
+    let max_age = time::Duration::seconds(10);
+    // Use a separate clock, so that advancing it doesn't trigger the pm's reconnect polling.
+    let queue_clock = time::FakeClock::default();
+    let (listed, polled) = pm
+        .with_state({
+            let old_peer = old_peer.clone();
+            let new_peer = new_peer.clone();
+            move |s| async move {
+                tracing::info!(target:"test", "queue the same peer twice");
+                s.push_pending_reconnect(&queue_clock.clock(), old_peer.clone());
+                s.push_pending_reconnect(&queue_clock.clock(), old_peer.clone());
+                queue_clock.advance(max_age + time::Duration::seconds(1));
+                s.push_pending_reconnect(&queue_clock.clock(), new_peer.clone());
+                let listed = s.list_pending_reconnect();
+
+                tracing::info!(target:"test", "prune the entries older than max_age");
+                s.prune_pending_reconnect(&queue_clock.clock(), max_age);
+                (listed, s.poll_pending_reconnect())
+            }
+        })
+        .await;
+    assert_eq!(vec![old_peer, new_peer.clone()], listed);
+    assert_eq!(vec![new_peer], polled);
+}
+
+#[tokio::test]
+async fn node_id_and_key_fingerprint() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let cfg = chain.make_config(rng);
+    let node_key = cfg.node_key.clone();
+    let pm = start_pm(clock.clock(), TestDB::new(), cfg, chain.clone()).await;
+    let (node_id, fingerprint) =
+        pm.with_state(|s| async move { (s.node_id(), s.node_key_fingerprint()) }).await;
+    assert_eq!(pm.cfg.node_id(), node_id);
+
+    // The fingerprint is derived from the public key only.
+    assert_eq!(CryptoHash::hash_borsh(node_key.public_key()).to_string(), fingerprint);
+}
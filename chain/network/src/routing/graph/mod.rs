@@ -36,6 +36,8 @@ pub struct GraphSnapshot {
     pub local_edges: HashMap<PeerId, Edge>,
     pub next_hops: Arc<NextHopTable>,
     pub distances: Arc<DistanceTable>,
+    /// Number of active edges in `edges`.
+    pub active_edges: u64,
 }
 
 struct Inner {
@@ -333,9 +335,10 @@ impl Inner {
         }
         metrics::ROUTING_TABLE_RECALCULATIONS.inc();
         metrics::PEER_REACHABLE.set(next_hops.len() as i64);
-        metrics::EDGE_ACTIVE.set(self.graph.total_active_edges() as i64);
+        let active_edges = self.graph.total_active_edges();
+        metrics::EDGE_ACTIVE.set(active_edges as i64);
         metrics::EDGE_TOTAL.set(self.edges.len() as i64);
-        GraphSnapshot { edges: self.edges.clone(), local_edges, next_hops, distances }
+        GraphSnapshot { edges: self.edges.clone(), local_edges, next_hops, distances, active_edges }
     }
 }
 
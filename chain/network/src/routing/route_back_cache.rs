@@ -1,15 +1,12 @@
+use crate::stats::metrics;
 use ::time::ext::InstantExt as _;
 use near_async::time;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
 use std::collections::{BTreeMap, BTreeSet, HashMap, btree_map};
 
-/// default value for `capacity`
-const DEFAULT_CAPACITY: usize = 100_000;
-/// default value for `evict_timeout`
-const DEFAULT_CACHE_EVICT_TIMEOUT: time::Duration = time::Duration::milliseconds(120_000);
 /// default value for `remove_frequent_min_size`
-const DEFAULT_REMOVE_BATCH_SIZE: usize = 100;
+pub(crate) const DEFAULT_REMOVE_BATCH_SIZE: usize = 100;
 
 /// Cache to store route back messages.
 ///
@@ -67,12 +64,6 @@ pub struct RouteBackCache {
     record_per_target: BTreeMap<PeerId, BTreeSet<(time::Instant, CryptoHash)>>,
 }
 
-impl Default for RouteBackCache {
-    fn default() -> Self {
-        Self::new(DEFAULT_CAPACITY, DEFAULT_CACHE_EVICT_TIMEOUT, DEFAULT_REMOVE_BATCH_SIZE)
-    }
-}
-
 impl RouteBackCache {
     pub fn new(
         capacity: usize,
@@ -129,6 +120,9 @@ impl RouteBackCache {
             }
         }
 
+        metrics::ROUTE_BACK_CACHE_EVICTIONS
+            .with_label_values(&["frequent_target"])
+            .inc_by(removed as u64);
         self.size_per_target.remove(&(size, target.clone()));
         // Since self.size is equal to capacity - real_size, adding 1, is equivalent to subtracting 1 from the real size.
         size += removed;
@@ -156,6 +150,9 @@ impl RouteBackCache {
                 for evicted in value.iter() {
                     self.main.remove(&evicted.1);
                 }
+                metrics::ROUTE_BACK_CACHE_EVICTIONS
+                    .with_label_values(&["expired"])
+                    .inc_by(value.len() as u64);
 
                 *value = keep;
                 let new_size = value.len();
@@ -215,6 +212,18 @@ impl RouteBackCache {
         }
     }
 
+    /// Removes all records which should be routed back to `peer_id`.
+    /// Used when the peer disconnects, since these records can't be routed anymore.
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        let Some(records) = self.record_per_target.remove(peer_id) else {
+            return;
+        };
+        self.size_per_target.remove(&(self.capacity - records.len(), peer_id.clone()));
+        for (_, hash) in records {
+            self.main.remove(&hash);
+        }
+    }
+
     pub fn insert(&mut self, clock: &time::Clock, hash: CryptoHash, target: PeerId) {
         if self.main.contains_key(&hash) {
             return;
@@ -288,6 +297,13 @@ mod test {
         assert_eq!(cache.get(&hash0), None);
     }
 
+    fn total_evictions() -> u64 {
+        ["expired", "frequent_target"]
+            .into_iter()
+            .map(|reason| metrics::ROUTE_BACK_CACHE_EVICTIONS.with_label_values(&[reason]).get())
+            .sum()
+    }
+
     /// Check record is removed after some timeout.
     #[test]
     fn evicted() {
@@ -298,10 +314,13 @@ mod test {
         cache.insert(&clock.clock(), hash0, peer0.clone());
         check_consistency(&cache);
         assert_eq!(cache.get(&hash0), Some(&peer0));
+        let evictions_before = total_evictions();
         clock.advance(time::Duration::milliseconds(2));
         cache.remove_evicted(&clock.clock());
         check_consistency(&cache);
         assert_eq!(cache.get(&hash0), None);
+        // Other tests may bump the counter concurrently, hence only a lower bound.
+        assert!(total_evictions() >= evictions_before + 1);
     }
 
     /// Check element is removed after timeout triggered by insert at max capacity.
@@ -412,6 +431,39 @@ mod test {
         assert!(cache.get(&hash3).is_some());
     }
 
+    /// Insert records for two peers, remove one of them and check that only
+    /// the records of the removed peer are gone.
+    #[test]
+    fn remove_peer() {
+        let clock = time::FakeClock::default();
+        let mut cache = RouteBackCache::new(100, time::Duration::milliseconds(1000000), 1);
+        let peer0 = PeerId::random();
+        let peer1 = PeerId::random();
+        let hashes0: Vec<_> = (0..3).map(|ix| hash(&[ix])).collect();
+        let hashes1: Vec<_> = (3..6).map(|ix| hash(&[ix])).collect();
+
+        for (hash0, hash1) in hashes0.iter().zip(&hashes1) {
+            cache.insert(&clock.clock(), *hash0, peer0.clone());
+            cache.insert(&clock.clock(), *hash1, peer1.clone());
+            clock.advance(time::Duration::milliseconds(1));
+        }
+        check_consistency(&cache);
+
+        cache.remove_peer(&peer0);
+        check_consistency(&cache);
+        for hash0 in &hashes0 {
+            assert_eq!(cache.get(hash0), None);
+        }
+        for hash1 in &hashes1 {
+            assert_eq!(cache.get(hash1), Some(&peer1));
+        }
+
+        // Removing an unknown peer is a no-op.
+        cache.remove_peer(&peer0);
+        check_consistency(&cache);
+        assert_eq!(cache.main.len(), hashes1.len());
+    }
+
     /// Simulate an attack from a malicious actor which sends several routing back message
     /// to overtake the cache. Create 4 legitimate hashes from 3 peers. Then insert
     /// 50 hashes from attacker. Since the cache size is 17, first 5 message from attacker will
@@ -53,8 +53,8 @@ impl Inner {
     }
 }
 
-#[derive(Debug)]
-pub(crate) enum FindRouteError {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindRouteError {
     PeerUnreachable,
     RouteBackNotFound,
 }
@@ -7,6 +7,7 @@
 use crate::concurrency;
 use crate::network_protocol::SnapshotHostInfo;
 use crate::network_protocol::SnapshotHostInfoVerificationError;
+use crate::stats::metrics;
 use itertools::Itertools;
 use lru::LruCache;
 use near_primitives::hash::CryptoHash;
@@ -36,6 +37,15 @@ pub(crate) enum SnapshotHostInfoError {
     VerificationError(#[from] SnapshotHostInfoVerificationError),
 }
 
+/// Error returned when advertising the local node's own `SnapshotHostInfo`.
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+pub(crate) enum LocalSnapshotHostInfoError {
+    #[error("snapshot host info doesn't describe the local node")]
+    NotLocalNode,
+    #[error(transparent)]
+    VerificationError(#[from] SnapshotHostInfoVerificationError),
+}
+
 #[derive(Clone)]
 pub struct Config {
     /// The maximum number of SnapshotHostInfos to store locally.
@@ -43,6 +53,9 @@ pub struct Config {
     /// least-recently-used cache. In the future, we may wish to
     /// implement something more sophisticated.
     pub snapshot_hosts_cache_size: u32,
+    /// The maximum number of hosts retained for any single shard. When exceeded,
+    /// the least recently inserted hosts stop being hosts for that shard.
+    pub max_snapshot_hosts_per_shard: u32,
     /// The number of hosts we'll add to structures related to state part peer
     /// selection each time we need to request parts from a new peer
     pub part_selection_cache_batch_size: u32,
@@ -144,12 +157,17 @@ struct Inner {
     discard_snapshot_infos_below_epoch_height: Option<EpochHeight>,
     /// Available hosts for the active state sync, by shard
     hosts_for_shard: HashMap<ShardId, HashSet<PeerId>>,
+    /// Hosts retained for each shard, at most `max_hosts_per_shard` of them,
+    /// regardless of the sync hash
+    retained_hosts_for_shard: HashMap<ShardId, LruCache<PeerId, ()>>,
     /// Local data structures used to distribute state part requests among known hosts
     peer_selector: HashMap<(ShardId, u64), PartPeerSelector>,
     /// Batch size for populating the peer_selector from the hosts
     part_selection_cache_batch_size: usize,
     /// Epoch retention window
     epoch_retention_window: EpochHeight,
+    /// Maximum number of hosts retained for any single shard
+    max_hosts_per_shard: NonZeroUsize,
 }
 
 impl Inner {
@@ -185,6 +203,9 @@ impl Inner {
             return;
         }
         for shard_id in &info.shards {
+            if !self.is_retained_for_shard(&info.peer_id, shard_id) {
+                continue;
+            }
             self.hosts_for_shard.entry(*shard_id).or_default().insert(info.peer_id.clone());
         }
     }
@@ -193,11 +214,58 @@ impl Inner {
         for hosts in self.hosts_for_shard.values_mut() {
             hosts.remove(peer_id);
         }
+        for retained in self.retained_hosts_for_shard.values_mut() {
+            retained.pop(peer_id);
+        }
+    }
+
+    fn is_retained_for_shard(&self, peer_id: &PeerId, shard_id: &ShardId) -> bool {
+        self.retained_hosts_for_shard
+            .get(shard_id)
+            .is_some_and(|retained| retained.contains(peer_id))
+    }
+
+    /// Retains the peer as a host for each of the advertised shards. A shard
+    /// keeps only its `max_hosts_per_shard` most recently inserted hosts, the
+    /// hosts pushed out remain hosts for their other shards. A host which is no
+    /// longer retained for any shard is evicted.
+    fn retain_for_shards(&mut self, info: &SnapshotHostInfo) {
+        // An update replaces the shards previously advertised by the peer.
+        if let Some(old_info) = self.hosts.peek(&info.peer_id) {
+            for shard_id in old_info.shards.iter().filter(|s| !info.shards.contains(s)) {
+                if let Some(retained) = self.retained_hosts_for_shard.get_mut(shard_id) {
+                    retained.pop(&info.peer_id);
+                }
+            }
+        }
+        for shard_id in &info.shards {
+            let retained = self
+                .retained_hosts_for_shard
+                .entry(*shard_id)
+                .or_insert_with(|| LruCache::new(self.max_hosts_per_shard));
+            let Some((evicted, ())) = retained.push(info.peer_id.clone(), ()) else {
+                continue;
+            };
+            if evicted == info.peer_id {
+                continue;
+            }
+            if let Some(hosts) = self.hosts_for_shard.get_mut(shard_id) {
+                hosts.remove(&evicted);
+            }
+            let still_retained = self.hosts.peek(&evicted).is_some_and(|evicted_info| {
+                evicted_info.shards.iter().any(|s| self.is_retained_for_shard(&evicted, s))
+            });
+            if !still_retained {
+                self.hosts.pop(&evicted);
+                self.remove_from_shard_hosts(&evicted);
+            }
+        }
     }
 
     /// Ingests a new SnapshotHostInfo into the cache
     /// assumes that the SnapshotHostInfo is valid and new
     fn insert(&mut self, d: &Arc<SnapshotHostInfo>) {
+        self.retain_for_shards(d.as_ref());
         self.add_to_shard_hosts(d.as_ref());
         // `push` returns a different peer on eviction, or `d` itself on a
         // same-key update; only an eviction should leave the shard caches.
@@ -206,6 +274,7 @@ impl Inner {
                 self.remove_from_shard_hosts(&displaced);
             }
         }
+        metrics::SNAPSHOT_HOSTS_CACHE_SIZE.set(self.hosts.len() as i64);
     }
 
     /// Updates the current state sync hash. This is called when a local state sync request is initiated.
@@ -249,6 +318,7 @@ impl Inner {
             }
         }
         self.hosts = new_hosts;
+        metrics::SNAPSHOT_HOSTS_CACHE_SIZE.set(self.hosts.len() as i64);
     }
 
     /// Given a state header request produced by the local node,
@@ -326,9 +396,12 @@ impl SnapshotHostsCache {
             current_state_sync_hash: None,
             discard_snapshot_infos_below_epoch_height: None,
             hosts_for_shard: HashMap::new(),
+            retained_hosts_for_shard: HashMap::new(),
             peer_selector: HashMap::new(),
             part_selection_cache_batch_size: config.part_selection_cache_batch_size as usize,
             epoch_retention_window,
+            max_hosts_per_shard: NonZeroUsize::new(config.max_snapshot_hosts_per_shard as usize)
+                .unwrap(),
         }))
     }
 
@@ -389,8 +462,12 @@ impl SnapshotHostsCache {
     }
 
     /// Skips signature verification. Used only for the local node's own information.
-    pub fn insert_skip_verify(self: &Self, my_info: Arc<SnapshotHostInfo>) {
-        let _ = self.0.lock().try_insert(my_info);
+    /// Returns the info if it is new, i.e. if it should be broadcast to the network.
+    pub fn insert_skip_verify(
+        self: &Self,
+        my_info: Arc<SnapshotHostInfo>,
+    ) -> Option<Arc<SnapshotHostInfo>> {
+        self.0.lock().try_insert(my_info)
     }
 
     pub fn get_hosts(&self) -> Vec<Arc<SnapshotHostInfo>> {
@@ -401,6 +478,21 @@ impl SnapshotHostsCache {
         self.0.lock().hosts.peek(peer_id).cloned()
     }
 
+    /// Returns the number of hosts stored in the cache.
+    pub fn num_hosts(&self) -> usize {
+        self.0.lock().hosts.len()
+    }
+
+    /// Returns the peers retained as hosts for the given shard, regardless of
+    /// the sync hash of their snapshot. Sorted by peer id.
+    pub fn hosts_for_shard(&self, shard_id: ShardId) -> Vec<PeerId> {
+        let inner = self.0.lock();
+        let Some(retained) = inner.retained_hosts_for_shard.get(&shard_id) else {
+            return vec![];
+        };
+        retained.iter().map(|(peer_id, _)| peer_id.clone()).sorted().collect()
+    }
+
     /// Given a state header request, selects a peer host to which the request should be sent.
     pub fn select_host_for_header(
         &self,
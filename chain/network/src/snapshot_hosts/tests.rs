@@ -48,7 +48,11 @@ async fn happy_path() {
     let peer1 = PeerId::new(key1.public_key());
     let peer2 = PeerId::new(key2.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 100, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new(config);
     assert_eq!(cache.get_hosts().len(), 0); // initially empty
 
@@ -85,7 +89,11 @@ async fn invalid_signature() {
     let peer0 = PeerId::new(key0.public_key());
     let peer1 = PeerId::new(key1.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 100, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new(config);
 
     let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
@@ -121,7 +129,11 @@ async fn too_many_shards() {
     let peer0 = PeerId::new(key0.public_key());
     let peer1 = PeerId::new(key1.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 100, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new(config);
 
     let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
@@ -159,7 +171,11 @@ async fn duplicate_peer_id() {
     let key0 = data::make_secret_key(rng);
     let peer0 = PeerId::new(key0.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 100, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new(config);
 
     let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
@@ -188,7 +204,11 @@ async fn test_lru_eviction() {
     let peer1 = PeerId::new(key1.public_key());
     let peer2 = PeerId::new(key2.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 2, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 2,
+        max_snapshot_hosts_per_shard: 2,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new(config);
 
     let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
@@ -221,6 +241,52 @@ async fn test_lru_eviction() {
     assert_eq!([&peer1, &peer2].as_set(), cache.shard_host_peers().iter().collect::<HashSet<_>>());
 }
 
+#[tokio::test]
+async fn test_max_hosts_per_shard() {
+    init_test_logger();
+    let mut rng = make_rng(2947294234);
+    let rng = &mut rng;
+
+    let keys = (0..4).map(|_| data::make_secret_key(rng)).collect_vec();
+    let peers = keys.iter().map(|key| PeerId::new(key.public_key())).collect_vec();
+
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 2,
+        part_selection_cache_batch_size: 1,
+    };
+    let cache = SnapshotHostsCache::new(config);
+
+    let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
+
+    // The first host advertises shards 0 and 1, the others only shard 0.
+    let both = Arc::new(make_snapshot_host_info(&peers[0], 123, sid_vec(&[0, 1]), &keys[0]));
+    let res = cache.insert(vec![both.clone()]).await;
+    assert_eq!([&both].as_set(), unwrap(&res).as_set());
+
+    let mut infos = vec![];
+    for (peer, key) in peers.iter().zip(&keys).skip(1) {
+        let info = Arc::new(make_snapshot_host_info(peer, 123, sid_vec(&[0]), key));
+        let res = cache.insert(vec![info.clone()]).await;
+        assert_eq!([&info].as_set(), unwrap(&res).as_set());
+        infos.push(info);
+    }
+
+    // Only the two most recently inserted hosts for shard 0 are retained.
+    assert_eq!(
+        cache.hosts_for_shard(ShardId::new(0)),
+        [peers[2].clone(), peers[3].clone()].into_iter().sorted().collect_vec()
+    );
+    // A host pushed out of shard 0 remains a host for its other shards.
+    assert_eq!(cache.hosts_for_shard(ShardId::new(1)), vec![peers[0].clone()]);
+    // A host which is no longer retained for any shard is evicted.
+    assert_eq!(
+        [&both, &infos[1], &infos[2]].as_set(),
+        cache.get_hosts().iter().collect::<HashSet<_>>()
+    );
+    assert!(cache.get_host_info(&peers[1]).is_none());
+}
+
 #[tokio::test]
 async fn test_update_keeps_shard_hosts() {
     init_test_logger();
@@ -230,7 +296,11 @@ async fn test_update_keeps_shard_hosts() {
     let key0 = data::make_secret_key(rng);
     let peer0 = PeerId::new(key0.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 100, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new(config);
 
     let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
@@ -248,6 +318,50 @@ async fn test_update_keeps_shard_hosts() {
     assert_eq!([&peer0].as_set(), cache.shard_host_peers().iter().collect::<HashSet<_>>());
 }
 
+#[tokio::test]
+async fn test_hosts_for_shard() {
+    init_test_logger();
+    let mut rng = make_rng(2947294234);
+    let rng = &mut rng;
+
+    let key0 = data::make_secret_key(rng);
+    let key1 = data::make_secret_key(rng);
+    let peer0 = PeerId::new(key0.public_key());
+    let peer1 = PeerId::new(key1.public_key());
+
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
+    let cache = SnapshotHostsCache::new(config);
+    assert_eq!(cache.num_hosts(), 0);
+    assert!(cache.hosts_for_shard(ShardId::new(0)).is_empty());
+
+    let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
+
+    // The hosts are reported regardless of the sync hash they advertise.
+    let info0 = Arc::new(make_snapshot_host_info(&peer0, 123, sid_vec(&[0, 1]), &key0));
+    let info1 = Arc::new(make_snapshot_host_info(&peer1, 124, sid_vec(&[1, 2]), &key1));
+    cache.insert(vec![info0, info1]).await;
+    assert_eq!(cache.num_hosts(), 2);
+    assert_eq!(cache.hosts_for_shard(ShardId::new(0)), vec![peer0.clone()]);
+    assert_eq!(
+        cache.hosts_for_shard(ShardId::new(1)),
+        [peer0.clone(), peer1.clone()].into_iter().sorted().collect_vec()
+    );
+    assert_eq!(cache.hosts_for_shard(ShardId::new(2)), vec![peer1.clone()]);
+    assert!(cache.hosts_for_shard(ShardId::new(3)).is_empty());
+
+    // An update replaces the shards previously advertised by the peer.
+    let info0_new = Arc::new(make_snapshot_host_info(&peer0, 125, sid_vec(&[3]), &key0));
+    cache.insert(vec![info0_new]).await;
+    assert_eq!(cache.num_hosts(), 2);
+    assert!(cache.hosts_for_shard(ShardId::new(0)).is_empty());
+    assert_eq!(cache.hosts_for_shard(ShardId::new(1)), vec![peer1]);
+    assert_eq!(cache.hosts_for_shard(ShardId::new(3)), vec![peer0]);
+}
+
 #[tokio::test]
 async fn test_discard_removes_shard_hosts() {
     init_test_logger();
@@ -257,7 +371,11 @@ async fn test_discard_removes_shard_hosts() {
     let key0 = data::make_secret_key(rng);
     let peer0 = PeerId::new(key0.public_key());
 
-    let config = Config { snapshot_hosts_cache_size: 100, part_selection_cache_batch_size: 1 };
+    let config = Config {
+        snapshot_hosts_cache_size: 100,
+        max_snapshot_hosts_per_shard: 100,
+        part_selection_cache_batch_size: 1,
+    };
     let cache = SnapshotHostsCache::new_with_epoch_retention_window(config, 1);
 
     let sid_vec = |v: &[u64]| v.iter().cloned().map(Into::into).collect_vec();
@@ -467,8 +585,11 @@ async fn run_select_peer_test(
     part_id: u64,
     part_selection_cache_batch_size: u32,
 ) {
-    let config =
-        Config { snapshot_hosts_cache_size: keys.len() as u32, part_selection_cache_batch_size };
+    let config = Config {
+        snapshot_hosts_cache_size: keys.len() as u32,
+        max_snapshot_hosts_per_shard: keys.len() as u32,
+        part_selection_cache_batch_size,
+    };
     let cache = SnapshotHostsCache::new_with_epoch_retention_window(config, 1);
 
     tracing::debug!(test_name, "start");
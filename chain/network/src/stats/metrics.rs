@@ -203,6 +203,30 @@ pub(crate) static PEER_MESSAGE_RATE_LIMITED_BY_TYPE_TOTAL: LazyLock<IntCounterVe
         )
         .unwrap()
     });
+pub(crate) static PARTIAL_ENCODED_CHUNK_FORWARD_RATE_LIMITED: LazyLock<IntCounter> = LazyLock::new(
+    || {
+        try_create_int_counter(
+            "near_partial_encoded_chunk_forward_rate_limited_total",
+            "Number of PartialEncodedChunkForward messages dropped because the peer delivering them exceeded the rate limit",
+        )
+        .unwrap()
+    },
+);
+pub(crate) static ROUTE_BACK_CACHE_EVICTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "near_route_back_cache_evictions_total",
+        "Number of route back entries evicted from a full route back cache, by whether they expired or belonged to the most frequent target",
+        &["reason"],
+    )
+    .unwrap()
+});
+pub(crate) static NETWORK_ROUTING_LOOPS_DETECTED: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_network_routing_loops_detected_total",
+        "Number of routed messages dropped because they came back to a node which had already forwarded them",
+    )
+    .unwrap()
+});
 pub(crate) static SYNC_ACCOUNTS_DATA: LazyLock<IntCounterVec> = LazyLock::new(|| {
     try_create_int_counter_vec(
         "near_sync_accounts_data",
@@ -219,6 +243,13 @@ pub(crate) static SYNC_SNAPSHOT_HOSTS: LazyLock<IntCounterVec> = LazyLock::new(|
     )
     .unwrap()
 });
+pub(crate) static SNAPSHOT_HOSTS_CACHE_SIZE: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_snapshot_hosts_cache_size",
+        "Number of snapshot hosts currently stored in the snapshot hosts cache",
+    )
+    .unwrap()
+});
 
 pub(crate) static REQUEST_COUNT_BY_TYPE_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
     try_create_int_counter_vec(
@@ -254,6 +285,13 @@ pub(crate) static EDGE_TOTAL: LazyLock<IntGauge> = LazyLock::new(|| {
     try_create_int_gauge("near_edge_total", "Total edges between peers (including removed ones).")
         .unwrap()
 });
+pub(crate) static EDGE_ADD_QUEUE_DEPTH: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_edge_add_queue_depth",
+        "Number of edge batches waiting to be added to the routing graph",
+    )
+    .unwrap()
+});
 
 pub(crate) static EDGE_DROPPED: LazyLock<IntCounter> = LazyLock::new(|| {
     try_create_int_counter(
@@ -263,6 +301,14 @@ pub(crate) static EDGE_DROPPED: LazyLock<IntCounter> = LazyLock::new(|| {
     .unwrap()
 });
 
+pub(crate) static NETWORK_EDGES_DEDUPLICATED: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_network_edges_deduplicated_total",
+        "Number of duplicate edges removed from routing table updates before broadcasting",
+    )
+    .unwrap()
+});
+
 pub(crate) static EDGE_TOMBSTONE_SENDING_SKIPPED: LazyLock<IntCounter> = LazyLock::new(|| {
     try_create_int_counter(
         "near_edge_tombstone_sending_skip",
@@ -271,6 +317,14 @@ pub(crate) static EDGE_TOMBSTONE_SENDING_SKIPPED: LazyLock<IntCounter> = LazyLoc
     .unwrap()
 });
 
+pub(crate) static EDGES_FROM_OTHER_GENESIS_REJECTED: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_edges_from_other_genesis_rejected",
+        "Number of edges rejected because an endpoint is known to be on a different genesis.",
+    )
+    .unwrap()
+});
+
 pub(crate) static EDGE_TOMBSTONE_RECEIVING_SKIPPED: LazyLock<IntCounter> = LazyLock::new(|| {
     try_create_int_counter(
         "near_edge_tombstone_receiving_skip",
@@ -7,10 +7,10 @@ pub use crate::network_protocol::{
 /// Exported types, which are part of network protocol.
 pub use crate::network_protocol::{
     Edge, PartialEdgeInfo, PartialEncodedChunkForwardMsg, PartialEncodedChunkRequestMsg,
-    PartialEncodedChunkResponseMsg, PeerChainInfoV2, PeerInfo, SnapshotHostInfo, StateResponseInfo,
-    StateResponseInfoV1, StateResponseInfoV2,
+    PartialEncodedChunkResponseMsg, PeerChainInfoV2, PeerIdOrHash, PeerInfo, SnapshotHostInfo,
+    StateResponseInfo, StateResponseInfoV1, StateResponseInfoV2,
 };
-use crate::routing::routing_table_view::RoutingTableInfo;
+use crate::routing::routing_table_view::{FindRouteError, RoutingTableInfo};
 use crate::spice::data_distribution::SpicePartialDataRequest;
 pub use crate::state_sync::StateSyncResponse;
 use near_async::messaging::{AsyncSender, Sender};
@@ -105,6 +105,16 @@ pub enum KnownPeerStatus {
     Banned(ReasonForBan, time::Utc),
 }
 
+/// Reported to `NetworkConfig::routed_message_dropped_sink` whenever a routed
+/// message is dropped, because no route to its target was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutedMessageDroppedEvent {
+    pub target: PeerIdOrHash,
+    /// Variant of the message body, as returned by `TieredMessageBody::variant`.
+    pub msg_type: &'static str,
+    pub reason: FindRouteError,
+}
+
 /// Information node stores about known peers.
 #[derive(Debug, Clone)]
 pub struct KnownPeerState {
@@ -115,6 +125,9 @@ pub struct KnownPeerState {
     // Last time we tried to connect to this peer.
     // This data is not persisted in storage.
     pub last_outbound_attempt: Option<(time::Utc, Result<(), String>)>,
+    // Genesis the peer reported during a handshake, if known.
+    // This data is not persisted in storage.
+    pub genesis_id: Option<GenesisId>,
 }
 
 impl KnownPeerState {
@@ -125,6 +138,7 @@ impl KnownPeerState {
             first_seen: now,
             last_seen: now,
             last_outbound_attempt: None,
+            genesis_id: None,
         }
     }
 }
@@ -177,6 +191,9 @@ pub enum PeerManagerMessageRequest {
     /// The following types of requests are used to trigger actions in the Peer Manager for testing.
     /// TEST-ONLY: Fetch current routing table.
     FetchRoutingTable,
+    /// Request PeerManager to start or stop draining inbound TIER2 connections.
+    /// See `NetworkState::set_inbound_draining`.
+    SetInboundDraining(bool),
 }
 
 impl PeerManagerMessageRequest {
@@ -203,6 +220,7 @@ pub enum PeerManagerMessageResponse {
     NetworkResponses(NetworkResponses),
     AdvertiseTier1Proxies,
     FetchRoutingTable(RoutingTableInfo),
+    SetInboundDraining,
 }
 
 impl PeerManagerMessageResponse {
@@ -230,7 +248,8 @@ pub enum SnapshotHostEvent {
 }
 
 // TODO(#1313): Use Box
-#[derive(Clone, strum::AsRefStr, Debug, Eq, PartialEq)]
+// `NetworkRequestsDiscriminants` lets tests intercepting requests select them by kind.
+#[derive(Clone, strum::AsRefStr, strum::EnumDiscriminants, Debug, Eq, PartialEq)]
 #[allow(clippy::large_enum_variant)]
 pub enum NetworkRequests {
     /// Sends block, either when block was just produced or when requested.
@@ -417,6 +436,8 @@ pub struct NetworkInfo {
     pub connected_peers: Vec<ConnectedPeerInfo>,
     pub num_connected_peers: usize,
     pub peer_max_count: u32,
+    /// Whether new inbound TIER2 connections are currently being drained.
+    pub inbound_draining: bool,
     pub highest_height_peers: Vec<HighestHeightPeerInfo>,
     pub sent_bytes_per_sec: u64,
     pub received_bytes_per_sec: u64,
@@ -1,8 +1,11 @@
 use super::pending_events_sender::RawPendingEventsSender;
 use super::sender::TestLoopSender;
 use crate::messaging::{Actor, LateBoundSender};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 use std::any::{Any, type_name};
 use std::marker::PhantomData;
+use std::mem;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
@@ -12,7 +15,7 @@ use std::sync::atomic::AtomicBool;
 /// the execution of the TestLoop.
 ///
 /// ```rust, ignore
-/// let mut data = TestLoopData::new(raw_pending_events_sender, shutting_down);
+/// let mut data = TestLoopData::new(raw_pending_events_sender, shutting_down, seed);
 ///
 /// let actor = TestActor::new();
 /// let adapter = LateBoundSender::new();
@@ -28,7 +31,7 @@ use std::sync::atomic::AtomicBool;
 /// useful if we would like to have some arbitrary callback event in testloop to access this data.
 ///
 /// ```rust, ignore
-/// let mut data = TestLoopData::new(raw_pending_events_sender, shutting_down);
+/// let mut data = TestLoopData::new(raw_pending_events_sender, shutting_down, seed);
 /// let handle: TestLoopDataHandle<usize> = data.register_data(42);
 /// assert_eq!(data.get(&handle), 42);
 /// ```
@@ -42,14 +45,29 @@ pub struct TestLoopData {
     raw_pending_events_sender: RawPendingEventsSender,
     // Atomic bool to check if the test loop is shutting down. Used mainly for registering actors.
     shutting_down: Arc<AtomicBool>,
+    // Deterministic source of randomness, seeded when the test loop is constructed.
+    rng: ChaCha20Rng,
 }
 
 impl TestLoopData {
     pub(crate) fn new(
         raw_pending_events_sender: RawPendingEventsSender,
         shutting_down: Arc<AtomicBool>,
+        seed: u64,
     ) -> Self {
-        Self { data: Vec::new(), raw_pending_events_sender, shutting_down }
+        Self {
+            data: Vec::new(),
+            raw_pending_events_sender,
+            shutting_down,
+            rng: ChaCha20Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns the random number generator of the test loop, seeded via
+    /// `TestLoopV2::with_seed`. Test code should prefer it over `rand::thread_rng()`, so that
+    /// runs with the same seed are reproducible. Production code paths are unaffected.
+    pub fn rng(&mut self) -> &mut ChaCha20Rng {
+        &mut self.rng
     }
 
     /// Function to register data of any type in the TestLoopData.
@@ -118,6 +136,23 @@ impl TestLoopData {
             .downcast_mut()
             .expect("Handle type mismatched. Does handle belong to this TestLoopData?")
     }
+
+    /// Returns a copy of the data stored in TestLoopData, or None if the handle does not belong
+    /// to this TestLoopData. Together with `restore_entry`, this allows a test to checkpoint
+    /// some state and later fork execution from it.
+    ///
+    /// Only types implementing Clone are supported, i.e. typically plain data registered via
+    /// `register_data`. Actors registered via `register_actor` usually own channels, stores or
+    /// other resources and don't implement Clone.
+    pub fn try_clone_entry<T: Clone>(&self, handle: &TestLoopDataHandle<T>) -> Option<T> {
+        self.data.get(handle.id)?.downcast_ref::<T>().cloned()
+    }
+
+    /// Replaces the data stored in TestLoopData with `value`, e.g. a copy previously taken
+    /// with `try_clone_entry`, and returns the replaced data.
+    pub fn restore_entry<T>(&mut self, handle: &TestLoopDataHandle<T>, value: T) -> T {
+        mem::replace(self.get_mut(handle), value)
+    }
 }
 
 /// This is a handle to the data stored in TestLoopData.
@@ -152,7 +187,7 @@ mod tests {
     use std::sync::Arc;
     use std::sync::atomic::AtomicBool;
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     struct TestData {
         pub value: usize,
     }
@@ -162,6 +197,7 @@ mod tests {
         let mut data = TestLoopData::new(
             RawPendingEventsSender::new(|_| {}),
             Arc::new(AtomicBool::new(false)),
+            0,
         );
         let test_data = TestData { value: 42 };
         let handle = data.register_data(test_data);
@@ -170,4 +206,29 @@ mod tests {
         data.get_mut(&handle).value = 43;
         assert_eq!(data.get(&handle), &TestData { value: 43 });
     }
+
+    #[test]
+    fn test_clone_and_restore_entry() {
+        let mut data = TestLoopData::new(
+            RawPendingEventsSender::new(|_| {}),
+            Arc::new(AtomicBool::new(false)),
+            0,
+        );
+        let handle = data.register_data(TestData { value: 42 });
+        let checkpoint = data.try_clone_entry(&handle).unwrap();
+
+        data.get_mut(&handle).value = 43;
+        assert_eq!(checkpoint, TestData { value: 42 });
+        assert_eq!(data.restore_entry(&handle, checkpoint), TestData { value: 43 });
+        assert_eq!(data.get(&handle), &TestData { value: 42 });
+
+        let mut other_data = TestLoopData::new(
+            RawPendingEventsSender::new(|_| {}),
+            Arc::new(AtomicBool::new(false)),
+            0,
+        );
+        assert_eq!(other_data.register_data(1usize).id, 0);
+        let other_handle = other_data.register_data(2usize);
+        assert_eq!(data.try_clone_entry(&other_handle), None);
+    }
 }
@@ -65,10 +65,33 @@ fn drive_futures(task: &Arc<FutureTask>) {
     }
 }
 
+/// Determines what happens to a computation spawned via [`TestLoopAsyncComputationSpawner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsyncComputationPolicy {
+    /// Run the computation after the given delay.
+    Delay(Duration),
+    /// Lose the computation: it is dropped without running, after the given delay. This models
+    /// a computation whose result never arrives, e.g. because its thread died; the spawner has
+    /// no way to make the computation itself return an error. Everything captured by the
+    /// computation is dropped with it, so a caller waiting for the result on a channel observes
+    /// the channel being closed.
+    DropAfter(Duration),
+}
+
+impl AsyncComputationPolicy {
+    /// Returns the same policy with the delay increased by `extra`.
+    pub fn with_extra_delay(self, extra: Duration) -> Self {
+        match self {
+            Self::Delay(delay) => Self::Delay(delay + extra),
+            Self::DropAfter(delay) => Self::DropAfter(delay + extra),
+        }
+    }
+}
+
 /// [`AsyncComputationSpawner`] implementation that schedules the computation via the TestLoop.
 pub struct TestLoopAsyncComputationSpawner {
     sender: PendingEventsSender,
-    artificial_delay: Box<dyn Fn(&str) -> Duration + Send + Sync>,
+    policy: Box<dyn Fn(&str) -> AsyncComputationPolicy + Send + Sync>,
 }
 
 impl TestLoopAsyncComputationSpawner {
@@ -76,16 +99,30 @@ impl TestLoopAsyncComputationSpawner {
         sender: PendingEventsSender,
         artificial_delay: impl Fn(&str) -> Duration + Send + Sync + 'static,
     ) -> Self {
-        Self { sender, artificial_delay: Box::new(artificial_delay) }
+        Self::with_policy(sender, move |name| AsyncComputationPolicy::Delay(artificial_delay(name)))
+    }
+
+    pub fn with_policy(
+        sender: PendingEventsSender,
+        policy: impl Fn(&str) -> AsyncComputationPolicy + Send + Sync + 'static,
+    ) -> Self {
+        Self { sender, policy: Box::new(policy) }
     }
 }
 
 impl AsyncComputationSpawner for TestLoopAsyncComputationSpawner {
     fn spawn_boxed(&self, name: &str, f: Box<dyn FnOnce() + Send>) {
-        self.sender.send_with_delay(
-            format!("AsyncComputation({})", name),
-            Box::new(move |_| f()),
-            (self.artificial_delay)(name),
-        );
+        match (self.policy)(name) {
+            AsyncComputationPolicy::Delay(delay) => self.sender.send_with_delay(
+                format!("AsyncComputation({})", name),
+                Box::new(move |_| f()),
+                delay,
+            ),
+            AsyncComputationPolicy::DropAfter(delay) => self.sender.send_with_delay(
+                format!("AsyncComputationDropped({})", name),
+                Box::new(move |_| drop(f)),
+                delay,
+            ),
+        }
     }
 }
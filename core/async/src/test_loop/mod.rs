@@ -65,12 +65,12 @@ pub mod pending_events_sender;
 pub mod sender;
 
 use data::TestLoopData;
-use futures::{TestLoopAsyncComputationSpawner, TestLoopFutureSpawner};
+use futures::{AsyncComputationPolicy, TestLoopAsyncComputationSpawner, TestLoopFutureSpawner};
 use near_time::{Clock, Duration, FakeClock};
 use parking_lot::Mutex;
 use pending_events_sender::{CallbackEvent, PendingEventsSender, RawPendingEventsSender};
 use serde::Serialize;
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::panicking;
@@ -110,6 +110,14 @@ pub struct TestLoopV2 {
     /// Buffer for identifiers that should be added to the denylist. Written to by
     /// `ShutdownSignal` callbacks and drained at the start of each `process_event()`.
     pending_denylist: Arc<Mutex<Vec<String>>>,
+    /// If present, a function deciding whether an event should be executed (true) or
+    /// ignored (false). Consulted in addition to the identifier denylist.
+    event_filter: Option<Box<dyn FnMut(&CallbackEvent) -> bool>>,
+    /// Statistics of the executed events, keyed by identifier.
+    event_stats: HashMap<String, EventStats>,
+    /// If present, an artificial delay applied to async computations of all spawners,
+    /// keyed by the spawner's identifier and the name of the computation.
+    global_async_delay: Option<Arc<dyn Fn(&str, &str) -> Duration + Send + Sync>>,
 }
 
 /// An event waiting to be executed, ordered by the due time and then by ID.
@@ -198,6 +206,11 @@ struct EventEndLogOutput {
 
 impl TestLoopV2 {
     pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Creates a test loop whose `TestLoopData::rng` is seeded with the given seed.
+    pub fn with_seed(seed: u64) -> Self {
         let pending_events = Arc::new(Mutex::new(InFlightEvents::new()));
         let pending_events_clone = pending_events.clone();
         let raw_pending_events_sender = RawPendingEventsSender::new(move |callback_event| {
@@ -208,7 +221,7 @@ impl TestLoopV2 {
         // Needed for the log visualizer to know when the test loop starts.
         tracing::info!(target: "test_loop", "TEST_LOOP_INIT");
         Self {
-            data: TestLoopData::new(raw_pending_events_sender.clone(), shutting_down.clone()),
+            data: TestLoopData::new(raw_pending_events_sender.clone(), shutting_down.clone(), seed),
             events: BinaryHeap::new(),
             pending_events,
             raw_pending_events_sender,
@@ -219,6 +232,9 @@ impl TestLoopV2 {
             every_event_callback: None,
             denylisted_identifiers: HashSet::new(),
             pending_denylist: Arc::new(Mutex::new(Vec::new())),
+            event_filter: None,
+            event_stats: HashMap::new(),
+            global_async_delay: None,
         }
     }
 
@@ -229,16 +245,45 @@ impl TestLoopV2 {
 
     /// Returns an AsyncComputationSpawner that can be used to spawn async computation into the
     /// loop. The `artificial_delay` allows the test to determine an artificial delay that the
-    /// computation should take, based on the name of the computation.
+    /// computation should take, based on the name of the computation. If a global delay
+    /// was set with `set_global_async_delay`, it is added on top of `artificial_delay`.
     pub fn async_computation_spawner(
         &self,
         identifier: &str,
         artificial_delay: impl Fn(&str) -> Duration + Send + Sync + 'static,
     ) -> TestLoopAsyncComputationSpawner {
-        TestLoopAsyncComputationSpawner::new(
-            self.raw_pending_events_sender.for_identifier(identifier),
-            artificial_delay,
-        )
+        self.async_computation_spawner_with_policy(identifier, move |name| {
+            AsyncComputationPolicy::Delay(artificial_delay(name))
+        })
+    }
+
+    /// Like `async_computation_spawner`, but the `policy` may additionally decide, based on
+    /// the name of the computation, that it is lost instead of running. See
+    /// `AsyncComputationPolicy`. A global delay set with `set_global_async_delay` is added on
+    /// top of the delay chosen by the policy.
+    pub fn async_computation_spawner_with_policy(
+        &self,
+        identifier: &str,
+        policy: impl Fn(&str) -> AsyncComputationPolicy + Send + Sync + 'static,
+    ) -> TestLoopAsyncComputationSpawner {
+        let sender = self.raw_pending_events_sender.for_identifier(identifier);
+        let Some(global_async_delay) = self.global_async_delay.clone() else {
+            return TestLoopAsyncComputationSpawner::with_policy(sender, policy);
+        };
+        let identifier = identifier.to_string();
+        TestLoopAsyncComputationSpawner::with_policy(sender, move |name| {
+            policy(name).with_extra_delay(global_async_delay(&identifier, name))
+        })
+    }
+
+    /// Sets an artificial delay for async computations of all spawners, as a function of
+    /// the spawner's identifier and the name of the computation. Only affects spawners
+    /// created after this call, so it should be set before the nodes are set up.
+    pub fn set_global_async_delay(
+        &mut self,
+        f: impl Fn(&str, &str) -> Duration + Send + Sync + 'static,
+    ) {
+        self.global_async_delay = Some(Arc::new(f));
     }
 
     /// Sends any ad-hoc event to the loop.
@@ -286,10 +331,45 @@ impl TestLoopV2 {
         self.clock.clock()
     }
 
+    /// Returns the total number of events ever queued into the loop, including
+    /// events that have not been handled yet. Events sent from outside the loop
+    /// are only counted once the loop picks them up, i.e. during a run.
+    pub fn total_events_queued(&self) -> usize {
+        self.next_event_index
+    }
+
+    /// Returns the number of events with the given identifier that are yet to be handled,
+    /// including events sent to the loop that it has not picked up yet. Useful to check
+    /// that a node which has been shut down does not keep scheduling work.
+    pub fn pending_events_for(&self, identifier: &str) -> usize {
+        let queued = self.events.iter().filter(|event| event.event.identifier == identifier);
+        let received = self
+            .pending_events
+            .lock()
+            .events
+            .iter()
+            .filter(|event| event.identifier == identifier)
+            .count();
+        queued.count() + received
+    }
+
     pub fn set_every_event_callback(&mut self, callback: impl FnMut(&TestLoopData) + 'static) {
         self.every_event_callback = Some(Box::new(callback));
     }
 
+    /// Sets a filter deciding, for each event, whether it should be executed (true) or ignored
+    /// (false). Ignored events are logged with `event_ignored` set, just like events with a
+    /// denylisted identifier; the denylist still applies to events accepted by the filter.
+    pub fn set_event_filter(&mut self, filter: impl FnMut(&CallbackEvent) -> bool + 'static) {
+        self.event_filter = Some(Box::new(filter));
+    }
+
+    /// Returns statistics of the events executed so far, keyed by identifier. Ignored events
+    /// are not included.
+    pub fn event_stats(&self) -> &HashMap<String, EventStats> {
+        &self.event_stats
+    }
+
     /// Helper to push events we have just received into the heap.
     fn queue_received_events(&mut self) {
         for event in self.pending_events.lock().events.drain(..) {
@@ -362,9 +442,10 @@ impl TestLoopV2 {
     }
 
     /// Processes the given event, by logging a line first and then finding a handler to run it.
-    fn process_event(&mut self, event: EventInHeap) {
+    /// Returns whether the event was ignored instead of executed.
+    fn process_event(&mut self, event: EventInHeap) -> bool {
         if self.shutting_down.load(Ordering::Relaxed) {
-            return;
+            return true;
         }
 
         // Drain any identifiers that were pushed by ShutdownSignal callbacks.
@@ -374,7 +455,8 @@ impl TestLoopV2 {
                 self.denylisted_identifiers.insert(id);
             }
         }
-        let event_ignored = self.denylisted_identifiers.contains(&event.event.identifier);
+        let event_ignored = self.denylisted_identifiers.contains(&event.event.identifier)
+            || self.event_filter.as_mut().is_some_and(|filter| !filter(&event.event));
         if tracing::enabled!(target: "test_loop", tracing::Level::INFO) {
             let start_json = serde_json::to_string(&EventStartLogOutput {
                 current_index: event.id,
@@ -391,6 +473,10 @@ impl TestLoopV2 {
         assert_eq!(self.current_time, event.due);
 
         if !event_ignored {
+            self.event_stats
+                .entry(event.event.identifier.clone())
+                .and_modify(|stats| stats.record(event.due))
+                .or_insert(EventStats { count: 1, min_due: event.due, max_due: event.due });
             if let Some(callback) = &mut self.every_event_callback {
                 callback(&self.data);
             }
@@ -409,6 +495,7 @@ impl TestLoopV2 {
             // TODO(logging): testloop visualizer may have a dependency on seeing the trace in this specific format
             tracing::info!(target: "test_loop", "TEST_LOOP_EVENT_END {}", end_json);
         }
+        event_ignored
     }
 
     /// Runs the test loop for the given duration. This function may be called
@@ -434,10 +521,24 @@ impl TestLoopV2 {
     /// To maximize logical consistency, the condition is only checked before the clock would
     /// advance. If it returns true, execution stops before advancing the clock.
     pub fn run_until(
+        &mut self,
+        condition: impl FnMut(&mut TestLoopData) -> bool,
+        maximum_duration: Duration,
+    ) {
+        self.run_until_with_wall_clock_timeout(condition, maximum_duration, None)
+    }
+
+    /// Like `run_until`, but additionally panics if the condition is not fulfilled within
+    /// `wall_clock_timeout` of real time, if given. This guards against conditions that never
+    /// become true while the loop keeps generating events close to the current virtual time,
+    /// which could take practically forever to reach `maximum_duration`.
+    pub fn run_until_with_wall_clock_timeout(
         &mut self,
         mut condition: impl FnMut(&mut TestLoopData) -> bool,
         maximum_duration: Duration,
+        wall_clock_timeout: Option<std::time::Duration>,
     ) {
+        let started_at = std::time::Instant::now();
         let deadline = self.current_time + maximum_duration;
         let mut decider = move |next_time, data: &mut TestLoopData| {
             if condition(data) {
@@ -452,9 +553,38 @@ impl TestLoopV2 {
         };
         while let Some(event) = self.advance_till_next_event(&mut decider) {
             self.process_event(event);
+            if let Some(wall_clock_timeout) = wall_clock_timeout {
+                let elapsed = started_at.elapsed();
+                if elapsed > wall_clock_timeout {
+                    panic!(
+                        "run_until did not fulfill the condition within the wall clock timeout \
+                         of {wall_clock_timeout:?}, after {} of virtual time and {} events",
+                        self.current_time, self.next_event_index
+                    );
+                }
+            }
         }
     }
 
+    /// Processes exactly the next due event, advancing the clock to its time if needed, and
+    /// returns information about it. Returns None if there are no more events to process.
+    ///
+    /// Events are handled exactly like in `run_for` or `run_until`: nothing is executed once
+    /// shutdown has been initiated, and events with denylisted identifiers are skipped. Such
+    /// events (as well as those rejected by the event filter) are still returned, with
+    /// `StepInfo::ignored` set.
+    pub fn step(&mut self) -> Option<StepInfo> {
+        let event = self.advance_till_next_event(&mut |next_time, _| match next_time {
+            Some(_) => AdvanceDecision::AdvanceToNextEvent,
+            None => AdvanceDecision::Stop,
+        })?;
+        let identifier = event.event.identifier.clone();
+        let description = event.event.description.clone();
+        let due = event.due;
+        let ignored = self.process_event(event);
+        Some(StepInfo { identifier, description, due, ignored })
+    }
+
     pub fn run_instant(&mut self) {
         self.run_for(Duration::ZERO);
     }
@@ -485,6 +615,38 @@ impl Drop for TestLoopV2 {
     }
 }
 
+/// Information about an event processed by `TestLoopV2::step`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepInfo {
+    /// The identifier of the event, usually the node_id.
+    pub identifier: String,
+    /// The Debug representation of the event payload.
+    pub description: String,
+    /// The virtual time at which the event was processed.
+    pub due: Duration,
+    /// Whether the event was skipped, due to shutdown or a denylisted identifier.
+    pub ignored: bool,
+}
+
+/// Statistics of the events executed by `TestLoopV2` for a single identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventStats {
+    /// The number of executed events.
+    pub count: usize,
+    /// The virtual time of the earliest executed event.
+    pub min_due: Duration,
+    /// The virtual time of the latest executed event.
+    pub max_due: Duration,
+}
+
+impl EventStats {
+    fn record(&mut self, due: Duration) {
+        self.count += 1;
+        self.min_due = self.min_due.min(due);
+        self.max_due = self.max_due.max(due);
+    }
+}
+
 enum AdvanceDecision {
     AdvanceToNextEvent,
     AdvanceToAndStop(Duration),
@@ -493,10 +655,15 @@ enum AdvanceDecision {
 
 #[cfg(test)]
 mod tests {
-    use crate::futures::FutureSpawnerExt;
-    use crate::test_loop::TestLoopV2;
+    use crate::futures::{AsyncComputationSpawnerExt, FutureSpawnerExt};
+    use crate::test_loop::futures::AsyncComputationPolicy;
+    use crate::test_loop::{EventStats, TestLoopV2};
+    use parking_lot::Mutex;
+    use rand::Rng;
+    use std::collections::BTreeMap;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::{self, TryRecvError};
     use time::Duration;
 
     // Tests that the TestLoop correctly handles futures that sleep on the fake clock.
@@ -537,4 +704,216 @@ mod tests {
         test_loop.run_for(Duration::seconds(30));
         assert_eq!(finished.load(Ordering::Relaxed), 2);
     }
+
+    #[test]
+    fn test_total_events_queued() {
+        let mut test_loop = TestLoopV2::new();
+        let handled = Arc::new(AtomicUsize::new(0));
+        for delay in [Duration::ZERO, Duration::ZERO, Duration::seconds(5)] {
+            let handled = handled.clone();
+            test_loop.send_adhoc_event_with_delay("event".to_string(), delay, move |_| {
+                handled.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        // Events are only picked up by the loop once it runs.
+        assert_eq!(test_loop.total_events_queued(), 0);
+
+        test_loop.run_for(Duration::seconds(1));
+        assert_eq!(test_loop.total_events_queued(), 3);
+        assert_eq!(handled.load(Ordering::Relaxed), 2);
+
+        test_loop.run_for(Duration::seconds(10));
+        assert_eq!(test_loop.total_events_queued(), 3);
+        assert_eq!(handled.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_global_async_delay() {
+        let mut test_loop = TestLoopV2::new();
+        test_loop.set_global_async_delay(|identifier, name| match (identifier, name) {
+            ("node0", "compute") => Duration::seconds(1),
+            ("node1", "compute") => Duration::seconds(5),
+            _ => Duration::ZERO,
+        });
+        let clock = test_loop.clock();
+        let start_time = clock.now();
+
+        let finished_at = Arc::new(Mutex::new(BTreeMap::new()));
+        for identifier in ["node0", "node1", "node2"] {
+            let spawner =
+                test_loop.async_computation_spawner(identifier, |_| Duration::milliseconds(100));
+            let clock = clock.clone();
+            let finished_at = finished_at.clone();
+            spawner.spawn("compute", move || {
+                finished_at.lock().insert(identifier, clock.now() - start_time);
+            });
+        }
+
+        test_loop.run_for(Duration::seconds(10));
+        assert_eq!(
+            *finished_at.lock(),
+            BTreeMap::from([
+                ("node0", Duration::milliseconds(1100)),
+                ("node1", Duration::milliseconds(5100)),
+                ("node2", Duration::milliseconds(100)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_pending_events_for() {
+        let mut test_loop = TestLoopV2::new();
+        let node0 = test_loop.async_computation_spawner("node0", |_| Duration::seconds(1));
+        let node1 = test_loop.async_computation_spawner("node1", |_| Duration::seconds(5));
+        node0.spawn("compute", || {});
+        node0.spawn("compute", || {});
+        node1.spawn("compute", || {});
+        // Events not picked up by the loop yet are counted as well.
+        assert_eq!(test_loop.pending_events_for("node0"), 2);
+        assert_eq!(test_loop.pending_events_for("node1"), 1);
+        assert_eq!(test_loop.pending_events_for("node2"), 0);
+
+        test_loop.run_for(Duration::milliseconds(500));
+        assert_eq!(test_loop.pending_events_for("node0"), 2);
+        assert_eq!(test_loop.pending_events_for("node1"), 1);
+
+        test_loop.run_for(Duration::seconds(1));
+        assert_eq!(test_loop.pending_events_for("node0"), 0);
+        assert_eq!(test_loop.pending_events_for("node1"), 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "run_until did not fulfill the condition within the wall clock timeout"
+    )]
+    fn test_run_until_wall_clock_timeout() {
+        let mut test_loop = TestLoopV2::new();
+        let clock = test_loop.clock();
+        // Keeps generating events close to the current virtual time forever.
+        test_loop.future_spawner("busy").spawn("busy loop", async move {
+            loop {
+                clock.sleep(Duration::milliseconds(1)).await;
+            }
+        });
+        test_loop.run_until_with_wall_clock_timeout(
+            |_| false,
+            Duration::days(365 * 1000),
+            Some(std::time::Duration::from_millis(100)),
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let mut test_loop = TestLoopV2::new();
+        let handled = Arc::new(AtomicUsize::new(0));
+        for (description, delay) in [("first", Duration::seconds(1)), ("second", Duration::ZERO)] {
+            let handled = handled.clone();
+            test_loop.send_adhoc_event_with_delay(description.to_string(), delay, move |_| {
+                handled.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        test_loop
+            .raw_pending_events_sender
+            .for_identifier("node0")
+            .send("denylisted".to_string(), Box::new(|_| panic!("denylisted event executed")));
+        test_loop.event_denylist().lock().push("node0".to_string());
+
+        let step = test_loop.step().unwrap();
+        assert_eq!(
+            (step.description.as_str(), step.due, step.ignored),
+            ("second", Duration::ZERO, false)
+        );
+        assert_eq!(handled.load(Ordering::Relaxed), 1);
+
+        let step = test_loop.step().unwrap();
+        assert_eq!((step.identifier.as_str(), step.ignored), ("node0", true));
+        assert_eq!(handled.load(Ordering::Relaxed), 1);
+
+        // Stepping advances the clock to the next event.
+        let step = test_loop.step().unwrap();
+        assert_eq!(
+            (step.description.as_str(), step.due, step.ignored),
+            ("first", Duration::seconds(1), false)
+        );
+        assert_eq!(handled.load(Ordering::Relaxed), 2);
+
+        assert_eq!(test_loop.step(), None);
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let mut test_loop = TestLoopV2::new();
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        for (identifier, description) in
+            [("node0", "keep"), ("node0", "drop"), ("node1", "drop"), ("node2", "keep")]
+        {
+            let handled = handled.clone();
+            test_loop.raw_pending_events_sender.for_identifier(identifier).send(
+                description.to_string(),
+                Box::new(move |_| handled.lock().push(format!("{identifier}:{description}"))),
+            );
+        }
+        test_loop.set_event_filter(|event| {
+            !(event.identifier() == "node0" && event.description() == "drop")
+        });
+        test_loop.event_denylist().lock().push("node2".to_string());
+
+        test_loop.run_instant();
+        assert_eq!(*handled.lock(), vec!["node0:keep".to_string(), "node1:drop".to_string()]);
+    }
+
+    #[test]
+    fn test_event_stats() {
+        let mut test_loop = TestLoopV2::new();
+        let node0 = test_loop.async_computation_spawner("node0", |_| Duration::seconds(1));
+        let node1 = test_loop.async_computation_spawner("node1", |_| Duration::seconds(5));
+        node0.spawn("compute", || {});
+        node1.spawn("compute", || {});
+        test_loop.run_for(Duration::seconds(6));
+        node0.spawn("compute", || {});
+        node1.spawn("compute", || {});
+        test_loop.event_denylist().lock().push("node1".to_string());
+        test_loop.run_for(Duration::seconds(10));
+
+        let stats = test_loop.event_stats();
+        assert_eq!(
+            stats["node0"],
+            EventStats { count: 2, min_due: Duration::seconds(1), max_due: Duration::seconds(7) }
+        );
+        assert_eq!(
+            stats["node1"],
+            EventStats { count: 1, min_due: Duration::seconds(5), max_due: Duration::seconds(5) }
+        );
+    }
+
+    #[test]
+    fn test_seeded_rng() {
+        let sample = |seed| {
+            let mut test_loop = TestLoopV2::with_seed(seed);
+            (0..10).map(|_| test_loop.data.rng().r#gen::<u64>()).collect::<Vec<_>>()
+        };
+        assert_eq!(sample(42), sample(42));
+        assert_ne!(sample(42), sample(43));
+    }
+
+    #[test]
+    fn test_async_computation_drop_policy() {
+        let mut test_loop = TestLoopV2::new();
+        let spawner = test_loop.async_computation_spawner_with_policy("node0", |name| match name {
+            "lost" => AsyncComputationPolicy::DropAfter(Duration::seconds(2)),
+            _ => AsyncComputationPolicy::Delay(Duration::seconds(1)),
+        });
+        let (ok_sender, ok_receiver) = mpsc::channel();
+        let (lost_sender, lost_receiver) = mpsc::channel::<()>();
+        spawner.spawn("succeed", move || ok_sender.send(()).unwrap());
+        spawner.spawn("lost", move || lost_sender.send(()).unwrap());
+
+        test_loop.run_for(Duration::milliseconds(1500));
+        assert_eq!(ok_receiver.try_recv(), Ok(()));
+        assert_eq!(lost_receiver.try_recv(), Err(TryRecvError::Empty));
+
+        // The lost computation is dropped without running, which closes its channel.
+        test_loop.run_for(Duration::seconds(1));
+        assert_eq!(lost_receiver.try_recv(), Err(TryRecvError::Disconnected));
+    }
 }
@@ -52,9 +52,21 @@ impl PendingEventsSender {
 /// This is very versatile and we can potentially have anything as a callback. For example, for the case of Senders
 /// and Handlers, we can have a simple implementation of the CanSend function as a callback calling the Handle function
 /// of the actor.
-pub(crate) struct CallbackEvent {
+pub struct CallbackEvent {
     pub(crate) callback: TestLoopCallback,
     pub(crate) delay: Duration,
     pub(crate) identifier: String,
     pub(crate) description: String,
 }
+
+impl CallbackEvent {
+    /// The identifier of the event, usually the node_id.
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The Debug representation of the event payload.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
@@ -3,9 +3,11 @@ use super::parameter_table::InvalidConfigError;
 use crate::config_store::INITIAL_TESTNET_CONFIG;
 use crate::cost::RuntimeFeesConfig;
 use crate::parameter_table::ParameterTable;
+use crate::vm::VMKind;
 use near_account_id::AccountId;
 use near_primitives_core::types::{Balance, Gas, ProtocolVersion};
 use near_primitives_core::version::PROTOCOL_VERSION;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 // Lowered promise yield timeout length used in integration tests.
@@ -44,6 +46,17 @@ pub struct RuntimeConfig {
     /// How much creating an account should cost in NEAR. Taken into account when burning gas for
     /// account creation.
     pub account_creation_charge: Balance,
+
+    /// Maximum number of validator proposals that may be produced while applying a single chunk.
+    pub max_validator_proposals_per_chunk: u64,
+
+    /// How unused gas of a function call is distributed between the function call actions it
+    /// scheduled with a non-zero gas weight.
+    pub gas_distribution_strategy: GasDistributionStrategy,
+
+    /// Function calls the runtime is restricted to, meant for private deployments. An empty
+    /// allowlist allows all function calls.
+    pub function_call_allowlist: FunctionCallAllowlist,
 }
 
 impl RuntimeConfig {
@@ -80,6 +93,9 @@ impl RuntimeConfig {
             use_state_stored_receipt: runtime_config.use_state_stored_receipt,
             min_gas_purchase_price: runtime_config.min_gas_purchase_price,
             account_creation_charge: runtime_config.account_creation_charge,
+            max_validator_proposals_per_chunk: runtime_config.max_validator_proposals_per_chunk,
+            gas_distribution_strategy: runtime_config.gas_distribution_strategy,
+            function_call_allowlist: runtime_config.function_call_allowlist.clone(),
         }
     }
 
@@ -103,14 +119,111 @@ impl RuntimeConfig {
             // zero as well so the invariant min_gas_purchase_price * create_account_gas_cost >=
             // account_creation_charge holds trivially (0 >= 0).
             account_creation_charge: Balance::ZERO,
+            max_validator_proposals_per_chunk: runtime_config.max_validator_proposals_per_chunk,
+            gas_distribution_strategy: runtime_config.gas_distribution_strategy,
+            function_call_allowlist: runtime_config.function_call_allowlist.clone(),
         }
     }
 
+    /// Returns a builder of test configs, starting from `RuntimeConfig::test()`.
+    pub fn test_builder() -> RuntimeConfigTestBuilder {
+        RuntimeConfigTestBuilder { config: Self::test() }
+    }
+
     pub fn storage_amount_per_byte(&self) -> Balance {
         self.fees.storage_usage_config.storage_amount_per_byte
     }
 }
 
+/// Builder of `RuntimeConfig` variants for tests, created with `RuntimeConfig::test_builder()`.
+///
+/// The setters take care of cloning the shared `fees` and `wasm_config`, so that
+/// other configs are not affected.
+#[derive(Debug, Clone)]
+pub struct RuntimeConfigTestBuilder {
+    config: RuntimeConfig,
+}
+
+impl RuntimeConfigTestBuilder {
+    /// Sets the maximum amount of gas that can be burnt by a single function call.
+    pub fn max_gas_burnt(mut self, max_gas_burnt: Gas) -> Self {
+        Arc::make_mut(&mut self.config.wasm_config).limit_config.max_gas_burnt = max_gas_burnt;
+        self
+    }
+
+    pub fn storage_amount_per_byte(mut self, storage_amount_per_byte: Balance) -> Self {
+        Arc::make_mut(&mut self.config.fees).storage_usage_config.storage_amount_per_byte =
+            storage_amount_per_byte;
+        self
+    }
+
+    pub fn vm_kind(mut self, vm_kind: VMKind) -> Self {
+        Arc::make_mut(&mut self.config.wasm_config).vm_kind = vm_kind;
+        self
+    }
+
+    pub fn function_call_allowlist(
+        mut self,
+        function_call_allowlist: FunctionCallAllowlist,
+    ) -> Self {
+        self.config.function_call_allowlist = function_call_allowlist;
+        self
+    }
+
+    pub fn congestion_control_config(
+        mut self,
+        congestion_control_config: CongestionControlConfig,
+    ) -> Self {
+        self.config.congestion_control_config = congestion_control_config;
+        self
+    }
+
+    pub fn build(self) -> RuntimeConfig {
+        self.config
+    }
+}
+
+/// Strategy used to distribute the unused gas of a function call between the function call
+/// actions it scheduled with a non-zero gas weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, strum::Display, strum::EnumString)]
+pub enum GasDistributionStrategy {
+    /// Distribute gas proportionally to the gas weights of the actions.
+    #[default]
+    Weighted,
+    /// Distribute gas evenly between the actions, regardless of their gas weights.
+    EvenSplit,
+}
+
+/// Set of `(account_id, method_name)` pairs that function calls are allowed to target.
+/// An empty allowlist allows all function calls.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FunctionCallAllowlist {
+    methods_by_account: HashMap<AccountId, HashSet<String>>,
+}
+
+impl FunctionCallAllowlist {
+    pub fn new(entries: impl IntoIterator<Item = (AccountId, String)>) -> Self {
+        let mut methods_by_account: HashMap<AccountId, HashSet<String>> = HashMap::new();
+        for (account_id, method_name) in entries {
+            methods_by_account.entry(account_id).or_default().insert(method_name);
+        }
+        Self { methods_by_account }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.methods_by_account.is_empty()
+    }
+
+    /// Whether calling `method_name` on `account_id` is allowed.
+    pub fn is_allowed(&self, account_id: &AccountId, method_name: &str) -> bool {
+        self.is_empty()
+            || self
+                .methods_by_account
+                .get(account_id)
+                .is_some_and(|methods| methods.contains(method_name))
+    }
+}
+
 /// The structure describes configuration for creation of new accounts.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AccountCreationConfig {
@@ -312,3 +425,50 @@ impl BandwidthSchedulerConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CongestionControlConfig, RuntimeConfig};
+    use crate::cost::RuntimeFeesConfig;
+    use crate::vm;
+    use crate::vm::VMKind;
+    use near_primitives_core::types::{Balance, Gas};
+
+    #[test]
+    fn test_builder_overrides() {
+        let base = RuntimeConfig::test();
+        let vm_kind = if base.wasm_config.vm_kind == VMKind::Wasmtime {
+            VMKind::NearVm
+        } else {
+            VMKind::Wasmtime
+        };
+        let config = RuntimeConfig::test_builder()
+            .max_gas_burnt(Gas::from_teragas(42))
+            .storage_amount_per_byte(Balance::from_yoctonear(7))
+            .vm_kind(vm_kind)
+            .congestion_control_config(CongestionControlConfig::test_disabled())
+            .build();
+
+        assert_eq!(config.wasm_config.limit_config.max_gas_burnt, Gas::from_teragas(42));
+        assert_eq!(config.storage_amount_per_byte(), Balance::from_yoctonear(7));
+        assert_eq!(config.wasm_config.vm_kind, vm_kind);
+        assert_eq!(config.congestion_control_config, CongestionControlConfig::test_disabled());
+
+        // The rest of the config is the same as `RuntimeConfig::test()`.
+        let mut expected = base.clone();
+        expected.fees = config.fees.clone();
+        expected.wasm_config = config.wasm_config.clone();
+        expected.congestion_control_config = config.congestion_control_config;
+        assert_eq!(config, expected);
+        let mut expected_fees = RuntimeFeesConfig::clone(&base.fees);
+        expected_fees.storage_usage_config.storage_amount_per_byte = Balance::from_yoctonear(7);
+        assert_eq!(*config.fees, expected_fees);
+        let mut expected_wasm_config = vm::Config::clone(&base.wasm_config);
+        expected_wasm_config.limit_config.max_gas_burnt = Gas::from_teragas(42);
+        expected_wasm_config.vm_kind = vm_kind;
+        assert_eq!(*config.wasm_config, expected_wasm_config);
+
+        // The shared configs of `RuntimeConfig::test()` are not affected.
+        assert_eq!(RuntimeConfig::test(), base);
+    }
+}
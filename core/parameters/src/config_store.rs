@@ -89,6 +89,9 @@ impl RuntimeConfigStore {
     pub fn new(genesis_runtime_config: Option<&RuntimeConfig>) -> Self {
         let mut params: ParameterTable =
             BASE_CONFIG.parse().expect("Failed parsing base parameter file.");
+        if let Err(missing) = params.validate_complete() {
+            panic!("base parameter file is missing parameters: {missing:?}");
+        }
 
         let mut store = BTreeMap::new();
         #[cfg(not(feature = "calimero_zero_storage"))]
@@ -116,12 +119,21 @@ impl RuntimeConfigStore {
         }
 
         for (protocol_version, diff_bytes) in CONFIG_DIFFS {
-            let diff: ParameterTableDiff = diff_bytes.parse().unwrap_or_else(|err| {
-                panic!(
-                    "Failed parsing runtime parameters diff for version {protocol_version}. \
-                     Error: {err:?}"
-                )
-            });
+            let (diff, skipped) =
+                ParameterTableDiff::from_str_lenient(diff_bytes).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed parsing runtime parameters diff for version {protocol_version}. \
+                         Error: {err:?}"
+                    )
+                });
+            for key in skipped {
+                tracing::warn!(
+                    target: "runtime_config",
+                    protocol_version,
+                    %key,
+                    "skipping unknown parameter in runtime parameters diff"
+                );
+            }
             params.apply_diff(diff).unwrap_or_else(|err| {
                 panic!(
                     "Failed applying diff to `RuntimeConfig` for version {protocol_version}. \
@@ -297,6 +309,24 @@ mod tests {
         }
     }
 
+    /// The shipped config files must not rely on the lenient parsing, which silently stores
+    /// integers too large for u64 as strings and skips unknown parameters in diffs.
+    #[test]
+    fn all_configs_parse_strictly() {
+        for (name, config) in
+            [("parameters.yaml", BASE_CONFIG), ("parameters_testnet.yaml", INITIAL_TESTNET_CONFIG)]
+        {
+            if let Err(err) = ParameterTable::from_str_strict(config) {
+                panic!("{name} failed strict parsing: {err}");
+            }
+        }
+        for (version, diff_bytes) in CONFIG_DIFFS {
+            if let Err(err) = ParameterTableDiff::from_str_strict(diff_bytes) {
+                panic!("{version}.yaml failed strict parsing: {err}");
+            }
+        }
+    }
+
     #[test]
     fn test_override_account_length() {
         // Check that default value is 32.
@@ -129,6 +129,20 @@ impl ExtCostsConfig {
         self.costs[param].compute
     }
 
+    /// Total compute cost of a host function usage profile, where `usage` holds how many
+    /// times each cost was charged.
+    pub fn total_compute(
+        &self,
+        usage: &EnumMap<ExtCosts, u64>,
+    ) -> Result<Compute, IntegerOverflowError> {
+        usage.iter().try_fold(0, |total: Compute, (cost, &count)| {
+            count
+                .checked_mul(self.compute_cost(cost))
+                .and_then(|compute| total.checked_add(compute))
+                .ok_or(IntegerOverflowError)
+        })
+    }
+
     /// Convenience constructor to use in tests where the exact gas cost does
     /// not need to correspond to a specific protocol version.
     pub fn test_with_undercharging_factor(factor: u64) -> ExtCostsConfig {
@@ -896,3 +910,31 @@ pub fn gas_key_add_key_exec_fee(
         .unwrap();
     GasKeyAddFee { base, per_byte }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ExtCosts, ExtCostsConfig, ParameterCost};
+    use enum_map::{EnumMap, enum_map};
+    use near_primitives_core::errors::IntegerOverflowError;
+    use near_primitives_core::types::Gas;
+
+    #[test]
+    fn test_total_compute() {
+        let mut config = ExtCostsConfig { costs: enum_map! { _ => ParameterCost::ZERO } };
+        config.costs[ExtCosts::base] = ParameterCost { gas: Gas::from_gas(1), compute: 10 };
+        config.costs[ExtCosts::sha256_base] = ParameterCost { gas: Gas::from_gas(1), compute: 7 };
+        config.costs[ExtCosts::sha256_byte] = ParameterCost { gas: Gas::from_gas(1), compute: 3 };
+
+        let mut usage = EnumMap::default();
+        assert_eq!(config.total_compute(&usage), Ok(0));
+        usage[ExtCosts::base] = 4;
+        usage[ExtCosts::sha256_base] = 2;
+        usage[ExtCosts::sha256_byte] = 100;
+        // Charges of costs with no compute cost don't contribute.
+        usage[ExtCosts::log_base] = 1_000;
+        assert_eq!(config.total_compute(&usage), Ok(4 * 10 + 2 * 7 + 100 * 3));
+
+        usage[ExtCosts::sha256_byte] = u64::MAX;
+        assert_eq!(config.total_compute(&usage), Err(IntegerOverflowError));
+    }
+}
@@ -6,7 +6,10 @@ pub mod parameter_table;
 pub mod view;
 pub mod vm;
 
-pub use config::{AccountCreationConfig, RuntimeConfig};
+pub use config::{
+    AccountCreationConfig, FunctionCallAllowlist, GasDistributionStrategy, RuntimeConfig,
+    RuntimeConfigTestBuilder,
+};
 pub use config_store::RuntimeConfigStore;
 pub use cost::{
     ActionCosts, ExtCosts, ExtCostsConfig, Fee, GasKeyAddFee, GasKeyTransferFee, ParameterCost,
@@ -14,5 +17,6 @@ pub use cost::{
     gas_key_add_key_send_fee, gas_key_transfer_exec_fee, gas_key_transfer_send_fee,
     transfer_exec_fee, transfer_send_fee,
 };
-pub use parameter::Parameter;
+pub use parameter::{Parameter, ParameterValueType};
+pub use parameter_table::{InvalidConfigError, ParameterTable, ParameterTableDiff, ParameterValue};
 pub use view::{RuntimeConfigView, RuntimeFeesConfigView};
@@ -1,5 +1,6 @@
 use crate::cost::ActionCosts;
 use std::slice;
+use strum::IntoEnumIterator;
 
 /// Protocol configuration parameter which may change between protocol versions.
 #[derive(
@@ -11,6 +12,7 @@ use std::slice;
     Ord,
     Debug,
     strum::Display,
+    strum::EnumIter,
     strum::EnumString,
     strum::IntoStaticStr,
 )]
@@ -316,6 +318,41 @@ pub enum Parameter {
     // they are handled correctly. All other inputs were already handled
     // correctly.
     Bls12381NotInGroupFix,
+
+    // Maximum number of validator proposals a single chunk application may
+    // produce across all of its receipts.
+    MaxValidatorProposalsPerChunk,
+
+    // How unused gas of a function call is distributed between the function
+    // calls it scheduled with a non-zero gas weight.
+    GasDistributionStrategy,
+
+    // Function calls the runtime is restricted to, as `account_id:method_name`
+    // entries. An empty list allows all function calls.
+    FunctionCallAllowlist,
+}
+
+/// The shape of the value a [`Parameter`] expects in the parameter config
+/// files.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, strum::Display, strum::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum ParameterValueType {
+    /// An unsigned 64-bit integer.
+    U64,
+    /// An unsigned 128-bit integer, usually a balance in yoctoNEAR.
+    U128,
+    /// A `{ numerator, denominator }` pair.
+    Rational,
+    /// A gas cost with an optional compute cost, `{ gas, compute }`.
+    ParameterCost,
+    /// A `{ send_sir, send_not_sir, execution }` fee.
+    Fee,
+    /// A string, such as an account id or a VM kind.
+    String,
+    /// A boolean flag.
+    Flag,
+    /// A list of strings, such as the function call allowlist.
+    List,
 }
 
 #[derive(
@@ -407,6 +444,237 @@ impl Parameter {
         ]
         .iter()
     }
+
+    /// The shape of the value this parameter expects in the config files.
+    pub fn value_type(self) -> ParameterValueType {
+        match self {
+            Parameter::BurntGasReward
+            | Parameter::PessimisticGasPriceInflation
+            | Parameter::GasRefundPenalty
+            | Parameter::RejectTxCongestionThreshold => ParameterValueType::Rational,
+            Parameter::MinGasPurchasePrice
+            | Parameter::AccountCreationCharge
+            | Parameter::StorageAmountPerByte
+            | Parameter::GlobalContractStorageAmountPerByte => ParameterValueType::U128,
+            Parameter::MinGasRefundPenalty
+            | Parameter::MainStorageProofSizeSoftLimit
+            | Parameter::PerReceiptStorageProofSizeLimit
+            | Parameter::NewTransactionsValidationStateSizeSoftLimit
+            | Parameter::CombinedTransactionsSizeLimit
+            | Parameter::OutgoingReceiptsUsualSizeLimit
+            | Parameter::OutgoingReceiptsBigSizeLimit
+            | Parameter::MinAllowedTopLevelAccountLength
+            | Parameter::StorageNumBytesAccount
+            | Parameter::StorageNumExtraBytesRecord
+            | Parameter::WasmRegularOpCost
+            | Parameter::WasmLinearOpBaseCost
+            | Parameter::WasmLinearOpUnitCost
+            | Parameter::WasmGrowMemCost
+            | Parameter::WasmStorageLargeReadOverheadBase
+            | Parameter::WasmStorageLargeReadOverheadByte
+            | Parameter::MaxGasBurnt
+            | Parameter::MaxGasBurntView
+            | Parameter::MaxStackHeight
+            | Parameter::InitialMemoryPages
+            | Parameter::MaxMemoryPages
+            | Parameter::RegistersMemoryLimit
+            | Parameter::MaxRegisterSize
+            | Parameter::MaxNumberRegisters
+            | Parameter::MaxNumberLogs
+            | Parameter::MaxTotalLogLength
+            | Parameter::MaxTotalPrepaidGas
+            | Parameter::MaxActionsPerReceipt
+            | Parameter::MaxDeployActionsPerReceipt
+            | Parameter::MaxNumberBytesMethodNames
+            | Parameter::MaxLengthMethodName
+            | Parameter::MaxArgumentsLength
+            | Parameter::MaxLengthReturnedData
+            | Parameter::MaxContractSize
+            | Parameter::MaxTransactionSize
+            | Parameter::MaxReceiptSize
+            | Parameter::MaxLengthStorageKey
+            | Parameter::MaxLengthStorageValue
+            | Parameter::MaxPromisesPerFunctionCallAction
+            | Parameter::MaxNumberInputDataDependencies
+            | Parameter::MaxFunctionsNumberPerContract
+            | Parameter::MaxLocalsPerContract
+            | Parameter::AccountIdValidityRulesVersion
+            | Parameter::YieldTimeoutLengthInBlocks
+            | Parameter::MaxYieldPayloadSize
+            | Parameter::MaxTablesPerContract
+            | Parameter::MaxElementsPerContractTable
+            | Parameter::MaxFunctionBodySize
+            | Parameter::MaxInstrumentedCodeSize
+            | Parameter::MaxBlocksPerFunction
+            | Parameter::MaxBlocksPerContract
+            | Parameter::MaxTypesPerContract
+            | Parameter::MaxParamsPerFunction
+            | Parameter::MaxParamsPerContract
+            | Parameter::MaxOperandStackBytesPerFunction
+            | Parameter::MaxCongestionIncomingGas
+            | Parameter::MaxCongestionOutgoingGas
+            | Parameter::MaxCongestionMemoryConsumption
+            | Parameter::MaxCongestionMissedChunks
+            | Parameter::MaxOutgoingGas
+            | Parameter::MinOutgoingGas
+            | Parameter::AllowedShardOutgoingGas
+            | Parameter::MaxTxGas
+            | Parameter::MinTxGas
+            | Parameter::MaxShardBandwidth
+            | Parameter::MaxSingleGrant
+            | Parameter::MaxAllowance
+            | Parameter::MaxBaseBandwidth
+            | Parameter::DeployGlobalContractExecutionBase
+            | Parameter::DeployGlobalContractExecutionPerByte
+            | Parameter::MaxValidatorProposalsPerChunk => ParameterValueType::U64,
+            Parameter::ActionReceiptCreation
+            | Parameter::DataReceiptCreationBase
+            | Parameter::DataReceiptCreationPerByte
+            | Parameter::ActionCreateAccount
+            | Parameter::ActionDeleteAccount
+            | Parameter::ActionDeployContract
+            | Parameter::ActionDeployContractPerByte
+            | Parameter::ActionFunctionCall
+            | Parameter::ActionFunctionCallPerByte
+            | Parameter::ActionTransfer
+            | Parameter::ActionStake
+            | Parameter::ActionAddFullAccessKey
+            | Parameter::ActionAddFunctionCallKey
+            | Parameter::ActionAddFunctionCallKeyPerByte
+            | Parameter::ActionDeleteKey
+            | Parameter::ActionDelegate
+            | Parameter::ActionDeterministicStateInit
+            | Parameter::ActionDeterministicStateInitPerEntry
+            | Parameter::ActionDeterministicStateInitPerByte
+            | Parameter::ActionGasKeyTransfer
+            | Parameter::ActionGasKeyByte
+            | Parameter::ActionGasKeyNonceWriteBase
+            | Parameter::ActionDeployGlobalContract
+            | Parameter::ActionDeployGlobalContractPerByte
+            | Parameter::ActionUseGlobalContract
+            | Parameter::ActionUseGlobalContractPerIdentifierByte => ParameterValueType::Fee,
+            Parameter::WasmBase
+            | Parameter::WasmContractLoadingBase
+            | Parameter::WasmContractLoadingBytes
+            | Parameter::WasmReadMemoryBase
+            | Parameter::WasmReadMemoryByte
+            | Parameter::WasmWriteMemoryBase
+            | Parameter::WasmWriteMemoryByte
+            | Parameter::WasmReadRegisterBase
+            | Parameter::WasmReadRegisterByte
+            | Parameter::WasmWriteRegisterBase
+            | Parameter::WasmWriteRegisterByte
+            | Parameter::WasmUtf8DecodingBase
+            | Parameter::WasmUtf8DecodingByte
+            | Parameter::WasmUtf16DecodingBase
+            | Parameter::WasmUtf16DecodingByte
+            | Parameter::WasmSha256Base
+            | Parameter::WasmSha256Byte
+            | Parameter::WasmKeccak256Base
+            | Parameter::WasmKeccak256Byte
+            | Parameter::WasmKeccak512Base
+            | Parameter::WasmKeccak512Byte
+            | Parameter::WasmSha3256Base
+            | Parameter::WasmSha3256Byte
+            | Parameter::WasmSha3384Base
+            | Parameter::WasmSha3384Byte
+            | Parameter::WasmSha3512Base
+            | Parameter::WasmSha3512Byte
+            | Parameter::WasmRipemd160Base
+            | Parameter::WasmRipemd160Block
+            | Parameter::WasmEcrecoverBase
+            | Parameter::WasmEd25519VerifyBase
+            | Parameter::WasmEd25519VerifyByte
+            | Parameter::WasmP256VerifyBase
+            | Parameter::WasmP256VerifyByte
+            | Parameter::WasmLogBase
+            | Parameter::WasmLogByte
+            | Parameter::WasmStorageWriteBase
+            | Parameter::WasmStorageWriteKeyByte
+            | Parameter::WasmStorageWriteValueByte
+            | Parameter::WasmStorageWriteEvictedByte
+            | Parameter::WasmStorageReadBase
+            | Parameter::WasmStorageReadKeyByte
+            | Parameter::WasmStorageReadValueByte
+            | Parameter::WasmStorageRemoveBase
+            | Parameter::WasmStorageRemoveKeyByte
+            | Parameter::WasmStorageRemoveRetValueByte
+            | Parameter::WasmStorageHasKeyBase
+            | Parameter::WasmStorageHasKeyByte
+            | Parameter::WasmStorageIterCreatePrefixBase
+            | Parameter::WasmStorageIterCreatePrefixByte
+            | Parameter::WasmStorageIterCreateRangeBase
+            | Parameter::WasmStorageIterCreateFromByte
+            | Parameter::WasmStorageIterCreateToByte
+            | Parameter::WasmStorageIterNextBase
+            | Parameter::WasmStorageIterNextKeyByte
+            | Parameter::WasmStorageIterNextValueByte
+            | Parameter::WasmTouchingTrieNode
+            | Parameter::WasmReadCachedTrieNode
+            | Parameter::WasmPromiseAndBase
+            | Parameter::WasmPromiseAndPerPromise
+            | Parameter::WasmPromiseReturn
+            | Parameter::WasmValidatorStakeBase
+            | Parameter::WasmValidatorTotalStakeBase
+            | Parameter::WasmAltBn128G1MultiexpBase
+            | Parameter::WasmAltBn128G1MultiexpElement
+            | Parameter::WasmAltBn128PairingCheckBase
+            | Parameter::WasmAltBn128PairingCheckElement
+            | Parameter::WasmAltBn128G1SumBase
+            | Parameter::WasmAltBn128G1SumElement
+            | Parameter::WasmYieldCreateBase
+            | Parameter::WasmYieldCreateByte
+            | Parameter::WasmYieldCreateWithIdBase
+            | Parameter::WasmYieldResumeBase
+            | Parameter::WasmYieldResumeByte
+            | Parameter::WasmBls12381P1SumBase
+            | Parameter::WasmBls12381P1SumElement
+            | Parameter::WasmBls12381P2SumBase
+            | Parameter::WasmBls12381P2SumElement
+            | Parameter::WasmBls12381G1MultiexpBase
+            | Parameter::WasmBls12381G1MultiexpElement
+            | Parameter::WasmBls12381G2MultiexpBase
+            | Parameter::WasmBls12381G2MultiexpElement
+            | Parameter::WasmBls12381MapFpToG1Base
+            | Parameter::WasmBls12381MapFpToG1Element
+            | Parameter::WasmBls12381MapFp2ToG2Base
+            | Parameter::WasmBls12381MapFp2ToG2Element
+            | Parameter::WasmBls12381PairingBase
+            | Parameter::WasmBls12381PairingElement
+            | Parameter::WasmBls12381P1DecompressBase
+            | Parameter::WasmBls12381P1DecompressElement
+            | Parameter::WasmBls12381P2DecompressBase
+            | Parameter::WasmBls12381P2DecompressElement
+            | Parameter::MlDsa65VerificationCost => ParameterValueType::ParameterCost,
+            Parameter::RegistrarAccountId
+            | Parameter::VmKind
+            | Parameter::GasDistributionStrategy => ParameterValueType::String,
+            Parameter::FlatStorageReads
+            | Parameter::FixContractLoadingCost
+            | Parameter::FixContractLoadingError
+            | Parameter::EthImplicitAccounts
+            | Parameter::DiscardCustomSections
+            | Parameter::ReftypesBulkMemory
+            | Parameter::UseStateStoredReceipt
+            | Parameter::GlobalContractHostFns
+            | Parameter::GasKeyHostFns
+            | Parameter::OneYoctoOnPromise
+            | Parameter::P256VerifyHostFn
+            | Parameter::Sha3HostFns
+            | Parameter::YieldWithIdHostFns
+            | Parameter::ChainIdHostFn
+            | Parameter::Bls12381NotInGroupFix => ParameterValueType::Flag,
+            Parameter::FunctionCallAllowlist => ParameterValueType::List,
+        }
+    }
+
+    /// Name and expected value shape of every parameter, in declaration order.
+    ///
+    /// Intended for tooling that needs a machine-readable description of the
+    /// parameter config format.
+    pub fn value_types() -> impl Iterator<Item = (&'static str, ParameterValueType)> {
+        Parameter::iter().map(|param| (param.into(), param.value_type()))
+    }
 }
 
 // TODO: consider renaming parameters to "action_{ActionCosts}" and deleting
@@ -445,3 +713,35 @@ impl From<ActionCosts> for FeeParameter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Parameter, ParameterValueType};
+
+    #[test]
+    fn test_parameter_value_types() {
+        let expected = [
+            (Parameter::BurntGasReward, ParameterValueType::Rational),
+            (Parameter::StorageAmountPerByte, ParameterValueType::U128),
+            (Parameter::ActionTransfer, ParameterValueType::Fee),
+            (Parameter::DataReceiptCreationPerByte, ParameterValueType::Fee),
+            (Parameter::WasmBase, ParameterValueType::ParameterCost),
+            (Parameter::MlDsa65VerificationCost, ParameterValueType::ParameterCost),
+            (Parameter::WasmRegularOpCost, ParameterValueType::U64),
+            (Parameter::MaxGasBurnt, ParameterValueType::U64),
+            (Parameter::RegistrarAccountId, ParameterValueType::String),
+            (Parameter::VmKind, ParameterValueType::String),
+            (Parameter::FlatStorageReads, ParameterValueType::Flag),
+        ];
+        for (param, value_type) in expected {
+            assert_eq!(param.value_type(), value_type, "{param}");
+        }
+    }
+
+    #[test]
+    fn test_parameter_value_types_cover_all_parameters() {
+        let value_types: Vec<_> = Parameter::value_types().collect();
+        assert_eq!(value_types.first(), Some(&("burnt_gas_reward", ParameterValueType::Rational)));
+        assert!(value_types.contains(&("wasm_sha3_256_base", ParameterValueType::ParameterCost)));
+    }
+}
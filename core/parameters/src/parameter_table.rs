@@ -1,15 +1,20 @@
 use super::config::{AccountCreationConfig, RuntimeConfig};
-use crate::config::{BandwidthSchedulerConfig, CongestionControlConfig, WitnessConfig};
+use crate::config::{
+    BandwidthSchedulerConfig, CongestionControlConfig, FunctionCallAllowlist,
+    GasDistributionStrategy, WitnessConfig,
+};
 use crate::cost::{
     ActionCosts, ExtCostsConfig, Fee, ParameterCost, RuntimeFeesConfig, SignatureKind,
     StorageUsageConfig,
 };
-use crate::parameter::{FeeParameter, Parameter};
+use crate::parameter::{FeeParameter, Parameter, ParameterValueType};
 use crate::vm::VMKind;
 use crate::vm::{Config, StorageGetMode};
 use near_primitives_core::account::id::ParseAccountError;
 use near_primitives_core::types::{AccountId, Balance, Compute, Gas, ShardId};
 use num_rational::Rational32;
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
@@ -17,10 +22,14 @@ use std::sync::Arc;
 /// Represents values supported by parameter config.
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(untagged)]
-pub(crate) enum ParameterValue {
+pub enum ParameterValue {
     U64(u64),
     Rational { numerator: i32, denominator: i32 },
     ParameterCost { gas: u64, compute: u64 },
+    // Only valid as the new value in a diff, where it replaces the compute cost of the current
+    // `ParameterCost` and keeps its gas cost. Must come after `ParameterCost`, otherwise it would
+    // also match full `{ gas, compute }` costs.
+    ParameterComputeOverride { compute: u64 },
     Fee { send_sir: FeeComponent, send_not_sir: FeeComponent, execution: FeeComponent },
     // Can be used to store either a string or u128. Ideally, we would use a dedicated enum member
     // for u128, but this is currently impossible to express in YAML (see
@@ -60,8 +69,48 @@ impl FeeComponent {
     }
 }
 
+impl ParameterValue {
+    /// Returns the value if it is a plain `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            &ParameterValue::U64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a rational number with a non-zero denominator.
+    pub fn as_rational(&self) -> Option<Rational32> {
+        match self {
+            &ParameterValue::Rational { numerator, denominator } if denominator != 0 => {
+                Some(Rational32::new(numerator, denominator))
+            }
+            _ => None,
+        }
+    }
+
+    /// Compares two values numerically, which is useful to check that a parameter only
+    /// changes in one direction across protocol versions.
+    ///
+    /// Plain numbers and parameter costs are compared by their gas value, rationals are
+    /// compared with each other. Returns `None` for any other combination of values.
+    pub fn partial_cmp_numeric(&self, other: &ParameterValue) -> Option<Ordering> {
+        if let (Some(a), Some(b)) = (self.as_rational(), other.as_rational()) {
+            return Some(a.cmp(&b));
+        }
+        Some(self.numeric_gas()?.cmp(&other.numeric_gas()?))
+    }
+
+    fn numeric_gas(&self) -> Option<u64> {
+        match self {
+            &ParameterValue::U64(v) => Some(v),
+            &ParameterValue::ParameterCost { gas, .. } => Some(gas),
+            _ => None,
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
-pub(crate) enum ValueConversionError {
+pub enum ValueConversionError {
     #[error("expected a value of type `{0}`, but could not parse it from `{1:?}`")]
     ParseType(&'static str, ParameterValue),
 
@@ -76,6 +125,14 @@ pub(crate) enum ValueConversionError {
 
     #[error("expected a VM kind, but could not parse it from `{1}`")]
     ParseVmKind(#[source] strum::ParseError, String),
+
+    #[error("expected a gas distribution strategy, but could not parse it from `{1}`")]
+    ParseGasDistributionStrategy(#[source] strum::ParseError, String),
+
+    #[error(
+        "expected an `account_id:method_name` allowlist entry, but could not parse it from `{0}`"
+    )]
+    ParseAllowlistEntry(String),
 }
 
 macro_rules! implement_conversion_to {
@@ -245,6 +302,43 @@ impl TryFrom<&ParameterValue> for VMKind {
     }
 }
 
+impl TryFrom<&ParameterValue> for GasDistributionStrategy {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &ParameterValue) -> Result<Self, Self::Error> {
+        match value {
+            ParameterValue::String(v) => v.parse().map_err(|e| {
+                ValueConversionError::ParseGasDistributionStrategy(e, value.to_string())
+            }),
+            _ => Err(ValueConversionError::ParseType(
+                std::any::type_name::<GasDistributionStrategy>(),
+                value.clone(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&ParameterValue> for FunctionCallAllowlist {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &ParameterValue) -> Result<Self, Self::Error> {
+        let entries: Vec<&str> = value.try_into()?;
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                let (account_id, method_name) = entry
+                    .split_once(':')
+                    .ok_or_else(|| ValueConversionError::ParseAllowlistEntry(entry.to_string()))?;
+                let account_id = account_id.parse().map_err(|err| {
+                    ValueConversionError::ParseAccountId(err, account_id.to_string())
+                })?;
+                Ok((account_id, method_name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(FunctionCallAllowlist::new(entries))
+    }
+}
+
 impl TryFrom<&ParameterValue> for ShardId {
     type Error = ValueConversionError;
 
@@ -325,6 +419,9 @@ impl core::fmt::Display for ParameterValue {
             ParameterValue::ParameterCost { gas, compute } => {
                 write!(f, "{}", FormattedGasComputePair { gas: *gas, compute: *compute })
             }
+            ParameterValue::ParameterComputeOverride { compute } => {
+                write!(f, "compute: {}", FormattedNumber(*compute))
+            }
             ParameterValue::Fee { send_sir, send_not_sir, execution } => {
                 write!(
                     f,
@@ -343,7 +440,7 @@ impl core::fmt::Display for ParameterValue {
     }
 }
 
-pub(crate) struct ParameterTable {
+pub struct ParameterTable {
     parameters: BTreeMap<Parameter, ParameterValue>,
 }
 
@@ -359,13 +456,13 @@ impl core::fmt::Display for ParameterTable {
 }
 
 /// Changes made to parameters between versions.
-pub(crate) struct ParameterTableDiff {
+pub struct ParameterTableDiff {
     parameters: BTreeMap<Parameter, (Option<ParameterValue>, Option<ParameterValue>)>,
 }
 
 /// Error returned by ParameterTable::from_str() that parses a runtime configuration YAML file.
 #[derive(thiserror::Error, Debug)]
-pub(crate) enum InvalidConfigError {
+pub enum InvalidConfigError {
     #[error("could not parse `{1}` as a parameter")]
     UnknownParameter(#[source] strum::ParseError, String),
     #[error("could not parse `{1}` as a value")]
@@ -380,6 +477,12 @@ pub(crate) enum InvalidConfigError {
     NoOldValueExists(Parameter, Box<ParameterValue>),
     #[error("expected old value `{1:?}` but found `{2:?}` for parameter `{0}` in config diff")]
     WrongOldValue(Parameter, Box<ParameterValue>, Box<ParameterValue>),
+    #[error(
+        "compute override for parameter `{0}` requires a parameter cost, but the current value is `{1:?}`"
+    )]
+    ComputeOverrideOnNonCost(Parameter, Option<Box<ParameterValue>>),
+    #[error("integer `{0}` does not fit into a u64")]
+    IntegerTooLarge(String),
     #[error("expected a value for `{0}` but found none")]
     MissingParameter(Parameter),
     #[error("failed to convert a value for `{1}`")]
@@ -389,6 +492,20 @@ pub(crate) enum InvalidConfigError {
 impl std::str::FromStr for ParameterTable {
     type Err = InvalidConfigError;
     fn from_str(arg: &str) -> Result<ParameterTable, InvalidConfigError> {
+        Self::parse(arg, false)
+    }
+}
+
+impl ParameterTable {
+    /// Like `from_str`, but integers which don't fit into a `u64` are rejected instead of
+    /// being stored as strings, unless the parameter holds a `u128` value. This catches
+    /// typos such as extra digits in gas costs.
+    #[cfg(test)]
+    pub(crate) fn from_str_strict(arg: &str) -> Result<ParameterTable, InvalidConfigError> {
+        Self::parse(arg, true)
+    }
+
+    fn parse(arg: &str, strict: bool) -> Result<ParameterTable, InvalidConfigError> {
         let yaml_map: BTreeMap<String, serde_yaml::Value> =
             serde_yaml::from_str(arg).map_err(|err| InvalidConfigError::InvalidYaml(err))?;
 
@@ -398,7 +515,8 @@ impl std::str::FromStr for ParameterTable {
                 let typed_key: Parameter = key
                     .parse()
                     .map_err(|err| InvalidConfigError::UnknownParameter(err, key.to_owned()))?;
-                Ok((typed_key, parse_parameter_value(value)?))
+                let strict = strict && typed_key.value_type() != ParameterValueType::U128;
+                Ok((typed_key, parse_parameter_value(value, strict)?))
             })
             .collect::<Result<BTreeMap<_, _>, _>>()?;
 
@@ -454,8 +572,7 @@ impl TryFrom<&ParameterTable> for RuntimeConfig {
                 linear_op_unit_cost: params.get(Parameter::WasmLinearOpUnitCost)?,
                 discard_custom_sections: params.get(Parameter::DiscardCustomSections)?,
                 reftypes_bulk_memory: params.get(Parameter::ReftypesBulkMemory)?,
-                limit_config: serde_yaml::from_value(params.yaml_map(Parameter::vm_limits()))
-                    .map_err(InvalidConfigError::InvalidYaml)?,
+                limit_config: params.get_yaml(Parameter::vm_limits())?,
                 fix_contract_loading_cost: params.get(Parameter::FixContractLoadingCost)?,
                 fix_contract_loading_error: params.get(Parameter::FixContractLoadingError)?,
                 storage_get_mode: match params.get(Parameter::FlatStorageReads)? {
@@ -495,6 +612,10 @@ impl TryFrom<&ParameterTable> for RuntimeConfig {
             use_state_stored_receipt: params.get(Parameter::UseStateStoredReceipt)?,
             min_gas_purchase_price: params.get(Parameter::MinGasPurchasePrice)?,
             account_creation_charge: params.get(Parameter::AccountCreationCharge)?,
+            max_validator_proposals_per_chunk: params
+                .get(Parameter::MaxValidatorProposalsPerChunk)?,
+            gas_distribution_strategy: params.get(Parameter::GasDistributionStrategy)?,
+            function_call_allowlist: params.get(Parameter::FunctionCallAllowlist)?,
         })
     }
 }
@@ -524,10 +645,7 @@ fn get_congestion_control_config(
 }
 
 impl ParameterTable {
-    pub(crate) fn apply_diff(
-        &mut self,
-        diff: ParameterTableDiff,
-    ) -> Result<(), InvalidConfigError> {
+    pub fn apply_diff(&mut self, diff: ParameterTableDiff) -> Result<(), InvalidConfigError> {
         for (key, (before, after)) in diff.parameters {
             let old_value = self.parameters.get(&key);
             if old_value != before.as_ref() {
@@ -551,6 +669,27 @@ impl ParameterTable {
             }
 
             if let Some(new_value) = after {
+                let new_value = match new_value {
+                    ParameterValue::ParameterComputeOverride { compute } => {
+                        let gas = match old_value {
+                            Some(&ParameterValue::ParameterCost { gas, .. }) => gas,
+                            // Plain numbers are costs with compute equal to gas.
+                            Some(&ParameterValue::U64(gas))
+                                if key.value_type() == ParameterValueType::ParameterCost =>
+                            {
+                                gas
+                            }
+                            _ => {
+                                return Err(InvalidConfigError::ComputeOverrideOnNonCost(
+                                    key,
+                                    old_value.cloned().map(Box::new),
+                                ));
+                            }
+                        };
+                        ParameterValue::ParameterCost { gas, compute }
+                    }
+                    new_value => new_value,
+                };
                 self.parameters.insert(key, new_value);
             } else {
                 self.parameters.remove(&key);
@@ -559,16 +698,110 @@ impl ParameterTable {
         Ok(())
     }
 
-    fn yaml_map(&self, params: impl Iterator<Item = &'static Parameter>) -> serde_yaml::Value {
+    /// Computes the diff that transforms `self` into `other`.
+    ///
+    /// Parameters only present in `other` are recorded as additions (no old value) and
+    /// parameters only present in `self` as removals (no new value).
+    pub fn diff_against(&self, other: &ParameterTable) -> ParameterTableDiff {
+        let added = other.parameters.keys().filter(|key| !self.parameters.contains_key(key));
+        let parameters = self
+            .parameters
+            .keys()
+            .chain(added)
+            .filter_map(|key| {
+                let old_value = self.parameters.get(key);
+                let new_value = other.parameters.get(key);
+                if old_value == new_value {
+                    return None;
+                }
+                Some((*key, (old_value.cloned(), new_value.cloned())))
+            })
+            .collect();
+        ParameterTableDiff { parameters }
+    }
+
+    /// Checks that every parameter needed to build a `RuntimeConfig` is present, and returns
+    /// all of the missing ones at once instead of only the first one `try_from` runs into.
+    ///
+    /// Every missing parameter reported by the conversion is filled in with a placeholder
+    /// value on a copy of the table, until the conversion stops reporting missing parameters.
+    pub(crate) fn validate_complete(&self) -> Result<(), Vec<Parameter>> {
+        let mut filled = ParameterTable { parameters: self.parameters.clone() };
+        let mut missing = vec![];
+        while let Err(InvalidConfigError::MissingParameter(param)) =
+            RuntimeConfig::try_from(&filled)
+        {
+            missing.push(param);
+            filled.parameters.insert(param, placeholder_value(param));
+        }
+        missing.sort();
+        if missing.is_empty() { Ok(()) } else { Err(missing) }
+    }
+
+    /// Export the table as a JSON object mapping parameter names to their typed values.
+    ///
+    /// Rationals, parameter costs and fees become nested objects; u128 values are kept as
+    /// strings, as in the config files, since they don't fit into a JSON number.
+    pub fn to_json(&self) -> serde_json::Value {
+        // All parameter values can be serialized as JSON, so we don't ever expect this to fail.
+        serde_json::to_value(
+            self.parameters
+                .iter()
+                .map(|(param, value)| (param.to_string(), value))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .expect("failed to convert parameter values to JSON")
+    }
+
+    /// Absent parameters for which `fill_absent` returns true are mapped to a placeholder value.
+    fn yaml_map(
+        &self,
+        params: impl Iterator<Item = &'static Parameter>,
+        fill_absent: impl Fn(Parameter) -> bool,
+    ) -> serde_yaml::Value {
         // All parameter values can be serialized as YAML, so we don't ever expect this to fail.
         serde_yaml::to_value(
             params
-                .filter_map(|param| Some((param.to_string(), self.parameters.get(param)?)))
+                .filter_map(|param| {
+                    let value = match self.parameters.get(param) {
+                        Some(value) => Cow::Borrowed(value),
+                        None if fill_absent(*param) => Cow::Owned(placeholder_value(*param)),
+                        None => return None,
+                    };
+                    Some((param.to_string(), value))
+                })
                 .collect::<BTreeMap<_, _>>(),
         )
         .expect("failed to convert parameter values to YAML")
     }
 
+    /// Read and deserialize a group of parameters, such as the VM limits, from the
+    /// `ParameterTable`. Parameters the target type can default are optional.
+    fn get_yaml<T: serde::de::DeserializeOwned>(
+        &self,
+        params: impl Iterator<Item = &'static Parameter> + Clone,
+    ) -> Result<T, InvalidConfigError> {
+        serde_yaml::from_value(self.yaml_map(params.clone(), |_| false)).map_err(|err| {
+            // If the group only fails because of an absent parameter the target type can't
+            // default, report that parameter instead.
+            let parses_without = |skipped: Option<Parameter>| {
+                let value = self.yaml_map(params.clone(), |param| Some(param) != skipped);
+                serde_yaml::from_value::<T>(value).is_ok()
+            };
+            if !parses_without(None) {
+                return InvalidConfigError::InvalidYaml(err);
+            }
+            params
+                .clone()
+                .find(|param| {
+                    !self.parameters.contains_key(param) && !parses_without(Some(**param))
+                })
+                .map_or(InvalidConfigError::InvalidYaml(err), |param| {
+                    InvalidConfigError::MissingParameter(*param)
+                })
+        })
+    }
+
     /// Read and parse a typed parameter from the `ParameterTable`.
     fn get<'a, T>(&'a self, key: Parameter) -> Result<T, InvalidConfigError>
     where
@@ -585,6 +818,28 @@ impl ParameterTable {
     }
 }
 
+/// A value of the right shape for `param`, used to stand in for absent parameters.
+fn placeholder_value(param: Parameter) -> ParameterValue {
+    match param.value_type() {
+        ParameterValueType::U64 => ParameterValue::U64(0),
+        ParameterValueType::U128 => ParameterValue::String("0".to_string()),
+        ParameterValueType::Rational => ParameterValue::Rational { numerator: 0, denominator: 1 },
+        ParameterValueType::ParameterCost => ParameterValue::ParameterCost { gas: 0, compute: 0 },
+        ParameterValueType::Fee => ParameterValue::Fee {
+            send_sir: FeeComponent::Gas(Gas::ZERO),
+            send_not_sir: FeeComponent::Gas(Gas::ZERO),
+            execution: FeeComponent::Gas(Gas::ZERO),
+        },
+        ParameterValueType::String => match param {
+            Parameter::VmKind => ParameterValue::String("Wasmtime".to_string()),
+            Parameter::GasDistributionStrategy => ParameterValue::String("Weighted".to_string()),
+            _ => ParameterValue::String("near".to_string()),
+        },
+        ParameterValueType::Flag => ParameterValue::Flag(false),
+        ParameterValueType::List => ParameterValue::Vec(vec![]),
+    }
+}
+
 /// Represents values supported by parameter diff config.
 #[derive(serde::Deserialize, Clone, Debug)]
 struct ParameterDiffConfigValue {
@@ -595,45 +850,89 @@ struct ParameterDiffConfigValue {
 impl std::str::FromStr for ParameterTableDiff {
     type Err = InvalidConfigError;
     fn from_str(arg: &str) -> Result<ParameterTableDiff, InvalidConfigError> {
+        Self::parse(arg, false, false).map(|(diff, _)| diff)
+    }
+}
+
+impl ParameterTableDiff {
+    /// Like `from_str`, but integers which don't fit into a `u64` are rejected, as in
+    /// `ParameterTable::from_str_strict`.
+    #[cfg(test)]
+    pub(crate) fn from_str_strict(arg: &str) -> Result<ParameterTableDiff, InvalidConfigError> {
+        Self::parse(arg, false, true).map(|(diff, _)| diff)
+    }
+
+    /// Like `from_str`, but keys which are not known parameters are skipped
+    /// instead of failing the whole diff. Returns the diff of the known
+    /// parameters, together with the skipped keys, so that the caller can
+    /// warn about them.
+    pub(crate) fn from_str_lenient(
+        arg: &str,
+    ) -> Result<(ParameterTableDiff, Vec<String>), InvalidConfigError> {
+        Self::parse(arg, true, false)
+    }
+
+    fn parse(
+        arg: &str,
+        skip_unknown: bool,
+        strict: bool,
+    ) -> Result<(ParameterTableDiff, Vec<String>), InvalidConfigError> {
         let yaml_map: BTreeMap<String, ParameterDiffConfigValue> =
             serde_yaml::from_str(arg).map_err(|err| InvalidConfigError::InvalidYaml(err))?;
 
-        let parameters = yaml_map
-            .iter()
-            .map(|(key, value)| {
-                let typed_key: Parameter = key
-                    .parse()
-                    .map_err(|err| InvalidConfigError::UnknownParameter(err, key.to_owned()))?;
+        let mut parameters = BTreeMap::new();
+        let mut skipped = vec![];
+        for (key, value) in &yaml_map {
+            let typed_key: Parameter = match key.parse() {
+                Ok(typed_key) => typed_key,
+                Err(_) if skip_unknown => {
+                    skipped.push(key.to_owned());
+                    continue;
+                }
+                Err(err) => return Err(InvalidConfigError::UnknownParameter(err, key.to_owned())),
+            };
+            let strict = strict && typed_key.value_type() != ParameterValueType::U128;
 
-                let old_value =
-                    if let Some(s) = &value.old { Some(parse_parameter_value(s)?) } else { None };
+            let old_value = if let Some(s) = &value.old {
+                Some(parse_parameter_value(s, strict)?)
+            } else {
+                None
+            };
 
-                let new_value =
-                    if let Some(s) = &value.new { Some(parse_parameter_value(s)?) } else { None };
+            let new_value = if let Some(s) = &value.new {
+                Some(parse_parameter_value(s, strict)?)
+            } else {
+                None
+            };
 
-                Ok((typed_key, (old_value, new_value)))
-            })
-            .collect::<Result<BTreeMap<_, _>, _>>()?;
-        Ok(ParameterTableDiff { parameters })
+            parameters.insert(typed_key, (old_value, new_value));
+        }
+        Ok((ParameterTableDiff { parameters }, skipped))
     }
 }
 
 /// Parses a value from YAML to a more restricted type of parameter values.
-fn parse_parameter_value(value: &serde_yaml::Value) -> Result<ParameterValue, InvalidConfigError> {
-    Ok(serde_yaml::from_value(canonicalize_yaml_value(value)?)
+///
+/// In `strict` mode, integers which don't fit into a `u64` are an error.
+fn parse_parameter_value(
+    value: &serde_yaml::Value,
+    strict: bool,
+) -> Result<ParameterValue, InvalidConfigError> {
+    Ok(serde_yaml::from_value(canonicalize_yaml_value(value, strict)?)
         .map_err(|err| InvalidConfigError::InvalidYaml(err))?)
 }
 
 /// Recursively canonicalizes values inside of the YAML structure.
 fn canonicalize_yaml_value(
     value: &serde_yaml::Value,
+    strict: bool,
 ) -> Result<serde_yaml::Value, InvalidConfigError> {
     Ok(match value {
-        serde_yaml::Value::String(s) => canonicalize_yaml_string(s)?,
+        serde_yaml::Value::String(s) => canonicalize_yaml_string(s, strict)?,
         serde_yaml::Value::Mapping(m) => serde_yaml::Value::Mapping(
             m.iter()
                 .map(|(key, value)| {
-                    let canonical_value = canonicalize_yaml_value(value)?;
+                    let canonical_value = canonicalize_yaml_value(value, strict)?;
                     Ok((key.clone(), canonical_value))
                 })
                 .collect::<Result<_, _>>()?,
@@ -649,7 +948,10 @@ fn canonicalize_yaml_value(
 ///
 /// The main purpose of this function is to add support for integers with underscore digit
 /// separators which we use in the config but are not supported in YAML.
-fn canonicalize_yaml_string(value: &str) -> Result<serde_yaml::Value, InvalidConfigError> {
+fn canonicalize_yaml_string(
+    value: &str,
+    strict: bool,
+) -> Result<serde_yaml::Value, InvalidConfigError> {
     if value.is_empty() {
         return Ok(serde_yaml::Value::Null);
     }
@@ -662,6 +964,8 @@ fn canonicalize_yaml_string(value: &str) -> Result<serde_yaml::Value, InvalidCon
         if raw_number.len() < 20 {
             serde_yaml::from_str(&raw_number)
                 .map_err(|err| InvalidConfigError::ValueParseError(err, value.to_owned()))
+        } else if strict && raw_number.parse::<u64>().is_err() {
+            Err(InvalidConfigError::IntegerTooLarge(value.to_owned()))
         } else {
             Ok(serde_yaml::Value::String(raw_number))
         }
@@ -674,10 +978,13 @@ fn canonicalize_yaml_string(value: &str) -> Result<serde_yaml::Value, InvalidCon
 mod tests {
     use super::{
         InvalidConfigError, ParameterTable, ParameterTableDiff, ParameterValue,
-        parse_parameter_value,
+        ValueConversionError, parse_parameter_value,
     };
     use crate::Parameter;
+    use crate::config::FunctionCallAllowlist;
     use assert_matches::assert_matches;
+    use num_rational::Rational32;
+    use std::cmp::Ordering;
     use std::collections::BTreeMap;
 
     #[track_caller]
@@ -697,6 +1004,7 @@ mod tests {
                 assert!(!value.is_empty(), "omit the parameter in the test instead");
                 parse_parameter_value(
                     &serde_yaml::from_str(value).expect("Test data has invalid YAML"),
+                    false,
                 )
                 .unwrap()
             })
@@ -848,6 +1156,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parameter_table_strict_integer_range() {
+        let config = "max_gas_burnt: 300_000_000_000_000_000_000\n\
+                      storage_amount_per_byte: 10_000_000_000_000_000_000";
+        // Lenient parsing keeps the oversized integer as a string.
+        let params: ParameterTable = config.parse().unwrap();
+        assert_eq!(
+            params.parameters[&Parameter::MaxGasBurnt],
+            ParameterValue::String("300000000000000000000".to_string())
+        );
+        assert_matches!(
+            ParameterTable::from_str_strict(config),
+            Err(InvalidConfigError::IntegerTooLarge(value)) => {
+                assert_eq!(value, "300_000_000_000_000_000_000");
+            }
+        );
+
+        // `u128` parameters and integers that fit into a `u64` are accepted.
+        let params = ParameterTable::from_str_strict(
+            "max_gas_burnt: 18_446_744_073_709_551_615\n\
+             storage_amount_per_byte: 10_000_000_000_000_000_000",
+        )
+        .unwrap();
+        assert_eq!(
+            params.parameters[&Parameter::StorageAmountPerByte],
+            ParameterValue::String("10000000000000000000000".to_string())
+        );
+
+        // Diffs are checked the same way.
+        let diff = "max_gas_burnt: { old: 1_000, new: 10_000_000_000_000_000_000 }\n\
+                    storage_amount_per_byte: { old: 1, new: 10_000_000_000_000_000_000 }";
+        assert!(diff.parse::<ParameterTableDiff>().is_ok());
+        assert_matches!(
+            ParameterTableDiff::from_str_strict(diff),
+            Err(InvalidConfigError::IntegerTooLarge(value)) => {
+                assert_eq!(value, "10_000_000_000_000_000_000");
+            }
+        );
+        assert!(
+            ParameterTableDiff::from_str_strict(
+                "storage_amount_per_byte: { old: 1, new: 10_000_000_000_000_000_000 }"
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parameter_value_numeric_comparison() {
+        let number = |v| ParameterValue::U64(v);
+        let rational = |numerator, denominator| ParameterValue::Rational { numerator, denominator };
+        let cost = |gas, compute| ParameterValue::ParameterCost { gas, compute };
+
+        assert_eq!(number(7).as_u64(), Some(7));
+        assert_eq!(rational(1, 3).as_u64(), None);
+        assert_eq!(rational(2, 6).as_rational(), Some(Rational32::new(1, 3)));
+        assert_eq!(rational(1, 0).as_rational(), None);
+        assert_eq!(number(7).as_rational(), None);
+
+        assert_eq!(number(1).partial_cmp_numeric(&number(2)), Some(Ordering::Less));
+        assert_eq!(rational(1, 2).partial_cmp_numeric(&rational(2, 4)), Some(Ordering::Equal));
+        assert_eq!(rational(2, 3).partial_cmp_numeric(&rational(1, 2)), Some(Ordering::Greater));
+        // Only the gas part of a cost is compared, plain numbers are costs as well.
+        assert_eq!(cost(5, 100).partial_cmp_numeric(&cost(6, 1)), Some(Ordering::Less));
+        assert_eq!(number(5).partial_cmp_numeric(&cost(5, 100)), Some(Ordering::Equal));
+
+        assert_eq!(number(1).partial_cmp_numeric(&rational(1, 1)), None);
+        assert_eq!(
+            ParameterValue::String("near".to_string()).partial_cmp_numeric(&number(1)),
+            None
+        );
+        assert_eq!(
+            ParameterValue::Flag(true).partial_cmp_numeric(&ParameterValue::Flag(false)),
+            None
+        );
+    }
+
     #[test]
     fn test_parameter_table_invalid_key_in_diff() {
         assert_matches!(
@@ -859,6 +1243,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parameter_table_unknown_key_in_lenient_diff() {
+        let mut params: ParameterTable = "wasm_regular_op_cost: 100".parse().unwrap();
+        let (diff, skipped) = ParameterTableDiff::from_str_lenient(
+            "invalid_key: { new: 100 }\nwasm_regular_op_cost: { old: 100, new: 200 }",
+        )
+        .unwrap();
+        assert_eq!(skipped, vec!["invalid_key".to_string()]);
+        params.apply_diff(diff).unwrap();
+        assert_eq!(
+            params.parameters,
+            BTreeMap::from([(Parameter::WasmRegularOpCost, ParameterValue::U64(200))])
+        );
+    }
+
     #[test]
     fn test_parameter_table_no_key() {
         assert_matches!(
@@ -1028,6 +1427,7 @@ mod tests {
                 Parameter::WasmStorageReadBase,
             ]
             .iter(),
+            |_| false,
         );
         assert_eq!(
             yaml,
@@ -1041,4 +1441,174 @@ mod tests {
             .unwrap()
         );
     }
+
+    #[test]
+    fn test_parameter_table_to_json_round_trip() {
+        let params: ParameterTable = r#"
+burnt_gas_reward: { numerator: 1, denominator: 3 }
+storage_amount_per_byte: 10_000_000_000_000_000_000
+registrar_account_id: registrar
+max_gas_burnt: 300_000_000_000_000
+flat_storage_reads: true
+wasm_base: { gas: 5, compute: 7 }
+action_transfer: {
+  send_sir: 100,
+  send_not_sir: { gas: 200, compute: 400 },
+  execution: 300
+}
+"#
+        .parse()
+        .unwrap();
+
+        let json = params.to_json();
+        assert_eq!(
+            json["burnt_gas_reward"],
+            serde_json::json!({ "numerator": 1, "denominator": 3 })
+        );
+        assert_eq!(json["storage_amount_per_byte"], serde_json::json!("10000000000000000000000"));
+        assert_eq!(json["wasm_base"], serde_json::json!({ "gas": 5, "compute": 7 }));
+        assert_eq!(
+            json["action_transfer"],
+            serde_json::json!({
+                "send_sir": 100,
+                "send_not_sir": { "gas": 200, "compute": 400 },
+                "execution": 300,
+            })
+        );
+
+        let round_trip: BTreeMap<Parameter, ParameterValue> =
+            serde_json::from_value::<BTreeMap<String, ParameterValue>>(json)
+                .unwrap()
+                .into_iter()
+                .map(|(key, value)| (key.parse().unwrap(), value))
+                .collect();
+        assert_eq!(round_trip, params.parameters);
+    }
+
+    #[test]
+    fn test_parameter_table_diff_against() {
+        let old: ParameterTable = r#"
+wasm_regular_op_cost: 100
+max_memory_pages: 512
+burnt_gas_reward: { numerator: 1, denominator: 3 }
+"#
+        .parse()
+        .unwrap();
+        let new: ParameterTable = r#"
+wasm_regular_op_cost: 200
+burnt_gas_reward: { numerator: 1, denominator: 3 }
+flat_storage_reads: true
+"#
+        .parse()
+        .unwrap();
+
+        let diff = old.diff_against(&new);
+        assert_eq!(
+            diff.parameters,
+            BTreeMap::from([
+                (
+                    Parameter::WasmRegularOpCost,
+                    (Some(ParameterValue::U64(100)), Some(ParameterValue::U64(200)))
+                ),
+                (Parameter::MaxMemoryPages, (Some(ParameterValue::U64(512)), None)),
+                (Parameter::FlatStorageReads, (None, Some(ParameterValue::Flag(true)))),
+            ])
+        );
+
+        let mut params: ParameterTable = r#"
+wasm_regular_op_cost: 100
+max_memory_pages: 512
+burnt_gas_reward: { numerator: 1, denominator: 3 }
+"#
+        .parse()
+        .unwrap();
+        params.apply_diff(diff).unwrap();
+        assert_eq!(params.parameters, new.parameters);
+        assert!(new.diff_against(&params).parameters.is_empty());
+    }
+
+    #[test]
+    fn test_parameter_table_validate_complete() {
+        let mut params: ParameterTable =
+            include_str!("../res/runtime_configs/parameters.yaml").parse().unwrap();
+        assert_eq!(params.validate_complete(), Ok(()));
+
+        params.parameters.remove(&Parameter::MaxGasBurnt);
+        params.parameters.remove(&Parameter::WasmRegularOpCost);
+        params.parameters.remove(&Parameter::ActionTransfer);
+        // Optional limits are not required for the conversion.
+        params.parameters.remove(&Parameter::MaxFunctionsNumberPerContract);
+        params.parameters.remove(&Parameter::AccountIdValidityRulesVersion);
+        let mut expected =
+            vec![Parameter::MaxGasBurnt, Parameter::WasmRegularOpCost, Parameter::ActionTransfer];
+        expected.sort();
+        assert_eq!(params.validate_complete(), Err(expected));
+
+        let empty = ParameterTable { parameters: BTreeMap::new() };
+        let missing = empty.validate_complete().unwrap_err();
+        assert!(missing.contains(&Parameter::RegistrarAccountId));
+        assert!(missing.contains(&Parameter::VmKind));
+        assert!(!missing.contains(&Parameter::MaxFunctionsNumberPerContract));
+    }
+
+    #[test]
+    fn test_parameter_table_compute_override() {
+        check_parameter_table(
+            "wasm_storage_read_base: 100\nwasm_storage_write_base: { gas: 200, compute: 300 }",
+            &[r#"
+wasm_storage_read_base: { old: 100, new: { compute: 1_000 } }
+wasm_storage_write_base: { old: { gas: 200, compute: 300 }, new: { compute: 2_000 } }
+"#],
+            [
+                (Parameter::WasmStorageReadBase, "{ gas: 100, compute: 1_000 }"),
+                (Parameter::WasmStorageWriteBase, "{ gas: 200, compute: 2_000 }"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parameter_table_compute_override_on_non_cost() {
+        assert_matches!(
+            check_invalid_parameter_table(
+                "max_gas_burnt: 100",
+                &["max_gas_burnt: { old: 100, new: { compute: 1_000 } }"]
+            ),
+            InvalidConfigError::ComputeOverrideOnNonCost(Parameter::MaxGasBurnt, Some(value)) => {
+                assert_eq!(*value, ParameterValue::U64(100));
+            }
+        );
+        assert_matches!(
+            check_invalid_parameter_table(
+                "burnt_gas_reward: { numerator: 1, denominator: 3 }",
+                &[
+                    "burnt_gas_reward: { old: { numerator: 1, denominator: 3 }, new: { compute: 1 } }"
+                ]
+            ),
+            InvalidConfigError::ComputeOverrideOnNonCost(Parameter::BurntGasReward, Some(_))
+        );
+    }
+
+    #[test]
+    fn test_parameter_table_function_call_allowlist() {
+        let params: ParameterTable =
+            r#"function_call_allowlist: ["alice.near:foo", "alice.near:bar"]"#.parse().unwrap();
+        let allowlist: FunctionCallAllowlist =
+            params.get(Parameter::FunctionCallAllowlist).unwrap();
+        assert_eq!(
+            allowlist,
+            FunctionCallAllowlist::new([
+                ("alice.near".parse().unwrap(), "foo".to_string()),
+                ("alice.near".parse().unwrap(), "bar".to_string()),
+            ])
+        );
+
+        let params: ParameterTable = r#"function_call_allowlist: ["alice.near"]"#.parse().unwrap();
+        assert_matches!(
+            params.get::<FunctionCallAllowlist>(Parameter::FunctionCallAllowlist),
+            Err(InvalidConfigError::ValueConversionError(
+                ValueConversionError::ParseAllowlistEntry(_),
+                Parameter::FunctionCallAllowlist
+            ))
+        );
+    }
 }
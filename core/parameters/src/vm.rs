@@ -277,6 +277,13 @@ impl Config {
         self.limit_config.max_gas_burnt = Gas::MAX;
     }
 
+    /// Removes the limit on burnt gas while keeping all costs intact, unlike
+    /// `make_free`. Allows tests to observe realistic gas usage without
+    /// running into `max_gas_burnt`. The prepaid gas still applies.
+    pub fn make_unlimited_gas(&mut self) {
+        self.limit_config.max_gas_burnt = Gas::MAX;
+    }
+
     /// Enable all protocol features. Only used for gas cost estimations.
     pub fn enable_all_features(&mut self) {
         self.eth_implicit_accounts = true;
@@ -1,6 +1,11 @@
 use crate::hash::{CryptoHash, hash as sha256};
 use std::fmt::{Debug, Formatter};
 
+/// Hash of the contract code `bytes`, the same as `ContractCode::hash` of the code.
+pub fn contract_code_hash(bytes: &[u8]) -> CryptoHash {
+    sha256(bytes)
+}
+
 #[derive(Clone)]
 pub struct ContractCode {
     code: Vec<u8>,
@@ -9,8 +14,8 @@ pub struct ContractCode {
 
 impl ContractCode {
     pub fn new(code: Vec<u8>, hash: Option<CryptoHash>) -> ContractCode {
-        let hash = hash.unwrap_or_else(|| sha256(&code));
-        debug_assert_eq!(hash, sha256(&code));
+        let hash = hash.unwrap_or_else(|| contract_code_hash(&code));
+        debug_assert_eq!(hash, contract_code_hash(&code));
 
         ContractCode { code, hash }
     }
@@ -45,3 +50,15 @@ impl Debug for ContractCode {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ContractCode, contract_code_hash};
+
+    #[test]
+    fn test_contract_code_hash() {
+        for code in [vec![], b"\0asm\x01\0\0\0".to_vec(), vec![42; 1000]] {
+            assert_eq!(&contract_code_hash(&code), ContractCode::new(code.clone(), None).hash());
+        }
+    }
+}
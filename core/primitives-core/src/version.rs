@@ -450,6 +450,9 @@ pub enum ProtocolFeature {
     /// `FunctionCall` back to the contract account as a reward. Sets the
     /// `burnt_gas_reward` parameter from 30% (3/10) to 0%.
     RemoveGasRewards,
+    /// Reject delegate actions whose inner actions serialize to more than the
+    /// `combined_transactions_size_limit`, before generating a receipt from them.
+    DelegateActionSizeLimit,
 }
 
 impl ProtocolFeature {
@@ -588,6 +591,7 @@ impl ProtocolFeature {
             // that always enables this for mocknet (see config_mocknet function).
             ProtocolFeature::ShuffleShardAssignments => 143,
             ProtocolFeature::EarlyKickout => 152,
+            ProtocolFeature::DelegateActionSizeLimit => 156,
             // Spice is setup to include nightly, but not be part of it for now so that features
             // that are released before spice can be tested properly.
             ProtocolFeature::Spice => 180,
@@ -600,6 +604,15 @@ impl ProtocolFeature {
     }
 }
 
+/// Returns the protocol version in which `feature` gets activated.
+///
+/// This is the same as `ProtocolFeature::protocol_version`, exposed as a free function for
+/// tooling which needs to look up the activation version of a feature, e.g. to generate
+/// documentation.
+pub const fn protocol_feature_activation(feature: ProtocolFeature) -> ProtocolVersion {
+    feature.protocol_version()
+}
+
 /// The protocol version of the genesis block on mainnet and testnet.
 pub const PROD_GENESIS_PROTOCOL_VERSION: ProtocolVersion = 29;
 
@@ -651,3 +664,22 @@ pub const PROTOCOL_VERSION: ProtocolVersion = if cfg!(feature = "protocol_featur
 } else {
     STABLE_PROTOCOL_VERSION
 };
+
+#[cfg(test)]
+mod tests {
+    use super::{ProtocolFeature, protocol_feature_activation};
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_protocol_feature_activation() {
+        assert_eq!(
+            protocol_feature_activation(
+                ProtocolFeature::_DeprecatedExcludeExistingCodeFromWitnessForCodeLen
+            ),
+            83
+        );
+        assert_eq!(protocol_feature_activation(ProtocolFeature::_DeprecatedWasmtime), 84);
+        assert_eq!(protocol_feature_activation(ProtocolFeature::RemoveGasRewards), 87);
+        assert_eq!(protocol_feature_activation(ProtocolFeature::EarlyKickout), 152);
+    }
+}
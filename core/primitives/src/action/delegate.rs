@@ -357,6 +357,16 @@ impl DelegateAction {
     }
 }
 
+/// Returns true if any of the inner actions of `delegate_action` is itself a delegate action.
+///
+/// `NonDelegateAction` rules this out by construction, so this should never be the case.
+/// It is meant as a defense-in-depth check for deserialized data.
+pub fn contains_nested_delegate<'a>(
+    delegate_action: impl Into<VersionedDelegateActionRef<'a>>,
+) -> bool {
+    delegate_action.into().actions().iter().any(|action| action.0.is_delegate())
+}
+
 /// This is Action which mustn't contain DelegateAction.
 ///
 /// This struct is needed to avoid the recursion when Action/DelegateAction is deserialized.
@@ -578,6 +588,33 @@ mod tests {
         }))
     }
 
+    #[test]
+    fn test_contains_nested_delegate() {
+        let actions = vec![
+            NonDelegateAction::try_from(Action::CreateAccount(CreateAccountAction {})).unwrap(),
+        ];
+        let delegate_action = DelegateAction {
+            sender_id: "aaa".parse().unwrap(),
+            receiver_id: "bbb".parse().unwrap(),
+            actions: actions.clone(),
+            nonce: 1,
+            max_block_height: 2,
+            public_key: PublicKey::empty(KeyType::ED25519),
+        };
+        assert!(!contains_nested_delegate(&delegate_action));
+        assert!(!contains_nested_delegate(&DelegateAction { actions: vec![], ..delegate_action }));
+
+        let delegate_action_v2 = DelegateActionV2 {
+            sender_id: "aaa".parse().unwrap(),
+            receiver_id: "bbb".parse().unwrap(),
+            actions,
+            nonce: TransactionNonce::from_nonce_and_index(1, 0),
+            max_block_height: 2,
+            public_key: PublicKey::empty(KeyType::ED25519),
+        };
+        assert!(!contains_nested_delegate(&delegate_action_v2));
+    }
+
     #[test]
     fn test_delegate_action_deserialization() {
         // Expected an error. Buffer is empty
@@ -369,6 +369,37 @@ pub enum Action {
     DelegateV2(Box<delegate::VersionedSignedDelegateAction>) = 14,
 }
 
+/// Returns the indices of `actions` that a function call access key cannot authorize, i.e.
+/// all actions other than function calls without an attached deposit.
+///
+/// Note that a function call access key can only sign a transaction or delegate action
+/// consisting of exactly one action, so any list with more than one action needs a full
+/// access key regardless of the returned indices.
+pub fn actions_requiring_full_access(actions: &[Action]) -> Vec<usize> {
+    actions
+        .iter()
+        .enumerate()
+        .filter(|(_, action)| match action {
+            Action::FunctionCall(function_call) => function_call.deposit > Balance::ZERO,
+            Action::CreateAccount(_)
+            | Action::DeployContract(_)
+            | Action::Transfer(_)
+            | Action::Stake(_)
+            | Action::AddKey(_)
+            | Action::DeleteKey(_)
+            | Action::DeleteAccount(_)
+            | Action::Delegate(_)
+            | Action::DeployGlobalContract(_)
+            | Action::UseGlobalContract(_)
+            | Action::DeterministicStateInit(_)
+            | Action::TransferToGasKey(_)
+            | Action::WithdrawFromGasKey(_)
+            | Action::DelegateV2(_) => true,
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
 const _: () = assert!(
     // 1 word for tag plus the largest variant `DeployContractAction` which is a 3-word `Vec`.
     // The `<=` check covers platforms that have pointers smaller than 8 bytes as well as random
@@ -532,3 +563,42 @@ impl From<WithdrawFromGasKeyAction> for Action {
         Self::WithdrawFromGasKey(Box::new(action))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Action, CreateAccountAction, DeleteAccountAction, DeleteKeyAction, DeployContractAction,
+        FunctionCallAction, TransferAction, actions_requiring_full_access,
+    };
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives_core::types::{Balance, Gas};
+
+    fn function_call(deposit: Balance) -> Action {
+        Action::FunctionCall(Box::new(FunctionCallAction {
+            method_name: "main".to_string(),
+            args: vec![],
+            gas: Gas::from_teragas(10),
+            deposit,
+        }))
+    }
+
+    #[test]
+    fn test_actions_requiring_full_access() {
+        let public_key = PublicKey::empty(KeyType::ED25519);
+        let actions = vec![
+            function_call(Balance::ZERO),
+            Action::DeployContract(DeployContractAction { code: vec![] }),
+            function_call(Balance::from_yoctonear(1)),
+            Action::Transfer(TransferAction { deposit: Balance::from_near(1) }),
+            Action::CreateAccount(CreateAccountAction {}),
+            Action::DeleteKey(Box::new(DeleteKeyAction { public_key })),
+            function_call(Balance::ZERO),
+            Action::DeleteAccount(DeleteAccountAction {
+                beneficiary_id: "alice.near".parse().unwrap(),
+            }),
+        ];
+        assert_eq!(actions_requiring_full_access(&actions), vec![1, 2, 3, 4, 5, 7]);
+        assert!(actions_requiring_full_access(&[function_call(Balance::ZERO)]).is_empty());
+        assert!(actions_requiring_full_access(&[]).is_empty());
+    }
+}
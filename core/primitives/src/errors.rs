@@ -849,6 +849,20 @@ pub enum ActionErrorKind {
         nonce_index: NonceIndex,
         num_nonces: NonceIndex,
     } = 26,
+    /// The called method is not on the runtime's function call allowlist
+    MethodNotAllowlisted {
+        account_id: AccountId,
+        method_name: String,
+    } = 27,
+    /// The inner actions of a DelegateAction are larger than the combined transactions size limit
+    DelegateActionTooLarge {
+        size: u64,
+        limit: u64,
+    } = 28,
+    /// The chunk already contains `max_validator_proposals_per_chunk` validator proposals
+    ValidatorProposalsLimitExceeded {
+        limit: u64,
+    } = 29,
 }
 
 impl From<ActionErrorKind> for ActionError {
@@ -1142,6 +1156,12 @@ impl Display for ActionErrorKind {
                 receiver_id, sender_id
             ),
             ActionErrorKind::DelegateActionExpired => write!(f, "DelegateAction has expired"),
+            ActionErrorKind::DelegateActionTooLarge { size, limit } => {
+                write!(f, "DelegateAction inner actions size {} exceeds the limit {}", size, limit)
+            }
+            ActionErrorKind::ValidatorProposalsLimitExceeded { limit } => {
+                write!(f, "The chunk already contains the maximum of {} validator proposals", limit)
+            }
             ActionErrorKind::DelegateActionAccessKeyError(access_key_error) => {
                 Display::fmt(&access_key_error, f)
             }
@@ -1163,6 +1183,11 @@ impl Display for ActionErrorKind {
             ActionErrorKind::GlobalContractDoesNotExist { identifier } => {
                 write!(f, "Global contract identifier {:?} not found", identifier)
             }
+            ActionErrorKind::MethodNotAllowlisted { account_id, method_name } => write!(
+                f,
+                "Method {:?} of account {:?} is not on the function call allowlist",
+                method_name, account_id
+            ),
             ActionErrorKind::GasKeyDoesNotExist { account_id, public_key } => {
                 write!(f, "Gas key {} does not exist for account {}", public_key, account_id)
             }
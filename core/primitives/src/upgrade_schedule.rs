@@ -70,6 +70,9 @@ impl ProtocolUpgradeVotingSchedule {
     /// way test neard upgrades.
     ///
     /// Otherwise it will use the provided schedule.
+    ///
+    /// Prefer `new_from_schedule` in tests which can pass the schedule
+    /// explicitly, as the environment is shared by all tests in the process.
     pub fn new_from_env_or_schedule(
         min_supported_protocol_version: ProtocolVersion,
         client_protocol_version: ProtocolVersion,
@@ -88,7 +91,15 @@ impl ProtocolUpgradeVotingSchedule {
                 "setting protocol upgrade override, this is fine in tests but should be avoided otherwise"
             );
         }
+        Self::new_from_schedule(client_protocol_version, schedule)
+    }
 
+    /// This method creates an instance of the ProtocolUpgradeVotingSchedule
+    /// from the provided schedule, ignoring the environment override.
+    pub fn new_from_schedule(
+        client_protocol_version: ProtocolVersion,
+        schedule: ProtocolUpgradeVotingScheduleRaw,
+    ) -> Result<Self, ProtocolUpgradeVotingScheduleError> {
         // Sanity and invariant checks.
 
         // The final upgrade must be the client protocol version.
@@ -675,4 +686,35 @@ mod tests {
             std::env::remove_var(NEAR_TESTS_PROTOCOL_UPGRADE_OVERRIDE);
         }
     }
+
+    #[test]
+    fn test_explicit_schedule_ignores_env_override() {
+        let client_protocol_version = 100;
+        unsafe {
+            // SAFE: our tests run with nextest with a process-per-test scheme, so in a
+            // single-threaded manner.
+            std::env::set_var(NEAR_TESTS_PROTOCOL_UPGRADE_OVERRIDE, PROTOCOL_UPGRADE_OVERRIDE_NOW);
+        }
+        let datetime =
+            ProtocolUpgradeVotingSchedule::parse_datetime("2999-02-03 23:59:59").unwrap();
+        let schedule = ProtocolUpgradeVotingSchedule::new_from_schedule(
+            client_protocol_version,
+            vec![(datetime, client_protocol_version)],
+        )
+        .unwrap();
+        assert_eq!(schedule.schedule(), &vec![(datetime, client_protocol_version)]);
+
+        let result = ProtocolUpgradeVotingSchedule::new_from_schedule(
+            client_protocol_version,
+            vec![(datetime, client_protocol_version - 1)],
+        );
+        assert!(matches!(
+            result,
+            Err(ProtocolUpgradeVotingScheduleError::InvalidFinalUpgrade(99, 100))
+        ));
+
+        unsafe {
+            std::env::remove_var(NEAR_TESTS_PROTOCOL_UPGRADE_OVERRIDE);
+        }
+    }
 }
@@ -607,6 +607,16 @@ pub struct StateSyncStatusView {
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PeerStoreView {
     pub peer_states: Vec<KnownPeerStateView>,
+    pub bans: Vec<BanView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BanView {
+    pub peer_id: PeerId,
+    pub reason: String,
+    /// Unix timestamp at which the ban expires.
+    pub expires_at: i64,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
@@ -632,8 +642,26 @@ pub struct EdgeView {
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkGraphView {
+    /// PeerId of this node, from which the next hops are computed.
+    pub node_id: PeerId,
+    /// Hash of the node public key, for correlating the logs of this node.
+    pub node_key_fingerprint: String,
     pub edges: Vec<EdgeView>,
     pub next_hops: HashMap<PeerId, Vec<PeerId>>,
+    pub routing_status: RoutingStatusView,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RoutingStatusView {
+    /// Number of active edges in the graph.
+    pub active_edges: usize,
+    /// Number of removed edges (tombstones) in the graph.
+    pub removed_edges: usize,
+    /// Number of peers reachable according to the routing table.
+    pub reachable_peers: usize,
+    /// Number of edges adjacent to this node.
+    pub local_edges: usize,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
@@ -33,6 +33,9 @@ pub struct TrieUpdate {
     contract_storage: ContractStorage,
     committed: RawStateChanges,
     prospective: TrieUpdates,
+    /// Writes made since `start_recording_writes`, in the order they were made.
+    #[cfg(feature = "test_features")]
+    recorded_writes: Option<Vec<TrieKeyValueUpdate>>,
 }
 
 static_assertions::assert_impl_all!(TrieUpdate: Send, Sync);
@@ -84,6 +87,8 @@ impl TrieUpdate {
             contract_storage: ContractStorage::new(trie_storage),
             committed: Default::default(),
             prospective: Default::default(),
+            #[cfg(feature = "test_features")]
+            recorded_writes: None,
         }
     }
 
@@ -96,6 +101,8 @@ impl TrieUpdate {
             contract_storage: ContractStorage::new(self.trie.storage.clone()),
             committed: self.committed.clone(),
             prospective: self.prospective.clone(),
+            #[cfg(feature = "test_features")]
+            recorded_writes: None,
         }
     }
 
@@ -107,6 +114,25 @@ impl TrieUpdate {
         self.prospective.len()
     }
 
+    /// Starts recording every write, including the ones which don't change the value.
+    #[cfg(feature = "test_features")]
+    pub fn start_recording_writes(&mut self) {
+        self.recorded_writes = Some(Vec::new());
+    }
+
+    /// Stops recording and returns the writes made since `start_recording_writes`.
+    #[cfg(feature = "test_features")]
+    pub fn take_recorded_writes(&mut self) -> Vec<TrieKeyValueUpdate> {
+        self.recorded_writes.take().unwrap_or_default()
+    }
+
+    #[cfg(feature = "test_features")]
+    fn record_write(&mut self, update: &TrieKeyValueUpdate) {
+        if let Some(writes) = &mut self.recorded_writes {
+            writes.push(update.clone());
+        }
+    }
+
     pub fn trie(&self) -> &Trie {
         &self.trie
     }
@@ -162,8 +188,10 @@ impl TrieUpdate {
         // - Using `Vec<u8>` for sorting `BTreeMap` in the same order as a `Trie` and
         //   avoid recomputing `Vec<u8>` every time. It helps for merging iterators.
         // - Using `TrieKey` later for `RawStateChangesWithTrieKey` for State changes RPCs.
-        self.prospective
-            .insert(trie_key.to_vec(), TrieKeyValueUpdate { trie_key, value: Some(value) });
+        let update = TrieKeyValueUpdate { trie_key, value: Some(value) };
+        #[cfg(feature = "test_features")]
+        self.record_write(&update);
+        self.prospective.insert(update.trie_key.to_vec(), update);
     }
 
     pub fn remove(&mut self, trie_key: TrieKey) {
@@ -178,7 +206,10 @@ impl TrieUpdate {
             }
         }
 
-        self.prospective.insert(trie_key.to_vec(), TrieKeyValueUpdate { trie_key, value: None });
+        let update = TrieKeyValueUpdate { trie_key, value: None };
+        #[cfg(feature = "test_features")]
+        self.record_write(&update);
+        self.prospective.insert(update.trie_key.to_vec(), update);
     }
 
     /// Returns the size (in num bytes) of the contract code for the given account.
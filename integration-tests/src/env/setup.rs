@@ -47,6 +47,7 @@ use near_primitives::epoch_info::RngSeed;
 use near_primitives::network::PeerId;
 use near_primitives::test_utils::create_test_signer;
 use near_primitives::types::{AccountId, Balance, BlockHeightDelta, Gas, NumSeats};
+use near_primitives::upgrade_schedule::ProtocolUpgradeVotingSchedule;
 use near_primitives::validator_signer::EmptyValidatorSigner;
 use near_primitives::version::{PROTOCOL_VERSION, get_protocol_upgrade_schedule};
 use near_store::adapter::StoreAdapter;
@@ -462,6 +463,7 @@ pub fn setup_client_with_runtime(
     save_receipt_to_tx: bool,
     protocol_version_check: ProtocolVersionCheckConfig,
     transaction_pool_size_limit: Option<u64>,
+    protocol_upgrade_schedule: Option<ProtocolUpgradeVotingSchedule>,
     snapshot_callbacks: Option<SnapshotCallbacks>,
     partial_witness_adapter: PartialWitnessSenderForClient,
     validator_signer: MutableValidatorSigner,
@@ -479,7 +481,8 @@ pub fn setup_client_with_runtime(
     config.save_receipt_to_tx = save_receipt_to_tx;
     config.protocol_version_check = protocol_version_check;
     config.epoch_length = chain_genesis.epoch_length;
-    let protocol_upgrade_schedule = get_protocol_upgrade_schedule(&chain_genesis.chain_id);
+    let protocol_upgrade_schedule = protocol_upgrade_schedule
+        .unwrap_or_else(|| get_protocol_upgrade_schedule(&chain_genesis.chain_id));
     let multi_spawner = AsyncComputationMultiSpawner::default()
         .custom_apply_chunks(Arc::new(RayonAsyncComputationSpawner)); // Use rayon instead of the default thread pool
     // TestEnv bypasses chunk validation actors and handles chunk validation
@@ -41,6 +41,7 @@ use near_primitives::stateless_validation::state_witness::ChunkStateWitness;
 use near_primitives::test_utils::create_test_signer;
 use near_primitives::transaction::{Action, FunctionCallAction, SignedTransaction};
 use near_primitives::types::{AccountId, Balance, BlockHeight, EpochId, Gas, NumSeats, ShardId};
+use near_primitives::upgrade_schedule::ProtocolUpgradeVotingSchedule;
 use near_primitives::utils::MaybeValidated;
 use near_primitives::version::{PROTOCOL_VERSION, ProtocolFeature};
 use near_primitives::views::{
@@ -80,6 +81,7 @@ pub struct TestEnv {
     pub(crate) save_tx_outcomes: bool,
     pub(crate) save_receipt_to_tx: bool,
     pub(crate) protocol_version_check: ProtocolVersionCheckConfig,
+    pub(crate) protocol_upgrade_schedule: Option<ProtocolUpgradeVotingSchedule>,
 }
 
 pub struct StateWitnessPropagationOutput {
@@ -675,6 +677,7 @@ impl TestEnv {
             self.save_receipt_to_tx,
             self.protocol_version_check,
             None,
+            self.protocol_upgrade_schedule.clone(),
             None,
             self.clients[idx].partial_witness_adapter.clone(),
             self.clients[idx].validator_signer.clone(),
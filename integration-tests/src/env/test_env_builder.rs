@@ -26,6 +26,7 @@ use near_primitives::epoch_info::RngSeed;
 use near_primitives::epoch_manager::{AllEpochConfigTestOverrides, EpochConfig, EpochConfigStore};
 use near_primitives::test_utils::create_test_signer;
 use near_primitives::types::{AccountId, ShardIndex};
+use near_primitives::upgrade_schedule::ProtocolUpgradeVotingSchedule;
 use near_store::genesis::initialize_genesis_state;
 use near_store::test_utils::create_test_store;
 use near_store::{NodeStorage, ShardUId, Store, StoreConfig, TrieConfig};
@@ -60,6 +61,7 @@ pub struct TestEnvBuilder {
     track_all_shards: bool,
     protocol_version_check: ProtocolVersionCheckConfig,
     transaction_pool_size_limit: Option<u64>,
+    protocol_upgrade_schedule: Option<ProtocolUpgradeVotingSchedule>,
 }
 
 /// Builder for the [`TestEnv`] structure.
@@ -91,6 +93,7 @@ impl TestEnvBuilder {
             track_all_shards: false,
             protocol_version_check: Default::default(),
             transaction_pool_size_limit: None,
+            protocol_upgrade_schedule: None,
         }
     }
 
@@ -462,6 +465,16 @@ impl TestEnvBuilder {
         self
     }
 
+    /// Sets the protocol upgrade voting schedule used by all clients, e.g.
+    /// `ProtocolUpgradeVotingSchedule::new_immediate(PROTOCOL_VERSION)` to
+    /// vote for the upgrade right away. If not set, the schedule for the
+    /// chain id is used, which honors the `NEAR_TESTS_PROTOCOL_UPGRADE_OVERRIDE`
+    /// environment variable.
+    pub fn protocol_upgrade_schedule(mut self, schedule: ProtocolUpgradeVotingSchedule) -> Self {
+        self.protocol_upgrade_schedule = Some(schedule);
+        self
+    }
+
     /// Constructs new `TestEnv` structure.
     ///
     /// If no clients were configured (either through count or vector) one
@@ -588,6 +601,7 @@ impl TestEnvBuilder {
                         self.save_receipt_to_tx,
                         self.protocol_version_check,
                         self.transaction_pool_size_limit,
+                        self.protocol_upgrade_schedule.clone(),
                         Some(snapshot_callbacks),
                         partial_witness_adapter.into_multi_sender(),
                         validator_signers[i].clone(),
@@ -654,6 +668,7 @@ impl TestEnvBuilder {
             save_tx_outcomes: self.save_tx_outcomes,
             save_receipt_to_tx: self.save_receipt_to_tx,
             protocol_version_check: self.protocol_version_check,
+            protocol_upgrade_schedule: self.protocol_upgrade_schedule,
         }
     }
 
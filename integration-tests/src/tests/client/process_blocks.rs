@@ -52,6 +52,7 @@ use near_primitives::transaction::{
 };
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{AccountId, Balance, BlockHeight, EpochId, Gas, NumBlocks};
+use near_primitives::upgrade_schedule::ProtocolUpgradeVotingSchedule;
 use near_primitives::version::{PROTOCOL_VERSION, ProtocolFeature};
 use near_primitives::views::{FinalExecutionStatus, QueryRequest, QueryResponseKind};
 use near_primitives_core::num_rational::Ratio;
@@ -471,6 +472,7 @@ async fn client_sync_headers() {
             }],
             num_connected_peers: 1,
             peer_max_count: 1,
+            inbound_draining: false,
             highest_height_peers: vec![HighestHeightPeerInfo {
                 peer_info: peer_info2,
                 genesis_id: Default::default(),
@@ -2444,14 +2446,12 @@ fn test_epoch_multi_protocol_version_change() {
     let v1_upgrade_time = start_time + chrono::Duration::seconds(1);
     let v2_upgrade_time = start_time + chrono::Duration::seconds(3);
 
-    let v1_upgrade_time = v1_upgrade_time.format("%Y-%m-%d %H:%M:%S").to_string();
-    let v2_upgrade_time = v2_upgrade_time.format("%Y-%m-%d %H:%M:%S").to_string();
-
-    let protocol_version_override =
-        format!("{}={},{}={}", v1_upgrade_time, v1, v2_upgrade_time, v2);
-
-    tracing::debug!(target: "test", ?protocol_version_override, "setting the protocol_version_override");
-    unsafe { std::env::set_var("NEAR_TESTS_PROTOCOL_UPGRADE_OVERRIDE", protocol_version_override) };
+    let protocol_upgrade_schedule = ProtocolUpgradeVotingSchedule::new_from_schedule(
+        v2,
+        vec![(v1_upgrade_time, v1), (v2_upgrade_time, v2)],
+    )
+    .unwrap();
+    tracing::debug!(target: "test", ?protocol_upgrade_schedule, "setting the protocol upgrade schedule");
 
     let epoch_length = 5;
     let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 2);
@@ -2462,6 +2462,7 @@ fn test_epoch_multi_protocol_version_change() {
         .clients_count(2)
         .validator_seats(2)
         .nightshade_runtimes(&genesis)
+        .protocol_upgrade_schedule(protocol_upgrade_schedule)
         .build();
 
     let mut seen_v0 = false;
@@ -2519,14 +2520,12 @@ fn slow_test_epoch_multi_protocol_version_change_epoch_overlap() {
     let v1_upgrade_time = start_time + chrono::Duration::seconds(1);
     let v2_upgrade_time = start_time + chrono::Duration::seconds(2);
 
-    let v1_upgrade_time = v1_upgrade_time.format("%Y-%m-%d %H:%M:%S").to_string();
-    let v2_upgrade_time = v2_upgrade_time.format("%Y-%m-%d %H:%M:%S").to_string();
-
-    let protocol_version_override =
-        format!("{}={},{}={}", v1_upgrade_time, v1, v2_upgrade_time, v2);
-
-    tracing::debug!(target: "test", ?protocol_version_override, "setting the protocol_version_override");
-    unsafe { std::env::set_var("NEAR_TESTS_PROTOCOL_UPGRADE_OVERRIDE", protocol_version_override) };
+    let protocol_upgrade_schedule = ProtocolUpgradeVotingSchedule::new_from_schedule(
+        v2,
+        vec![(v1_upgrade_time, v1), (v2_upgrade_time, v2)],
+    )
+    .unwrap();
+    tracing::debug!(target: "test", ?protocol_upgrade_schedule, "setting the protocol upgrade schedule");
 
     let epoch_length = 5;
     let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 2);
@@ -2537,6 +2536,7 @@ fn slow_test_epoch_multi_protocol_version_change_epoch_overlap() {
         .clients_count(2)
         .validator_seats(2)
         .nightshade_runtimes(&genesis)
+        .protocol_upgrade_schedule(protocol_upgrade_schedule)
         .build();
 
     let mut seen_v0 = false;
@@ -54,6 +54,7 @@ fn test_config_duration_all_std() {
                 network_config_overrides: NetworkConfigOverrides {
                     accounts_data_broadcast_rate_limit_burst: Some(0),
                     accounts_data_broadcast_rate_limit_qps: Some(0.0),
+                    accounts_data_broadcast_batch_size: Some(64),
                     connect_to_reliable_peers_on_startup: Some(true),
                     highest_peer_horizon: Some(0),
                     max_routes_to_store: Some(0),
@@ -63,6 +64,8 @@ fn test_config_duration_all_std() {
                     routed_message_ttl: Some(0),
                     routing_table_update_rate_limit_burst: Some(0),
                     routing_table_update_rate_limit_qps: Some(0.0),
+                    chunk_forward_rate_limit_burst: Some(0),
+                    chunk_forward_rate_limit_qps: Some(0.0),
                     received_messages_rate_limits: Some(
                         near_network::MessagesLimitsOverrideConfig::default(),
                     ),
@@ -70,6 +73,11 @@ fn test_config_duration_all_std() {
                     routing_graph_max_edges_per_source: Some(50_000),
                     routing_graph_max_peers: Some(100_000),
                     routing_graph_max_edges: Some(1_000_000),
+                    recent_routed_messages_cache_size: Some(10_000),
+                    route_back_cache_capacity: Some(100_000),
+                    route_back_cache_evict_timeout_millis: Some(120_000),
+                    reconnect_backoff_base_millis: Some(10_000),
+                    reconnect_backoff_max_millis: Some(300_000),
                 },
                 ..Default::default()
             },
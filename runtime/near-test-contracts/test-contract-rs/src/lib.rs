@@ -928,6 +928,24 @@ fn call_promise() {
                     gas,
                 );
                 promise_index
+            } else if let Some(action) = arg.get("action_function_call_weight") {
+                let promise_index = action["promise_index"].as_i64().unwrap() as u64;
+                let method_name = action["method_name"].as_str().unwrap().as_bytes();
+                let arguments = serde_json::to_vec(&action["arguments"]).unwrap();
+                let amount = action["amount"].as_str().unwrap().parse::<u128>().unwrap();
+                let gas = action["gas"].as_i64().unwrap() as u64;
+                let gas_weight = action["gas_weight"].as_i64().unwrap() as u64;
+                promise_batch_action_function_call_weight(
+                    promise_index,
+                    method_name.len() as u64,
+                    method_name.as_ptr() as u64,
+                    arguments.len() as u64,
+                    arguments.as_ptr() as u64,
+                    &amount as *const u128 as *const u64 as u64,
+                    gas,
+                    gas_weight,
+                );
+                promise_index
             } else if let Some(action) = arg.get("action_transfer") {
                 let promise_index = action["promise_index"].as_i64().unwrap() as u64;
                 let amount = action["amount"].as_str().unwrap().parse::<u128>().unwrap();
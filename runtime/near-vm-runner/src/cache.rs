@@ -1,5 +1,5 @@
 // cspell:ignore NOENT, RDONLY, RGRP, RUSR, TRUNC, WGRP, WRONLY, WUSR
-// cspell:ignore mikan, fstat, openat, renameat, unlinkat
+// cspell:ignore mikan, fstat, openat, renameat, unlinkat, mmap, mmapped, munmap
 
 use crate::ContractCode;
 use crate::errors::ContractPrecompilatonResult;
@@ -21,6 +21,8 @@ use rustix::fs::{
 };
 #[cfg(not(windows))]
 use rustix::io::Errno;
+#[cfg(not(windows))]
+use rustix::mm::{MapFlags, ProtFlags, mmap, munmap};
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt;
@@ -31,6 +33,8 @@ use std::num::NonZeroUsize;
 use std::str::FromStr;
 use std::sync::Arc;
 #[cfg(not(windows))]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(not(windows))]
 use std::time::{Duration, Instant};
 
 #[cfg(feature = "wasmtime_vm")]
@@ -55,12 +59,22 @@ pub(crate) fn get_contract_cache_key(
     config: &Config,
     vm_hash: u64,
 ) -> CryptoHash {
-    let key = ContractCacheKey::Version5 {
-        code_hash,
-        vm_config_non_crypto_hash: config.non_crypto_hash(),
-        vm_kind: config.vm_kind,
-        vm_hash,
-    };
+    contract_cache_key_parts(code_hash, config.non_crypto_hash(), config.vm_kind, vm_hash)
+}
+
+/// Compute the cache key for a contract from the individual inputs that
+/// [`get_contract_cache_key`] derives from a [`Config`].
+///
+/// Useful for tooling that needs to locate a cache entry and only has the
+/// serialized config hashes at hand.
+#[cfg(feature = "wasmtime_vm")]
+pub fn contract_cache_key_parts(
+    code_hash: CryptoHash,
+    vm_config_non_crypto_hash: u64,
+    vm_kind: near_parameters::vm::VMKind,
+    vm_hash: u64,
+) -> CryptoHash {
+    let key = ContractCacheKey::Version5 { code_hash, vm_config_non_crypto_hash, vm_kind, vm_hash };
     CryptoHash::hash_borsh(key)
 }
 
@@ -138,6 +152,13 @@ pub trait ContractRuntimeCache: Send + Sync {
     }
     fn put(&self, key: &CryptoHash, value: CompiledContractInfo) -> std::io::Result<()>;
     fn get(&self, key: &CryptoHash) -> std::io::Result<Option<CompiledContractInfo>>;
+    /// Look up several keys at once, returning the entries in the order of `keys`.
+    ///
+    /// The default calls [`Self::get`] for each key; implementations may override it to share
+    /// locks or file descriptors across the lookups.
+    fn get_many(&self, keys: &[CryptoHash]) -> std::io::Result<Vec<Option<CompiledContractInfo>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
         self.get(key).map(|entry| entry.is_some())
     }
@@ -180,6 +201,10 @@ impl ContractRuntimeCache for Box<dyn ContractRuntimeCache> {
         <dyn ContractRuntimeCache>::get(&**self, key)
     }
 
+    fn get_many(&self, keys: &[CryptoHash]) -> std::io::Result<Vec<Option<CompiledContractInfo>>> {
+        <dyn ContractRuntimeCache>::get_many(&**self, keys)
+    }
+
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
         <dyn ContractRuntimeCache>::has(&**self, key)
     }
@@ -206,6 +231,10 @@ impl<C: ContractRuntimeCache> ContractRuntimeCache for &C {
         <C as ContractRuntimeCache>::get(self, key)
     }
 
+    fn get_many(&self, keys: &[CryptoHash]) -> std::io::Result<Vec<Option<CompiledContractInfo>>> {
+        <C as ContractRuntimeCache>::get_many(self, keys)
+    }
+
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
         <C as ContractRuntimeCache>::has(self, key)
     }
@@ -234,6 +263,10 @@ impl ContractRuntimeCache for NoContractRuntimeCache {
     fn get(&self, _: &CryptoHash) -> std::io::Result<Option<CompiledContractInfo>> {
         Ok(None)
     }
+
+    fn get_many(&self, keys: &[CryptoHash]) -> std::io::Result<Vec<Option<CompiledContractInfo>>> {
+        Ok(vec![None; keys.len()])
+    }
 }
 
 #[derive(Default, Clone)]
@@ -263,6 +296,11 @@ impl ContractRuntimeCache for MockContractRuntimeCache {
         Ok(self.store.lock().get(key).map(Clone::clone))
     }
 
+    fn get_many(&self, keys: &[CryptoHash]) -> std::io::Result<Vec<Option<CompiledContractInfo>>> {
+        let store = self.store.lock();
+        Ok(keys.iter().map(|key| store.get(key).cloned()).collect())
+    }
+
     fn handle(&self) -> Box<dyn ContractRuntimeCache> {
         Box::new(self.clone())
     }
@@ -276,6 +314,14 @@ impl fmt::Debug for MockContractRuntimeCache {
     }
 }
 
+/// Sink for fire-and-forget background maintenance jobs.
+pub type BackgroundJobSpawner = Arc<dyn Fn(Box<dyn FnOnce() + Send>) + Send + Sync>;
+
+/// A [`BackgroundJobSpawner`] that sends every job to the void.
+pub fn noop_background_spawner() -> BackgroundJobSpawner {
+    Arc::new(|_job| {})
+}
+
 /// A cache that stores precompiled contract executables in a directory of a filesystem.
 ///
 /// This directory can optionally be a temporary directory. If created with [`Self::test`] the
@@ -286,15 +332,8 @@ impl fmt::Debug for MockContractRuntimeCache {
 ///
 /// This cache implements a size-bounded, best-effort on-disk eviction policy.
 /// Files are tracked by key and on-disk byte size; LRU victims are unlinked on `put`.
-/// (The in-memory cache is separate and VM-specific.)
-/// Sink for fire-and-forget background maintenance jobs.
-pub type BackgroundJobSpawner = Arc<dyn Fn(Box<dyn FnOnce() + Send>) + Send + Sync>;
-
-/// A [`BackgroundJobSpawner`] that sends every job to the void.
-pub fn noop_background_spawner() -> BackgroundJobSpawner {
-    Arc::new(|_job| {})
-}
-
+/// (The in-memory cache is separate and VM-specific.) Passing
+/// [`Self::MAX_DISK_CACHE_BYTES`] as the limit keeps the cache effectively unbounded.
 #[cfg(not(windows))]
 #[derive(Clone)]
 pub struct FilesystemContractRuntimeCache {
@@ -316,6 +355,42 @@ struct FilesystemContractRuntimeCacheState {
     /// Off-loads the on-disk atime refresh; see [`BackgroundJobSpawner`].
     bg_spawner: BackgroundJobSpawner,
     test_temp_dir: Option<tempfile::TempDir>,
+    /// `get` calls that found a valid cached entry.
+    hits: AtomicU64,
+    /// `get` calls that found no entry, or a truncated one.
+    misses: AtomicU64,
+    /// `get` calls that found an entry with an unknown tag.
+    malformed: AtomicU64,
+    /// Successful `put` calls.
+    puts: AtomicU64,
+}
+
+/// Snapshot of the [`FilesystemContractRuntimeCache`] access counters since startup.
+#[cfg(not(windows))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilesystemContractRuntimeCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub malformed: u64,
+    pub puts: u64,
+}
+
+/// Reason a [`FilesystemContractRuntimeCache`] entry failed [verification].
+///
+/// [verification]: FilesystemContractRuntimeCache::verify_entry
+#[cfg(not(windows))]
+#[derive(Debug, thiserror::Error)]
+pub enum CacheEntryError {
+    #[error("cache entry does not exist")]
+    Missing,
+    #[error("cache entry is truncated to {len} bytes")]
+    Truncated { len: usize },
+    #[error("cache entry has an unknown tag {tag:#04x}")]
+    UnknownTag { tag: u8 },
+    #[error("cache entry holds a compilation error that could not be decoded")]
+    Decode(#[source] std::io::Error),
+    #[error("cache entry could not be read")]
+    Io(#[from] std::io::Error),
 }
 
 /// Default minimum age of a tracked entry's last atime refresh before [`touch`] will enqueue another one.
@@ -443,6 +518,10 @@ impl FilesystemContractRuntimeCache {
                 access_time_refresh_throttle: ACCESS_TIME_REFRESH_THROTTLE,
                 bg_spawner,
                 test_temp_dir: None,
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                malformed: AtomicU64::new(0),
+                puts: AtomicU64::new(0),
             }),
         })
     }
@@ -481,6 +560,188 @@ impl FilesystemContractRuntimeCache {
         Ok(cache)
     }
 
+    /// Returns the cache access counters accumulated since the cache was opened.
+    pub fn stats(&self) -> FilesystemContractRuntimeCacheStats {
+        FilesystemContractRuntimeCacheStats {
+            hits: self.state.hits.load(Ordering::Relaxed),
+            misses: self.state.misses.load(Ordering::Relaxed),
+            malformed: self.state.malformed.load(Ordering::Relaxed),
+            puts: self.state.puts.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Like [`ContractRuntimeCache::get`], but memory-maps the cached file and lends the compiled
+    /// code to `with` instead of reading it into a freshly allocated buffer.
+    ///
+    /// Returns the contract's `wasm_bytes` alongside the value produced by `with`, or the cached
+    /// compilation error. Error entries are small and go through the regular read path.
+    pub fn get_mmap<R>(
+        &self,
+        key: &CryptoHash,
+        with: impl FnOnce(&[u8]) -> R,
+    ) -> std::io::Result<Option<(u64, Result<R, CompilationError>)>> {
+        let filename = key.to_string();
+        let file = match openat(&self.state.dir, &filename, OFlags::RDONLY, Mode::empty()) {
+            Err(Errno::NOENT) => {
+                self.state.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+            Ok(file) => file,
+        };
+        let len: usize = fstat(&file)?.st_size.try_into().unwrap();
+        if len < 9 {
+            // The file turns out to be empty/truncated? Treat as if there's no cached file.
+            self.state.misses.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        }
+        let map = MappedFile::new(&file, len)?;
+        drop(file);
+        let data = map.as_slice();
+        let wasm_bytes = u64::from_le_bytes(data[len - 8..].try_into().unwrap());
+        let result = match data[len - 9] {
+            CODE_TAG => {
+                let result = with(&data[..len - 9]);
+                self.state.hits.fetch_add(1, Ordering::Relaxed);
+                (wasm_bytes, Ok(result))
+            }
+            ERROR_TAG => {
+                drop(map);
+                return Ok(self.get(key)?.map(|value| match value.compiled {
+                    CompiledContract::CompileModuleError(e) => (value.wasm_bytes, Err(e)),
+                    // The entry has been replaced since we looked at it.
+                    CompiledContract::Code(code) => (value.wasm_bytes, Ok(with(&code))),
+                }));
+            }
+            _ => {
+                self.state.malformed.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(
+                    target: "vm",
+                    message = "cached contract executable was found to be malformed",
+                    key = %key
+                );
+                return Ok(None);
+            }
+        };
+        self.touch(key);
+        Ok(Some(result))
+    }
+
+    /// Check that the cached file for `key` is well-formed without recompiling the contract.
+    ///
+    /// The footer must carry a known tag and, for compilation errors, the payload must decode.
+    /// Compiled code is opaque to the cache and is not checked further. Unlike
+    /// [`ContractRuntimeCache::get`], this does not affect the access statistics or the eviction
+    /// order.
+    pub fn verify_entry(&self, key: &CryptoHash) -> Result<(), CacheEntryError> {
+        let filename = key.to_string();
+        let file = match openat(&self.state.dir, &filename, OFlags::RDONLY, Mode::empty()) {
+            Err(Errno::NOENT) => return Err(CacheEntryError::Missing),
+            Err(e) => return Err(std::io::Error::from(e).into()),
+            Ok(file) => file,
+        };
+        let mut buffer = Vec::new();
+        std::fs::File::from(file).read_to_end(&mut buffer)?;
+        if buffer.len() < 9 {
+            return Err(CacheEntryError::Truncated { len: buffer.len() });
+        }
+        let payload = &buffer[..buffer.len() - 9];
+        match buffer[buffer.len() - 9] {
+            CODE_TAG => Ok(()),
+            ERROR_TAG => {
+                borsh::from_slice::<CompilationError>(payload).map_err(CacheEntryError::Decode)?;
+                Ok(())
+            }
+            tag => Err(CacheEntryError::UnknownTag { tag }),
+        }
+    }
+
+    /// Read and decode the cached file for `key`, updating the access counters but not the
+    /// eviction order.
+    fn read_entry(&self, key: &CryptoHash) -> std::io::Result<Option<CompiledContractInfo>> {
+        let filename = key.to_string();
+        let mode = Mode::empty();
+        let flags = OFlags::RDONLY;
+        let file = openat(&self.state.dir, &filename, flags, mode);
+        let file = match file {
+            Err(Errno::NOENT) => {
+                self.state.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+            Ok(file) => file,
+        };
+        let stat = fstat(&file)?;
+        // See `FilesystemContractRuntimeCache::get_mmap` for a variant that avoids copying the
+        // file contents into a buffer.
+        let mut buffer = Vec::with_capacity(stat.st_size.try_into().unwrap());
+        let mut file = std::fs::File::from(file);
+        file.read_to_end(&mut buffer)?;
+        if buffer.len() < 9 {
+            // The file turns out to be empty/truncated? Treat as if there's no cached file.
+            self.state.misses.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        }
+        let wasm_bytes = u64::from_le_bytes(buffer[buffer.len() - 8..].try_into().unwrap());
+        let tag = buffer[buffer.len() - 9];
+        buffer.truncate(buffer.len() - 9);
+        let value = match tag {
+            CODE_TAG => {
+                CompiledContractInfo { wasm_bytes, compiled: CompiledContract::Code(buffer) }
+            }
+            ERROR_TAG => CompiledContractInfo {
+                wasm_bytes,
+                compiled: CompiledContract::CompileModuleError(borsh::from_slice(&buffer)?),
+            },
+            // File is malformed? For this code, since we're talking about a cache lets just treat
+            // it as if there is no cached file as well. The cached file may eventually be
+            // overwritten with a valid copy. And since we can compile a new copy, there doesn't
+            // seem to be much reason to possibly crash the node due to this.
+            _ => {
+                self.state.malformed.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(
+                    target: "vm",
+                    message = "cached contract executable was found to be malformed",
+                    key = %key
+                );
+                return Ok(None);
+            }
+        };
+        self.state.hits.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(value))
+    }
+
+    /// Promote `keys` in the on-disk LRU index under a single lock, and schedule an atime
+    /// refresh for the ones whose last refresh is older than the throttle window.
+    fn touch_many<'a>(&self, keys: impl IntoIterator<Item = &'a CryptoHash>) {
+        let needs_refresh: Vec<CryptoHash> = {
+            let mut index = self.state.disk_index.lock();
+            let now = Instant::now();
+            keys.into_iter()
+                .filter(|key| match index.get_mut(key) {
+                    Some((_weight, last_refresh)) => {
+                        if now >= *last_refresh + self.state.access_time_refresh_throttle {
+                            *last_refresh = now;
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    None => false,
+                })
+                .copied()
+                .collect()
+        };
+        if !needs_refresh.is_empty() {
+            let cache = self.clone();
+            (self.state.bg_spawner)(Box::new(move || {
+                for key in &needs_refresh {
+                    cache.refresh_disk_atime(key);
+                }
+            }));
+        }
+    }
+
     /// Stamp `key`'s on-disk file with the current access time, leaving its
     /// modification time untouched. Best-effort: failures are logged and dropped.
     fn refresh_disk_atime(&self, key: &CryptoHash) {
@@ -502,6 +763,47 @@ impl FilesystemContractRuntimeCache {
     }
 }
 
+/// A read-only, private memory mapping of a whole compiled-contract cache file.
+#[cfg(not(windows))]
+struct MappedFile {
+    ptr: *mut std::ffi::c_void,
+    len: usize,
+}
+
+#[cfg(not(windows))]
+impl MappedFile {
+    fn new(file: &OwnedFd, len: usize) -> std::io::Result<Self> {
+        // SAFETY: we map a fresh region chosen by the kernel, so no existing memory is affected.
+        // Cache files are never modified in place: `put` writes a temporary file and renames it
+        // over the final name, and eviction only unlinks, so the mapped contents stay stable for
+        // the lifetime of the mapping.
+        let ptr = unsafe {
+            mmap(std::ptr::null_mut(), len, ProtFlags::READ, MapFlags::PRIVATE, file, 0)?
+        };
+        Ok(Self { ptr, len })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: `ptr` points to a readable mapping of `len` bytes that lives as long as `self`.
+        unsafe { std::slice::from_raw_parts(self.ptr.cast::<u8>(), self.len) }
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` and `len` describe a mapping created in `MappedFile::new` and no slices
+        // borrowed from it outlive `self`.
+        if let Err(err) = unsafe { munmap(self.ptr, self.len) } {
+            tracing::debug!(
+                target: "vm",
+                err = &err as &dyn std::error::Error,
+                "failed to unmap compiled contract cache file"
+            );
+        }
+    }
+}
+
 /// Byte added after a serialized payload representing a compilation failure.
 ///
 /// This is ASCII LF.
@@ -703,6 +1005,7 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
         // issue(s) before too many temporary files gather up in the cache.
         //
         // (Operators are also somewhat encouraged to occasionally clear up their cache.)
+        self.state.puts.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
@@ -714,53 +1017,19 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
         fields(key = key.to_string()),
     )]
     fn get(&self, key: &CryptoHash) -> std::io::Result<Option<CompiledContractInfo>> {
-        let filename = key.to_string();
-        let mode = Mode::empty();
-        let flags = OFlags::RDONLY;
-        let file = openat(&self.state.dir, &filename, flags, mode);
-        let file = match file {
-            Err(Errno::NOENT) => return Ok(None),
-            Err(e) => return Err(e.into()),
-            Ok(file) => file,
-        };
-        let stat = fstat(&file)?;
-        // TODO: explore mmap-ing the file and lending the map to the caller via a closure callback.
-        // This would require some additional refactor work, but would likely help us to reduce the
-        // system call overhead in this area.
-        let mut buffer = Vec::with_capacity(stat.st_size.try_into().unwrap());
-        let mut file = std::fs::File::from(file);
-        file.read_to_end(&mut buffer)?;
-        if buffer.len() < 9 {
-            // The file turns out to be empty/truncated? Treat as if there's no cached file.
-            return Ok(None);
+        let value = self.read_entry(key)?;
+        if value.is_some() {
+            // Real cache hit: refresh recency so eviction doesn't drop it next.
+            self.touch(key);
         }
-        let wasm_bytes = u64::from_le_bytes(buffer[buffer.len() - 8..].try_into().unwrap());
-        let tag = buffer[buffer.len() - 9];
-        buffer.truncate(buffer.len() - 9);
-        let value = match tag {
-            CODE_TAG => {
-                CompiledContractInfo { wasm_bytes, compiled: CompiledContract::Code(buffer) }
-            }
-            ERROR_TAG => CompiledContractInfo {
-                wasm_bytes,
-                compiled: CompiledContract::CompileModuleError(borsh::from_slice(&buffer)?),
-            },
-            // File is malformed? For this code, since we're talking about a cache lets just treat
-            // it as if there is no cached file as well. The cached file may eventually be
-            // overwritten with a valid copy. And since we can compile a new copy, there doesn't
-            // seem to be much reason to possibly crash the node due to this.
-            _ => {
-                tracing::debug!(
-                    target: "vm",
-                    message = "cached contract executable was found to be malformed",
-                    key = %key
-                );
-                return Ok(None);
-            }
-        };
-        // Real cache hit: refresh recency so eviction doesn't drop it next.
-        self.touch(key);
-        Ok(Some(value))
+        Ok(value)
+    }
+
+    fn get_many(&self, keys: &[CryptoHash]) -> std::io::Result<Vec<Option<CompiledContractInfo>>> {
+        let values = keys.iter().map(|key| self.read_entry(key)).collect::<Result<Vec<_>, _>>()?;
+        let hits = keys.iter().zip(&values).filter(|(_, value)| value.is_some());
+        self.touch_many(hits.map(|(key, _)| key));
+        Ok(values)
     }
 
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
@@ -773,27 +1042,7 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
     }
 
     fn touch(&self, key: &CryptoHash) {
-        // Promote in the in-memory LRU and check if on-disk atime refresh is needed.
-        let needs_refresh = {
-            let mut index = self.state.disk_index.lock();
-            match index.get_mut(key) {
-                Some((_weight, last_refresh)) => {
-                    let now = Instant::now();
-                    if now >= *last_refresh + self.state.access_time_refresh_throttle {
-                        *last_refresh = now;
-                        true
-                    } else {
-                        false
-                    }
-                }
-                None => false,
-            }
-        };
-        if needs_refresh {
-            let cache = self.clone();
-            let key = *key;
-            (self.state.bg_spawner)(Box::new(move || cache.refresh_disk_atime(&key)));
-        }
+        self.touch_many([key]);
     }
 
     /// Clears the in-memory cache, the on-disk LRU index, and the files in the
@@ -1086,6 +1335,36 @@ pub fn precompile_contract(
     runtime.precompile(code, cache)
 }
 
+/// Batch version of [`precompile_contract`] for deploy-heavy workloads such as
+/// state sync or genesis. The runtime is instantiated once and shared by all
+/// compilations. Returns one result per entry of `codes`, each identical to what
+/// [`precompile_contract`] would have returned for it.
+pub fn precompile_contracts(
+    codes: &[&ContractCode],
+    config: Arc<Config>,
+    cache: Option<&dyn ContractRuntimeCache>,
+) -> Vec<Result<Result<ContractPrecompilatonResult, CompilationError>, CacheError>> {
+    let _span =
+        tracing::debug_span!(target: "vm", "precompile_contracts", count = codes.len()).entered();
+    if codes.is_empty() {
+        return Vec::new();
+    }
+    let vm_kind = config.vm_kind;
+    let runtime = vm_kind
+        .runtime(Arc::clone(&config))
+        .unwrap_or_else(|| panic!("the {vm_kind:?} runtime has not been enabled at compile time"));
+    let cache = match cache {
+        Some(it) => it,
+        None => {
+            return codes
+                .iter()
+                .map(|_| Ok(Ok(ContractPrecompilatonResult::CacheNotAvailable)))
+                .collect();
+        }
+    };
+    codes.iter().map(|code| runtime.precompile(code, cache)).collect()
+}
+
 /// Like [`precompile_contract`], but returns immediately if another thread is
 /// already compiling the same contract. Intended for opportunistic background
 /// warming on a low-priority pool: if a higher-priority worker (or any other
@@ -1113,6 +1392,7 @@ pub fn try_precompile_contract(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use assert_matches::assert_matches;
     #[test]
     fn any_cache_empty() {
         struct TestType;
@@ -1543,6 +1823,131 @@ mod tests {
         insert_and_assert_keys_exist();
     }
 
+    #[cfg(not(windows))]
+    #[test]
+    fn test_filesystem_cache_stats() {
+        let cache = FilesystemContractRuntimeCache::test().unwrap();
+        let key = CryptoHash::hash_bytes(b"contract");
+        let value =
+            CompiledContractInfo { wasm_bytes: 3, compiled: CompiledContract::Code(vec![1; 3]) };
+
+        assert_eq!(cache.get(&key).unwrap(), None);
+        cache.put(&key, value.clone()).unwrap();
+        assert_eq!(cache.get(&key).unwrap(), Some(value));
+
+        // An entry with an unknown tag is reported as malformed.
+        let malformed_key = CryptoHash::hash_bytes(b"malformed");
+        let dir = cache.state.test_temp_dir.as_ref().unwrap().path().join("contract.cache");
+        std::fs::write(dir.join(malformed_key.to_string()), [0u8; 16]).unwrap();
+        assert_eq!(cache.get(&malformed_key).unwrap(), None);
+
+        assert_eq!(
+            cache.stats(),
+            FilesystemContractRuntimeCacheStats { hits: 1, misses: 1, malformed: 1, puts: 1 }
+        );
+    }
+
+    #[test]
+    fn test_filesystem_cache_get_mmap() {
+        let cache = FilesystemContractRuntimeCache::test().unwrap();
+        let code_key = CryptoHash::hash_bytes(b"code");
+        let code = vec![7u8; 4096];
+        let value = CompiledContractInfo { wasm_bytes: 42, compiled: CompiledContract::Code(code) };
+        cache.put(&code_key, value).unwrap();
+        let (wasm_bytes, result) =
+            cache.get_mmap(&code_key, |bytes| bytes.to_vec()).unwrap().unwrap();
+        assert_eq!(wasm_bytes, 42);
+        assert_eq!(result.unwrap(), vec![7u8; 4096]);
+
+        let error_key = CryptoHash::hash_bytes(b"error");
+        let error = CompilationError::WasmerCompileError { msg: "bad".to_string() };
+        let value = CompiledContractInfo {
+            wasm_bytes: 3,
+            compiled: CompiledContract::CompileModuleError(error.clone()),
+        };
+        cache.put(&error_key, value).unwrap();
+        let (wasm_bytes, result) = cache.get_mmap(&error_key, |_| unreachable!()).unwrap().unwrap();
+        assert_eq!(wasm_bytes, 3);
+        assert_eq!(result.unwrap_err(), error);
+
+        let missing_key = CryptoHash::hash_bytes(b"missing");
+        assert!(cache.get_mmap(&missing_key, |_| ()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_many() {
+        let present = CryptoHash::hash_bytes(b"present");
+        let absent = CryptoHash::hash_bytes(b"absent");
+        let value =
+            CompiledContractInfo { wasm_bytes: 3, compiled: CompiledContract::Code(vec![1; 3]) };
+        let keys = [present, absent, present];
+        let expected = vec![Some(value.clone()), None, Some(value.clone())];
+
+        let fs_cache = FilesystemContractRuntimeCache::test().unwrap();
+        let mock_cache = MockContractRuntimeCache::default();
+        for cache in [&fs_cache as &dyn ContractRuntimeCache, &mock_cache] {
+            cache.put(&present, value.clone()).unwrap();
+            assert_eq!(cache.get_many(&keys).unwrap(), expected);
+            assert!(cache.get_many(&[]).unwrap().is_empty());
+        }
+        assert_eq!(fs_cache.stats().hits, 2);
+        assert_eq!(fs_cache.stats().misses, 1);
+
+        assert_eq!(NoContractRuntimeCache.get_many(&keys).unwrap(), vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_filesystem_cache_verify_entry() {
+        let cache = FilesystemContractRuntimeCache::test().unwrap();
+        let dir = cache.state.test_temp_dir.as_ref().unwrap().path().join("contract.cache");
+
+        let code_key = CryptoHash::hash_bytes(b"code");
+        let value =
+            CompiledContractInfo { wasm_bytes: 3, compiled: CompiledContract::Code(vec![1; 3]) };
+        cache.put(&code_key, value).unwrap();
+        cache.verify_entry(&code_key).unwrap();
+
+        let error_key = CryptoHash::hash_bytes(b"error");
+        let value = CompiledContractInfo {
+            wasm_bytes: 3,
+            compiled: CompiledContract::CompileModuleError(CompilationError::WasmerCompileError {
+                msg: "bad".to_string(),
+            }),
+        };
+        cache.put(&error_key, value).unwrap();
+        cache.verify_entry(&error_key).unwrap();
+
+        let missing_key = CryptoHash::hash_bytes(b"missing");
+        assert_matches!(cache.verify_entry(&missing_key), Err(CacheEntryError::Missing));
+
+        let truncated_key = CryptoHash::hash_bytes(b"truncated");
+        std::fs::write(dir.join(truncated_key.to_string()), [CODE_TAG; 4]).unwrap();
+        assert_matches!(
+            cache.verify_entry(&truncated_key),
+            Err(CacheEntryError::Truncated { len: 4 })
+        );
+
+        let unknown_tag_key = CryptoHash::hash_bytes(b"unknown tag");
+        std::fs::write(dir.join(unknown_tag_key.to_string()), [0u8; 16]).unwrap();
+        assert_matches!(
+            cache.verify_entry(&unknown_tag_key),
+            Err(CacheEntryError::UnknownTag { tag: 0 })
+        );
+
+        let undecodable_key = CryptoHash::hash_bytes(b"undecodable");
+        let mut contents = vec![0xff; 4];
+        contents.push(ERROR_TAG);
+        contents.extend_from_slice(&3u64.to_le_bytes());
+        std::fs::write(dir.join(undecodable_key.to_string()), contents).unwrap();
+        assert_matches!(cache.verify_entry(&undecodable_key), Err(CacheEntryError::Decode(_)));
+
+        // Verification is not a cache access.
+        assert_eq!(
+            cache.stats(),
+            FilesystemContractRuntimeCacheStats { puts: 2, ..Default::default() }
+        );
+    }
+
     // ----- on-disk eviction feature tests -----
     #[cfg(not(windows))]
     mod eviction {
@@ -13,6 +13,8 @@ mod metrics;
 pub mod prepare;
 mod profile;
 mod runner;
+#[cfg(any(test, feature = "test_features"))]
+pub mod test_utils;
 #[cfg(test)]
 mod tests;
 mod utils;
@@ -21,13 +23,16 @@ mod wasmtime_runner;
 
 pub use crate::logic::with_ext_cost_counter;
 #[cfg(not(windows))]
-pub use cache::FilesystemContractRuntimeCache;
-#[cfg(feature = "wasmtime_vm")]
-pub use cache::config_cache_key_signature;
+pub use cache::{
+    CacheEntryError, FilesystemContractRuntimeCache, FilesystemContractRuntimeCacheStats,
+};
 pub use cache::{
     CompiledContract, CompiledContractInfo, ContractRuntimeCache, MockContractRuntimeCache,
-    NoContractRuntimeCache, noop_background_spawner, precompile_contract, try_precompile_contract,
+    NoContractRuntimeCache, noop_background_spawner, precompile_contract, precompile_contracts,
+    try_precompile_contract,
 };
+#[cfg(feature = "wasmtime_vm")]
+pub use cache::{config_cache_key_signature, contract_cache_key_parts};
 pub use errors::ContractPrecompilatonResult;
 #[cfg(feature = "metrics")]
 pub use metrics::{report_metrics, reset_metrics};
@@ -51,5 +56,5 @@ pub(crate) const EXPORT_PREFIX: &str = "\0";
 /// implementation detail of `near-vm-runner`.
 #[doc(hidden)]
 pub mod internal {
-    pub use crate::runner::VMKindExt;
+    pub use crate::runner::{VM_KIND_FALLBACKS, VMKindExt};
 }
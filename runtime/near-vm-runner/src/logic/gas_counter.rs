@@ -79,6 +79,13 @@ pub struct GasCounter {
     profile: ProfileDataV3,
     /// Compute costs for the send step of outgoing receipts.
     pub(crate) send_action_compute_usage: Compute,
+    /// By how much the charge that caused `HostError::GasExceeded` would have
+    /// exceeded the prepaid gas, if known.
+    gas_overshoot: Option<Gas>,
+    /// Counts of the host function costs charged, like `with_ext_cost_counter`
+    /// but for this execution only. Only gathered in tests or with the
+    /// `costs_counting` feature, to avoid the overhead otherwise.
+    ext_costs_counts: HashMap<ExtCosts, u64>,
 }
 
 impl GasCounter {
@@ -105,6 +112,8 @@ impl GasCounter {
             is_view,
             profile: Default::default(),
             send_action_compute_usage: 0,
+            gas_overshoot: None,
+            ext_costs_counts: HashMap::new(),
         }
     }
 
@@ -202,10 +211,24 @@ impl GasCounter {
         if new_burnt_gas > self.max_gas_burnt {
             HostError::GasLimitExceeded
         } else {
+            // Unless `new_used_gas` wrapped (see above), it tells how much gas the
+            // contract attempted to use. This is only a lower bound of what the
+            // contract would have needed to finish, but it's better than nothing.
+            if new_used_gas >= new_burnt_gas {
+                self.gas_overshoot =
+                    new_used_gas.checked_sub(self.prepaid_gas).filter(|gas| *gas > Gas::ZERO);
+            }
             HostError::GasExceeded
         }
     }
 
+    /// By how much the charge that caused `HostError::GasExceeded` would have
+    /// exceeded the prepaid gas. None if the gas was not exceeded or the
+    /// overshoot is unknown, e.g. when running out of gas in wasm instructions.
+    pub fn gas_overshoot(&self) -> Option<Gas> {
+        self.gas_overshoot
+    }
+
     /// Add a cost for loading the contract code in the VM.
     ///
     /// This cost does not consider the structure of the contract code, only the
@@ -265,8 +288,18 @@ impl GasCounter {
     }
 
     #[inline]
-    fn inc_ext_costs_counter(&self, cost: ExtCosts, value: u64) {
-        with_ext_cost_counter(|cc| *cc.entry(cost).or_default() += value)
+    fn inc_ext_costs_counter(&mut self, cost: ExtCosts, value: u64) {
+        with_ext_cost_counter(|cc| *cc.entry(cost).or_default() += value);
+        #[cfg(any(test, feature = "costs_counting"))]
+        {
+            *self.ext_costs_counts.entry(cost).or_default() += value;
+        }
+    }
+
+    /// Counts of the host function costs charged so far. Empty unless built
+    /// for tests or with the `costs_counting` feature.
+    pub fn ext_costs_counts(&self) -> &HashMap<ExtCosts, u64> {
+        &self.ext_costs_counts
     }
 
     #[inline]
@@ -457,6 +490,28 @@ mod tests {
         test(Gas::from_gas(7), Gas::from_gas(5), true, Err(HostError::GasLimitExceeded));
     }
 
+    #[test]
+    fn test_gas_overshoot() {
+        let mut counter = make_test_counter(Gas::from_gas(100), Gas::from_gas(7), false);
+        assert_eq!(counter.burn_gas(Gas::from_gas(5)), Ok(()));
+        assert_eq!(counter.gas_overshoot(), None);
+        assert_eq!(counter.burn_gas(Gas::from_gas(3)), Err(HostError::GasExceeded.into()));
+        assert_eq!(counter.gas_overshoot(), Some(Gas::from_gas(1)));
+
+        // Exceeding the burnt gas limit does not record an overshoot.
+        let mut counter = make_test_counter(Gas::from_gas(7), Gas::from_gas(100), false);
+        assert_eq!(counter.burn_gas(Gas::from_gas(8)), Err(HostError::GasLimitExceeded.into()));
+        assert_eq!(counter.gas_overshoot(), None);
+
+        // When the limit is hit in wasm instructions, the gas is already clamped.
+        let mut counter = make_test_counter(Gas::from_gas(100), Gas::from_gas(7), false);
+        assert_eq!(
+            counter.process_gas_limit(Gas::from_gas(7), Gas::from_gas(7)),
+            HostError::GasExceeded
+        );
+        assert_eq!(counter.gas_overshoot(), None);
+    }
+
     #[test]
     fn test_deduct_too_much() {
         fn test(burn: Gas, prepaid: Gas, view: bool, want: Result<(), HostError>) {
@@ -27,6 +27,7 @@ use near_primitives_core::hash::{CryptoHash, YieldId};
 use near_primitives_core::types::{
     AccountId, Balance, Compute, EpochHeight, Gas, GasWeight, StorageUsage,
 };
+use std::collections::HashMap;
 use std::mem::size_of;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -151,6 +152,9 @@ impl ExecutionResultState {
             profile,
             aborted: None,
             subsidized_amount: self.subsidized_amount,
+            gas_distribution: Vec::new(),
+            gas_overshoot_estimate: self.gas_counter.gas_overshoot(),
+            ext_costs_counts: self.gas_counter.ext_costs_counts().clone(),
         }
     }
 }
@@ -4581,6 +4585,18 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
     }
 }
 
+/// Amount of the unused gas assigned to a single function call created by the contract,
+/// according to the gas weight of the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasDistribution {
+    /// Index of the created receipt containing the function call.
+    pub receipt_index: u64,
+    /// Gas explicitly attached to the function call by the contract.
+    pub prepaid_gas: Gas,
+    /// Part of the unused gas added on top of `prepaid_gas`.
+    pub distributed_gas: Gas,
+}
+
 #[derive(PartialEq)]
 pub struct VMOutcome {
     pub balance: Balance,
@@ -4596,6 +4612,18 @@ pub struct VMOutcome {
     /// Amount of balance subsidized (minted) by skipping deduction for
     /// 1 yoctoNEAR attached deposits on zero-balance contracts.
     pub subsidized_amount: Balance,
+    /// How the unused gas was distributed among the function calls created by the contract.
+    /// Only recorded by the runtime when debug logging is enabled, empty otherwise.
+    pub gas_distribution: Vec<GasDistribution>,
+    /// When the execution aborted with `HostError::GasExceeded`, an estimate of
+    /// how much gas beyond the prepaid gas the contract attempted to use. The
+    /// gas in the outcome is clamped to the prepaid gas. None if the VM could
+    /// not estimate it.
+    pub gas_overshoot_estimate: Option<Gas>,
+    /// How many times each host function cost was charged, e.g. `sha256_base`
+    /// once per `sha256` call and `sha256_byte` once per hashed byte. Only
+    /// gathered in tests or with the `costs_counting` feature, empty otherwise.
+    pub ext_costs_counts: HashMap<ExtCosts, u64>,
 }
 
 impl VMOutcome {
@@ -4629,6 +4657,9 @@ impl VMOutcome {
             profile: ProfileDataV3::default(),
             aborted: Some(error),
             subsidized_amount: Balance::ZERO,
+            gas_distribution: Vec::new(),
+            gas_overshoot_estimate: None,
+            ext_costs_counts: HashMap::new(),
         }
     }
 
@@ -18,7 +18,7 @@ pub use context::VMContext;
 pub use dependencies::{External, MemSlice, MemoryLike, StorageAccessTracker, ValuePtr};
 pub use errors::{HostError, VMLogicError};
 pub use gas_counter::{GasCounter, with_ext_cost_counter};
-pub use logic::{ExecutionResultState, VMLogic, VMOutcome};
+pub use logic::{ExecutionResultState, GasDistribution, VMLogic, VMOutcome};
 pub use near_parameters::vm::{Config, ContractPrepareVersion, LimitConfig};
 pub use near_primitives_core::types::ProtocolVersion;
 pub use types::ReturnData;
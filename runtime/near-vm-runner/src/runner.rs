@@ -5,7 +5,7 @@ use crate::{ContractCode, ContractRuntimeCache};
 use near_parameters::RuntimeFeesConfig;
 use near_parameters::vm::{Config, VMKind};
 use near_primitives_core::hash::CryptoHash;
-use std::sync::Arc;
+use std::sync::{Arc, Once};
 
 /// Returned by VM::run method.
 ///
@@ -192,9 +192,17 @@ pub trait VM {
     ) -> Result<Result<ContractPrecompilatonResult, CompilationError>, CacheError>;
 }
 
+/// VM kinds to fall back to, in order, when the configured VM kind has not been
+/// enabled at compile time, e.g. on platforms where it is unsupported.
+pub const VM_KIND_FALLBACKS: &[VMKind] = &[VMKind::Wasmtime];
+
 pub trait VMKindExt {
     fn is_available(&self) -> bool;
-    /// Make a [`VM`] for this [`VMKind`].
+    /// Returns this VM kind if it has been enabled at compile time, otherwise the
+    /// first of `fallbacks` which has been. Returns `None` if none has been.
+    fn resolve(&self, fallbacks: &[VMKind]) -> Option<VMKind>;
+    /// Make a [`VM`] for this [`VMKind`], or for the first of [`VM_KIND_FALLBACKS`]
+    /// enabled at compile time if this one is not.
     ///
     /// This is not intended to be used by code other than internal tools like
     /// the estimator.
@@ -211,8 +219,30 @@ impl VMKindExt for VMKind {
             Self::NearVm => false,
         }
     }
+    fn resolve(&self, fallbacks: &[VMKind]) -> Option<VMKind> {
+        std::iter::once(self).chain(fallbacks).find(|kind| kind.is_available()).copied()
+    }
     fn runtime(&self, config: std::sync::Arc<Config>) -> Option<Box<dyn VM>> {
-        match self {
+        let vm_kind = self.resolve(VM_KIND_FALLBACKS)?;
+        if vm_kind != *self {
+            static FALLBACK_WARNING: Once = Once::new();
+            FALLBACK_WARNING.call_once(|| {
+                tracing::warn!(
+                    target: "vm",
+                    configured = ?self,
+                    ?vm_kind,
+                    "the configured vm kind has not been enabled at compile time, falling back"
+                );
+            });
+        }
+        // The VM kind is replaced in the config as well, so that it is reflected
+        // in the contract cache keys.
+        let config = if config.vm_kind == vm_kind {
+            config
+        } else {
+            Arc::new(Config { vm_kind, ..Config::clone(&config) })
+        };
+        match vm_kind {
             #[cfg(feature = "wasmtime_vm")]
             Self::Wasmtime => Some(Box::new(crate::wasmtime_runner::WasmtimeVM::new(config))),
             #[allow(unreachable_patterns)] // reachable when some of the VMs are disabled.
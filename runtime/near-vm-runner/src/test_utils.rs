@@ -0,0 +1,73 @@
+//! Helpers for running contracts in tests without setting up the runtime by hand.
+
+use crate::ContractCode;
+use crate::logic::errors::VMRunnerError;
+use crate::logic::mocks::mock_external::MockedExternal;
+use crate::logic::{Config, VMContext, VMOutcome};
+use crate::runner::VMKindExt;
+use near_parameters::{ExtCosts, RuntimeFeesConfig};
+use near_primitives_core::account::AccountContract;
+use near_primitives_core::types::{Balance, Gas};
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub const CURRENT_ACCOUNT_ID: &str = "alice";
+pub const SIGNER_ACCOUNT_ID: &str = "bob";
+pub const SIGNER_ACCOUNT_PK: [u8; 3] = [0, 1, 2];
+pub const PREDECESSOR_ACCOUNT_ID: &str = "carol";
+pub const REFUND_TO_ACCOUNT_ID: &str = "david";
+
+/// Creates a context for calling a contract of `CURRENT_ACCOUNT_ID` with the given input.
+pub fn create_context(input: Vec<u8>) -> VMContext {
+    VMContext {
+        current_account_id: CURRENT_ACCOUNT_ID.parse().unwrap(),
+        signer_account_id: SIGNER_ACCOUNT_ID.parse().unwrap(),
+        signer_account_pk: Vec::from(&SIGNER_ACCOUNT_PK[..]),
+        predecessor_account_id: PREDECESSOR_ACCOUNT_ID.parse().unwrap(),
+        refund_to_account_id: REFUND_TO_ACCOUNT_ID.parse().unwrap(),
+        input: Rc::from(input),
+        promise_results: Vec::new().into(),
+        block_height: 10,
+        block_timestamp: 42,
+        epoch_height: 1,
+        account_balance: Balance::from_yoctonear(2),
+        account_locked_balance: Balance::ZERO,
+        storage_usage: 12,
+        account_contract: AccountContract::None,
+        attached_deposit: Balance::from_yoctonear(2),
+        prepaid_gas: Gas::from_teragas(100),
+        random_seed: vec![0, 1, 2],
+        view_config: None,
+        output_data_receivers: vec![],
+    }
+}
+
+/// Runs `method` of `code` with the given input, using the VM kind of `config`, a fresh
+/// `MockedExternal`, the context from `create_context` and test fees.
+pub fn run_method(
+    code: &ContractCode,
+    method: &str,
+    input: Vec<u8>,
+    config: Arc<Config>,
+) -> Result<VMOutcome, VMRunnerError> {
+    let mut external = MockedExternal::with_code(code.clone());
+    let context = create_context(input);
+    let gas_counter = context.make_gas_counter(&config);
+    let runtime = config.vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+    runtime.prepare(&external, None, gas_counter, method).run(
+        &mut external,
+        &context,
+        Arc::new(RuntimeFeesConfig::test()),
+    )
+}
+
+/// Asserts that the execution charged each of the `expected` host function costs exactly the
+/// given number of times, see `VMOutcome::ext_costs_counts`. A count of 0 asserts that the cost
+/// was not charged at all. Costs not mentioned in `expected` are not checked.
+#[track_caller]
+pub fn assert_ext_costs_counts(outcome: &VMOutcome, expected: &[(ExtCosts, u64)]) {
+    for &(cost, count) in expected {
+        let actual = outcome.ext_costs_counts.get(&cost).copied().unwrap_or(0);
+        assert_eq!(actual, count, "unexpected count of {cost:?}");
+    }
+}
@@ -11,17 +11,18 @@ pub(crate) mod test_builder;
 mod ts_contract;
 mod wasm_validation;
 
-use crate::logic::VMContext;
+use crate::ContractCode;
+use crate::logic::Config;
+use crate::runner::{VMKindExt, VMResult};
+use crate::test_utils::run_method;
+pub(crate) use crate::test_utils::{
+    CURRENT_ACCOUNT_ID, PREDECESSOR_ACCOUNT_ID, SIGNER_ACCOUNT_ID, SIGNER_ACCOUNT_PK,
+    create_context,
+};
 use near_parameters::RuntimeConfigStore;
 use near_parameters::vm::VMKind;
-use near_primitives_core::types::{Balance, Gas};
 use near_primitives_core::version::PROTOCOL_VERSION;
-
-const CURRENT_ACCOUNT_ID: &str = "alice";
-const SIGNER_ACCOUNT_ID: &str = "bob";
-const SIGNER_ACCOUNT_PK: [u8; 3] = [0, 1, 2];
-const PREDECESSOR_ACCOUNT_ID: &str = "carol";
-const REFUND_TO_ACCOUNT_ID: &str = "david";
+use std::sync::Arc;
 
 pub(crate) fn test_vm_config(vm_kind: Option<VMKind>) -> near_parameters::vm::Config {
     let store = RuntimeConfigStore::test();
@@ -43,26 +44,48 @@ pub(crate) fn with_vm_variants(runner: impl Fn(VMKind) -> ()) {
     run(VMKind::Wasmtime);
 }
 
-fn create_context(input: Vec<u8>) -> VMContext {
-    VMContext {
-        current_account_id: CURRENT_ACCOUNT_ID.parse().unwrap(),
-        signer_account_id: SIGNER_ACCOUNT_ID.parse().unwrap(),
-        signer_account_pk: Vec::from(&SIGNER_ACCOUNT_PK[..]),
-        predecessor_account_id: PREDECESSOR_ACCOUNT_ID.parse().unwrap(),
-        refund_to_account_id: REFUND_TO_ACCOUNT_ID.parse().unwrap(),
-        input: std::rc::Rc::from(input),
-        promise_results: Vec::new().into(),
-        block_height: 10,
-        block_timestamp: 42,
-        epoch_height: 1,
-        account_balance: Balance::from_yoctonear(2),
-        account_locked_balance: Balance::ZERO,
-        storage_usage: 12,
-        account_contract: near_primitives_core::account::AccountContract::None,
-        attached_deposit: Balance::from_yoctonear(2),
-        prepaid_gas: Gas::from_teragas(100),
-        random_seed: vec![0, 1, 2],
-        view_config: None,
-        output_data_receivers: vec![],
-    }
+/// Runs `methods` of `code` concurrently, each on its own thread and with its own
+/// `MockedExternal`. Returns the results in the order of `methods`.
+fn run_methods_parallel(
+    code: &ContractCode,
+    methods: &[&str],
+    config: Arc<Config>,
+) -> Vec<VMResult> {
+    std::thread::scope(|s| {
+        let handles: Vec<_> = methods
+            .iter()
+            .map(|method| {
+                let config = Arc::clone(&config);
+                s.spawn(move || run_method(code, method, vec![], config))
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+#[test]
+#[cfg(feature = "wasmtime_vm")]
+fn test_runtime_falls_back_to_available_vm_kind() {
+    use crate::runner::VM_KIND_FALLBACKS;
+    use crate::{ContractPrecompilatonResult, MockContractRuntimeCache, precompile_contract};
+    use assert_matches::assert_matches;
+
+    assert_eq!(VMKind::Wasmtime.resolve(VM_KIND_FALLBACKS), Some(VMKind::Wasmtime));
+    assert_eq!(VMKind::NearVm.resolve(VM_KIND_FALLBACKS), Some(VMKind::Wasmtime));
+    assert_eq!(
+        VMKind::NearVm.resolve(&[VMKind::Wasmer2, VMKind::Wasmtime]),
+        Some(VMKind::Wasmtime)
+    );
+    assert_eq!(VMKind::NearVm.resolve(&[VMKind::Wasmer2]), None);
+
+    // The configured VM kind is not enabled, so the contract is compiled with
+    // Wasmtime and cached under the same key as with a Wasmtime config.
+    let config = Arc::new(test_vm_config(Some(VMKind::NearVm)));
+    let code = ContractCode::new(near_test_contracts::trivial_contract().to_vec(), None);
+    let cache = MockContractRuntimeCache::default();
+    let result = precompile_contract(&code, Arc::clone(&config), Some(&cache));
+    assert_matches!(result, Ok(Ok(ContractPrecompilatonResult::ContractCompiled)));
+    let config = Arc::new(Config { vm_kind: VMKind::Wasmtime, ..Config::clone(&config) });
+    let result = precompile_contract(&code, config, Some(&cache));
+    assert_matches!(result, Ok(Ok(ContractPrecompilatonResult::ContractAlreadyInCache)));
 }
@@ -339,3 +339,56 @@ fn test_no_duplicate_compilation() {
         "lock entry for this contract should be cleaned up"
     );
 }
+
+/// The batch precompilation must behave exactly like precompiling each contract in turn.
+#[cfg(feature = "wasmtime_vm")]
+#[test]
+fn test_precompile_contracts_batch() {
+    use crate::cache::{precompile_contract, precompile_contracts};
+    use crate::errors::ContractPrecompilatonResult;
+
+    let config = Arc::new(test_vm_config(Some(VMKind::Wasmtime)));
+    let cache = MockContractRuntimeCache::default();
+    let code1 = ContractCode::new(wat::parse_str(r#"(module (func (export "a")))"#).unwrap(), None);
+    let code2 = ContractCode::new(wat::parse_str(r#"(module (func (export "b")))"#).unwrap(), None);
+
+    // One of the contracts is already in the cache.
+    assert_matches!(
+        precompile_contract(&code1, Arc::clone(&config), Some(&cache)),
+        Ok(Ok(ContractPrecompilatonResult::ContractCompiled))
+    );
+    let results = precompile_contracts(&[&code1, &code2], Arc::clone(&config), Some(&cache));
+    assert_matches!(
+        results.as_slice(),
+        [
+            Ok(Ok(ContractPrecompilatonResult::ContractAlreadyInCache)),
+            Ok(Ok(ContractPrecompilatonResult::ContractCompiled)),
+        ]
+    );
+    assert_eq!(cache.put_count(), 2);
+
+    let results = precompile_contracts(&[&code1, &code2], Arc::clone(&config), None);
+    assert_matches!(
+        results.as_slice(),
+        [
+            Ok(Ok(ContractPrecompilatonResult::CacheNotAvailable)),
+            Ok(Ok(ContractPrecompilatonResult::CacheNotAvailable)),
+        ]
+    );
+    assert!(precompile_contracts(&[], config, Some(&cache)).is_empty());
+}
+
+#[cfg(feature = "wasmtime_vm")]
+#[test]
+fn test_contract_cache_key_parts() {
+    use crate::cache::{contract_cache_key_parts, get_contract_cache_key};
+
+    let config = test_vm_config(Some(VMKind::Wasmtime));
+    let code_hash = CryptoHash::hash_bytes(b"code");
+    let key = contract_cache_key_parts(code_hash, config.non_crypto_hash(), config.vm_kind, 42);
+    assert_eq!(key, get_contract_cache_key(code_hash, &config, 42));
+    assert_ne!(
+        key,
+        contract_cache_key_parts(code_hash, config.non_crypto_hash(), config.vm_kind, 43)
+    );
+}
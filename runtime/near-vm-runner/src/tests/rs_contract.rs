@@ -6,12 +6,13 @@ use crate::logic::mocks::mock_external::{MockAction, MockedExternal};
 use crate::logic::types::ReturnData;
 use crate::runner::VMKindExt;
 use crate::runner::VMResult;
+use crate::test_utils::{assert_ext_costs_counts, run_method};
 use crate::tests::{
     CURRENT_ACCOUNT_ID, PREDECESSOR_ACCOUNT_ID, SIGNER_ACCOUNT_ID, SIGNER_ACCOUNT_PK,
-    create_context, with_vm_variants,
+    create_context, run_methods_parallel, with_vm_variants,
 };
-use near_parameters::RuntimeFeesConfig;
 use near_parameters::vm::VMKind;
+use near_parameters::{ExtCosts, RuntimeFeesConfig};
 use near_primitives_core::types::Balance;
 use near_primitives_core::types::Gas;
 use std::mem::size_of;
@@ -75,6 +76,26 @@ pub fn test_read_write() {
     });
 }
 
+#[test]
+pub fn test_run_methods_parallel() {
+    with_vm_variants(|vm_kind: VMKind| {
+        let config = Arc::new(test_vm_config(Some(vm_kind)));
+        let code = test_contract(vm_kind);
+        let results = run_methods_parallel(&code, &["ext_account_id", "ext_signer_id"], config);
+        let return_data: Vec<_> = results
+            .into_iter()
+            .map(|result| result.expect("Failed execution").return_data)
+            .collect();
+        assert_eq!(
+            return_data,
+            vec![
+                ReturnData::Value(CURRENT_ACCOUNT_ID.as_bytes().to_vec()),
+                ReturnData::Value(SIGNER_ACCOUNT_ID.as_bytes().to_vec()),
+            ]
+        );
+    });
+}
+
 macro_rules! def_test_ext {
     ($name:ident, $method:expr, $expected:expr, $input:expr, $validator:expr) => {
         #[test]
@@ -172,6 +193,39 @@ def_test_ext!(
     ],
     b"tesdsst"
 );
+#[test]
+pub fn ext_sha256_costs_counts() {
+    with_vm_variants(|vm_kind: VMKind| {
+        let config = Arc::new(test_vm_config(Some(vm_kind)));
+        let code = test_contract(vm_kind);
+        let outcome =
+            run_method(&code, "ext_sha256", b"tesdsst".to_vec(), config).expect("Failed execution");
+        assert_ext_costs_counts(
+            &outcome,
+            &[
+                (ExtCosts::sha256_base, 1),
+                (ExtCosts::sha256_byte, 7),
+                (ExtCosts::keccak256_base, 0),
+            ],
+        );
+    })
+}
+
+#[test]
+pub fn test_unlimited_gas() {
+    with_vm_variants(|vm_kind: VMKind| {
+        let mut config = test_vm_config(Some(vm_kind));
+        config.limit_config.max_gas_burnt = Gas::from_gas(1);
+        config.make_unlimited_gas();
+        let code = test_contract(vm_kind);
+        let outcome = run_method(&code, "ext_sha256", b"tesdsst".to_vec(), Arc::new(config))
+            .expect("Failed execution");
+        assert_eq!(outcome.aborted, None);
+        // Unlike with `make_free`, the gas is still counted.
+        assert!(outcome.burnt_gas > Gas::ZERO);
+    })
+}
+
 // current_account_balance = context.account_balance + context.attached_deposit;
 def_test_ext!(ext_account_balance, "ext_account_balance", &(2u128 + 2).to_le_bytes());
 def_test_ext!(ext_attached_deposit, "ext_attached_deposit", &2u128.to_le_bytes());
@@ -45,6 +45,9 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
         use_state_stored_receipt: latest_runtime_config.use_state_stored_receipt,
         min_gas_purchase_price: latest_runtime_config.min_gas_purchase_price,
         account_creation_charge: latest_runtime_config.account_creation_charge,
+        max_validator_proposals_per_chunk: latest_runtime_config.max_validator_proposals_per_chunk,
+        gas_distribution_strategy: latest_runtime_config.gas_distribution_strategy,
+        function_call_allowlist: latest_runtime_config.function_call_allowlist.clone(),
     };
     Ok(res)
 }
@@ -15,9 +15,12 @@ use near_primitives::account::{
     AccessKey, AccessKeyPermission, Account, AccountContract, GasKeyInfo,
 };
 use near_primitives::action::delegate::{
-    VersionedDelegateActionRef, VersionedSignedDelegateActionRef,
+    VersionedDelegateActionRef, VersionedSignedDelegateActionRef, contains_nested_delegate,
+};
+use near_primitives::errors::{
+    ActionError, ActionErrorKind, ActionsValidationError, InvalidAccessKeyError,
+    ReceiptValidationError, RuntimeError,
 };
-use near_primitives::errors::{ActionError, ActionErrorKind, InvalidAccessKeyError, RuntimeError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{
     ActionReceipt, Receipt, ReceiptEnum, ReceiptV0, VersionedActionReceipt, VersionedReceiptEnum,
@@ -27,7 +30,7 @@ use near_primitives::transaction::{
 };
 use near_primitives::types::validator_stake::ValidatorStake;
 use near_primitives::types::{
-    AccountId, Balance, BlockHeight, EpochInfoProvider, NonceIndex, StorageUsage,
+    AccountId, Balance, BlockHeight, EpochInfoProvider, Gas, NonceIndex, StorageUsage,
 };
 use near_primitives::utils::account_is_implicit;
 use near_primitives::version::ProtocolVersion;
@@ -116,30 +119,46 @@ pub(crate) fn try_refund_gas_key_balance(
     Ok(true)
 }
 
+/// Result of refunding a deposit to a function call access key allowance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AllowanceRefundOutcome {
+    /// The full deposit was added to the allowance.
+    Applied,
+    /// The key has no allowance to refund to (full access key or unlimited allowance).
+    Unlimited,
+    /// Adding the deposit overflowed, so the allowance was saturated at the maximum balance.
+    Clamped,
+    /// The access key does not exist anymore.
+    KeyNotFound,
+}
+
 pub(crate) fn try_refund_allowance(
     state_update: &mut TrieUpdate,
     account_id: &AccountId,
     public_key: &PublicKey,
     deposit: Balance,
-) -> Result<(), StorageError> {
-    if let Some(mut access_key) = get_access_key(state_update, account_id, public_key)? {
-        let mut updated = false;
-        if let AccessKeyPermission::FunctionCall(function_call_permission) =
-            &mut access_key.permission
-        {
-            if let Some(allowance) = function_call_permission.allowance.as_mut() {
-                let new_allowance = allowance.saturating_add(deposit);
-                if new_allowance > *allowance {
-                    *allowance = new_allowance;
-                    updated = true;
-                }
-            }
-        }
-        if updated {
-            set_access_key(state_update, account_id.clone(), public_key.clone(), &access_key);
-        }
+) -> Result<AllowanceRefundOutcome, StorageError> {
+    let Some(mut access_key) = get_access_key(state_update, account_id, public_key)? else {
+        return Ok(AllowanceRefundOutcome::KeyNotFound);
+    };
+    let AccessKeyPermission::FunctionCall(function_call_permission) = &mut access_key.permission
+    else {
+        return Ok(AllowanceRefundOutcome::Unlimited);
+    };
+    let Some(allowance) = function_call_permission.allowance.as_mut() else {
+        return Ok(AllowanceRefundOutcome::Unlimited);
+    };
+    let outcome = if allowance.checked_add(deposit).is_some() {
+        AllowanceRefundOutcome::Applied
+    } else {
+        AllowanceRefundOutcome::Clamped
+    };
+    let new_allowance = allowance.saturating_add(deposit);
+    if new_allowance > *allowance {
+        *allowance = new_allowance;
+        set_access_key(state_update, account_id.clone(), public_key.clone(), &access_key);
     }
-    Ok(())
+    Ok(outcome)
 }
 
 pub(crate) fn action_transfer(account: &mut Account, deposit: Balance) -> Result<(), StorageError> {
@@ -149,6 +168,18 @@ pub(crate) fn action_transfer(account: &mut Account, deposit: Balance) -> Result
     Ok(())
 }
 
+/// Checks whether `predecessor_id` is allowed to create the top-level account `account_id`.
+/// Top-level account IDs shorter than `min_allowed_top_level_account_length` can only be
+/// created by the registrar account.
+pub fn can_create_top_level_account(
+    account_id: &AccountId,
+    predecessor_id: &AccountId,
+    account_creation_config: &AccountCreationConfig,
+) -> bool {
+    account_id.len() >= account_creation_config.min_allowed_top_level_account_length as usize
+        || predecessor_id == &account_creation_config.registrar_account_id
+}
+
 pub(crate) fn action_create_account(
     fee_config: &RuntimeFeesConfig,
     account_creation_config: &AccountCreationConfig,
@@ -159,9 +190,7 @@ pub(crate) fn action_create_account(
     result: &mut ActionResult,
 ) {
     if account_id.is_top_level() {
-        if account_id.len() < account_creation_config.min_allowed_top_level_account_length as usize
-            && predecessor_id != &account_creation_config.registrar_account_id
-        {
+        if !can_create_top_level_account(account_id, predecessor_id, account_creation_config) {
             // A short top-level account ID can only be created registrar account.
             result.result = Err(ActionErrorKind::CreateAccountOnlyByRegistrar {
                 account_id: account_id.clone(),
@@ -256,6 +285,9 @@ pub(crate) fn action_implicit_account_creation_transfer(
     }
 }
 
+/// Deploys `deploy_contract` as the account's local contract, replacing any previous one.
+/// Returns the change in the account's storage usage: the new code size minus the storage
+/// released by the previous contract.
 pub(crate) fn action_deploy_contract(
     state_update: &mut TrieUpdate,
     account: &mut Account,
@@ -264,8 +296,9 @@ pub(crate) fn action_deploy_contract(
     config: Arc<VmConfig>,
     next_config: Option<Arc<VmConfig>>,
     cache: Option<&dyn ContractRuntimeCache>,
-) -> Result<(), StorageError> {
+) -> Result<i64, StorageError> {
     let _span = tracing::debug_span!(target: "runtime", "action_deploy_contract").entered();
+    let storage_usage_before = account.storage_usage();
     clear_account_contract_storage_usage(state_update, account_id, account)?;
 
     let code = ContractCode::new(deploy_contract.code.clone(), None);
@@ -293,7 +326,7 @@ pub(crate) fn action_deploy_contract(
     // return the contract before the contract is written out to the underlying storage as part of
     // the `TrieUpdate` commit.)
     state_update.record_contract_deploy(code);
-    Ok(())
+    Ok(account.storage_usage() as i64 - storage_usage_before as i64)
 }
 
 pub(crate) fn action_delete_account(
@@ -419,6 +452,21 @@ pub(crate) fn clear_account_contract_storage_usage(
     Ok(())
 }
 
+/// Checks that the serialized inner actions of `delegate_action` fit in `limit` bytes, so that
+/// the receipt generated from them stays within the size budget of the transactions it came in.
+pub(crate) fn check_delegate_action_size(
+    delegate_action: VersionedDelegateActionRef<'_>,
+    limit: usize,
+) -> Result<(), ActionErrorKind> {
+    // A size which can't be computed doesn't fit in any limit.
+    let size = borsh::object_length(delegate_action.actions()).map_or(u64::MAX, |size| size as u64);
+    let limit = limit as u64;
+    if size > limit {
+        return Err(ActionErrorKind::DelegateActionTooLarge { size, limit });
+    }
+    Ok(())
+}
+
 pub(crate) fn apply_delegate_action(
     state_update: &mut TrieUpdate,
     apply_state: &ApplyState,
@@ -444,6 +492,24 @@ pub(crate) fn apply_delegate_action(
         .into());
         return Ok(());
     }
+    if ProtocolFeature::DelegateActionSizeLimit.enabled(apply_state.current_protocol_version) {
+        let limit = apply_state.config.witness_config.combined_transactions_size_limit;
+        if let Err(err) = check_delegate_action_size(delegate_action, limit) {
+            result.result = Err(err.into());
+            return Ok(());
+        }
+    }
+    // `NonDelegateAction` rules out nested delegate actions, but we don't rely on it here,
+    // as the invariant may be violated by data which bypassed the borsh deserialization.
+    if contains_nested_delegate(delegate_action) {
+        result.result = Err(ActionErrorKind::NewReceiptValidationError(
+            ReceiptValidationError::ActionsValidation(
+                ActionsValidationError::DelegateActionMustBeOnlyOne,
+            ),
+        )
+        .into());
+        return Ok(());
+    }
 
     validate_delegate_action_key(state_update, apply_state, delegate_action, result)?;
     if result.result.is_err() {
@@ -475,7 +541,7 @@ pub(crate) fn apply_delegate_action(
     // Therefore Relayer should verify DelegateAction before submitting it because it spends the attached deposit.
 
     let prepaid_send_fees = total_prepaid_send_fees(&apply_state.config, action_receipt.actions())?;
-    let required_cost = receipt_required_cost(apply_state, &new_receipt)?;
+    let required_cost = receipt_required_cost(&apply_state.config, &new_receipt)?;
     // This gas will be burnt by the receiver of the created receipt.
     // Compute costs of that are not relevant at this point, the "used" gas is
     // only reserved for execution later, potentially on a different shard.
@@ -490,15 +556,27 @@ pub(crate) fn apply_delegate_action(
     Ok(())
 }
 
+/// Returns the gas required to execute the Receipt and all actions it contains,
+/// including the gas attached to function calls.
+///
+/// Doesn't need any state, so it can be used to estimate the gas of a receipt
+/// before submitting it.
+pub fn estimate_required_gas(
+    config: &RuntimeConfig,
+    receipt: &Receipt,
+) -> Result<Gas, RuntimeError> {
+    Ok(receipt_required_cost(config, receipt)?.gas)
+}
+
 /// Returns the cost required to execute the Receipt and all actions it contains
 fn receipt_required_cost(
-    apply_state: &ApplyState,
+    config: &RuntimeConfig,
     receipt: &Receipt,
 ) -> Result<ParameterCost, RuntimeError> {
     Ok(match receipt.versioned_receipt() {
         VersionedReceiptEnum::Action(action_receipt)
         | VersionedReceiptEnum::PromiseYield(action_receipt) => {
-            action_receipt_required_cost(apply_state, receipt, action_receipt.into())?
+            action_receipt_required_cost(config, receipt, action_receipt.into())?
         }
         VersionedReceiptEnum::GlobalContractDistribution(_)
         | VersionedReceiptEnum::Data(_)
@@ -507,23 +585,19 @@ fn receipt_required_cost(
 }
 
 fn action_receipt_required_cost(
-    apply_state: &ApplyState,
+    config: &RuntimeConfig,
     receipt: &Receipt,
     action_receipt: VersionedActionReceipt,
 ) -> Result<ParameterCost, RuntimeError> {
-    let mut required_gas = total_prepaid_exec_fees(
-        &apply_state.config,
-        &action_receipt.actions(),
-        receipt.receiver_id(),
-    )?;
+    let mut required_gas =
+        total_prepaid_exec_fees(config, &action_receipt.actions(), receipt.receiver_id())?;
     let attached_gas = total_prepaid_gas(&action_receipt.actions())?;
     // Gas attached to outgoing function calls have no associated compute costs.
     // Compute costs are only relevant when burning gas.
     let attached_gas_cost = ParameterCost { gas: attached_gas, compute: 0 };
     required_gas = required_gas.checked_add_result(attached_gas_cost)?;
-    required_gas = required_gas.checked_add_result(
-        apply_state.config.fees.fee(ActionCosts::new_action_receipt).exec_fee(),
-    )?;
+    required_gas = required_gas
+        .checked_add_result(config.fees.fee(ActionCosts::new_action_receipt).exec_fee())?;
     Ok(required_gas)
 }
 
@@ -854,6 +928,7 @@ mod tests {
     use super::*;
     use crate::actions_test_utils::{setup_account, test_delete_account};
     use crate::near_primitives::shard_layout::ShardUId;
+    use near_crypto::InMemorySigner;
     use near_primitives::account::FunctionCallPermission;
     use near_primitives::action::FunctionCallAction;
     use near_primitives::action::delegate::{
@@ -863,6 +938,7 @@ mod tests {
     use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
     use near_primitives::congestion_info::BlockCongestionInfo;
     use near_primitives::errors::InvalidAccessKeyError;
+    use near_primitives::receipt::DataReceipt;
     use near_primitives::transaction::CreateAccountAction;
     use near_primitives::types::EpochId;
     use near_primitives::types::Gas;
@@ -970,6 +1046,22 @@ mod tests {
         assert!(action_result.result.is_ok());
     }
 
+    #[test]
+    fn test_can_create_top_level_account() {
+        let config = AccountCreationConfig {
+            min_allowed_top_level_account_length: 11,
+            registrar_account_id: "registrar".parse().unwrap(),
+        };
+        let short: AccountId = "bob".parse().unwrap();
+        let long: AccountId = "bob_near_long_name".parse().unwrap();
+        let registrar: AccountId = "registrar".parse().unwrap();
+        let other: AccountId = "alice.near".parse().unwrap();
+        assert!(can_create_top_level_account(&short, &registrar, &config));
+        assert!(!can_create_top_level_account(&short, &other, &config));
+        assert!(can_create_top_level_account(&long, &registrar, &config));
+        assert!(can_create_top_level_account(&long, &other, &config));
+    }
+
     #[test]
     fn test_delete_account_too_large() {
         let tries = TestTriesBuilder::new().build();
@@ -1030,6 +1122,37 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_deploy_contract_storage_delta() {
+        let tries = TestTriesBuilder::new().build();
+        let mut state_update =
+            tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+        let account_id = "alice".parse::<AccountId>().unwrap();
+        let mut account =
+            Account::new(Balance::from_yoctonear(100), Balance::ZERO, AccountContract::None, 1_000);
+        let apply_state = create_apply_state(0);
+        let mut deploy = |account: &mut Account, code_len: usize| {
+            action_deploy_contract(
+                &mut state_update,
+                account,
+                &account_id,
+                &DeployContractAction { code: vec![0; code_len] },
+                Arc::clone(&apply_state.config.wasm_config),
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(deploy(&mut account, 300), 300);
+        assert_eq!(account.storage_usage(), 1_300);
+        // Replacing a contract releases the storage of the previous code.
+        assert_eq!(deploy(&mut account, 100), -200);
+        assert_eq!(account.storage_usage(), 1_100);
+        assert_eq!(deploy(&mut account, 500), 400);
+        assert_eq!(account.storage_usage(), 1_500);
+    }
+
     fn expect_delete_account_too_large(action_result: &ActionResult) {
         assert_eq!(
             action_result.result,
@@ -1319,6 +1442,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_estimate_required_gas() {
+        let config = RuntimeConfig::test();
+        let (_, signed_delegate_action) = create_delegate_action_receipt();
+        let delegate_action = signed_delegate_action.delegate_action;
+        let actions = delegate_action.get_actions();
+        let receipt = Receipt::V0(ReceiptV0 {
+            predecessor_id: delegate_action.sender_id.clone(),
+            receiver_id: delegate_action.receiver_id.clone(),
+            receipt_id: CryptoHash::default(),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: "alice.test.near".parse().unwrap(),
+                signer_public_key: PublicKey::empty(near_crypto::KeyType::ED25519),
+                gas_price: Balance::from_yoctonear(1),
+                output_data_receivers: Vec::new(),
+                input_data_ids: Vec::new(),
+                actions: actions.clone(),
+            }),
+        });
+        let exec_fees =
+            total_prepaid_exec_fees(&config, &actions, &delegate_action.receiver_id).unwrap();
+        let expected = exec_fees
+            .gas
+            .checked_add(Gas::from_teragas(30))
+            .unwrap()
+            .checked_add(config.fees.fee(ActionCosts::new_action_receipt).exec_fee().gas)
+            .unwrap();
+        assert_eq!(estimate_required_gas(&config, &receipt).unwrap(), expected);
+
+        // Data receipts don't require any gas.
+        let data_receipt = Receipt::V0(ReceiptV0 {
+            predecessor_id: delegate_action.sender_id,
+            receiver_id: delegate_action.receiver_id,
+            receipt_id: CryptoHash::default(),
+            receipt: ReceiptEnum::Data(DataReceipt { data_id: CryptoHash::default(), data: None }),
+        });
+        assert_eq!(estimate_required_gas(&config, &data_receipt).unwrap(), Gas::ZERO);
+    }
+
+    #[test]
+    fn test_delegate_action_size_limit() {
+        let (action_receipt, signed_delegate_action) = create_delegate_action_receipt();
+        let sender_id = signed_delegate_action.delegate_action.sender_id.clone();
+        let sender_pub_key = signed_delegate_action.delegate_action.public_key.clone();
+        let access_key = AccessKey { nonce: 19000000, permission: AccessKeyPermission::FullAccess };
+        let size = borsh::object_length(&signed_delegate_action.delegate_action.actions).unwrap();
+
+        let feature_version = ProtocolFeature::DelegateActionSizeLimit.protocol_version();
+        for (protocol_version, limit, expected) in [
+            (feature_version, size, Ok(())),
+            (
+                feature_version,
+                size - 1,
+                Err(ActionError::from(ActionErrorKind::DelegateActionTooLarge {
+                    size: size as u64,
+                    limit: (size - 1) as u64,
+                })),
+            ),
+            // The limit isn't enforced before the protocol feature is enabled.
+            (feature_version - 1, size - 1, Ok(())),
+        ] {
+            let mut result = ActionResult::default();
+            let mut apply_state =
+                create_apply_state(signed_delegate_action.delegate_action.max_block_height);
+            apply_state.current_protocol_version = protocol_version;
+            let mut config = RuntimeConfig::test();
+            config.witness_config.combined_transactions_size_limit = limit;
+            apply_state.config = Arc::new(config);
+            let mut state_update = setup_account(&sender_id, &sender_pub_key, &access_key);
+
+            apply_delegate_action(
+                &mut state_update,
+                &apply_state,
+                &VersionedActionReceipt::from(&action_receipt),
+                &sender_id,
+                (&signed_delegate_action).into(),
+                &mut result,
+            )
+            .expect("Expect ok");
+
+            assert_eq!(result.result, expected, "version {protocol_version}, limit {limit}");
+        }
+    }
+
     #[test]
     fn test_delegate_action_signature_verification() {
         let mut result = ActionResult::default();
@@ -1373,6 +1580,56 @@ mod tests {
         assert_eq!(result.result, Err(ActionErrorKind::DelegateActionExpired.into()));
     }
 
+    #[test]
+    fn test_delegate_action_nested_delegate() {
+        let mut result = ActionResult::default();
+        let (action_receipt, signed_delegate_action) = create_delegate_action_receipt();
+        let sender_id = signed_delegate_action.delegate_action.sender_id.clone();
+        let signer = InMemorySigner::test_signer(&sender_id);
+        let access_key = AccessKey { nonce: 19000000, permission: AccessKeyPermission::FullAccess };
+
+        // A nested delegate action is rejected when constructing a `NonDelegateAction`
+        // or parsing it from borsh, but JSON deserialization lets it through.
+        let nested_delegate: NonDelegateAction = serde_json::from_value(
+            serde_json::to_value(Action::Delegate(Box::new(signed_delegate_action.clone())))
+                .unwrap(),
+        )
+        .unwrap();
+        let signed_delegate_action = SignedDelegateAction::sign(
+            &signer,
+            DelegateAction {
+                actions: vec![nested_delegate],
+                public_key: signer.public_key(),
+                ..signed_delegate_action.delegate_action
+            },
+        );
+
+        let apply_state =
+            create_apply_state(signed_delegate_action.delegate_action.max_block_height);
+        let mut state_update = setup_account(&sender_id, &signer.public_key(), &access_key);
+
+        apply_delegate_action(
+            &mut state_update,
+            &apply_state,
+            &VersionedActionReceipt::from(&action_receipt),
+            &sender_id,
+            (&signed_delegate_action).into(),
+            &mut result,
+        )
+        .expect("Expect ok");
+
+        assert_eq!(
+            result.result,
+            Err(ActionErrorKind::NewReceiptValidationError(
+                ReceiptValidationError::ActionsValidation(
+                    ActionsValidationError::DelegateActionMustBeOnlyOne
+                )
+            )
+            .into())
+        );
+        assert!(result.new_receipts.is_empty());
+    }
+
     #[test]
     fn test_delegate_action_validate_sender_account() {
         let mut result = ActionResult::default();
@@ -2139,4 +2396,78 @@ mod tests {
             .into())
         );
     }
+
+    fn refund_allowance_with_key(
+        permission: AccessKeyPermission,
+        deposit: Balance,
+    ) -> (AllowanceRefundOutcome, AccessKeyPermission) {
+        let account_id: AccountId = "alice.near".parse().unwrap();
+        let public_key = PublicKey::empty(near_crypto::KeyType::ED25519);
+        let access_key = AccessKey { nonce: 0, permission };
+        let mut state_update = setup_account(&account_id, &public_key, &access_key);
+        let outcome =
+            try_refund_allowance(&mut state_update, &account_id, &public_key, deposit).unwrap();
+        let access_key = get_access_key(&state_update, &account_id, &public_key).unwrap().unwrap();
+        (outcome, access_key.permission)
+    }
+
+    fn allowance_permission(allowance: Option<Balance>) -> AccessKeyPermission {
+        AccessKeyPermission::FunctionCall(FunctionCallPermission {
+            allowance,
+            ..function_call_permission("token.test.near", vec![])
+        })
+    }
+
+    #[test]
+    fn test_refund_allowance_applied() {
+        let (outcome, permission) = refund_allowance_with_key(
+            allowance_permission(Some(Balance::from_yoctonear(100))),
+            Balance::from_yoctonear(50),
+        );
+        assert_eq!(outcome, AllowanceRefundOutcome::Applied);
+        assert_eq!(permission, allowance_permission(Some(Balance::from_yoctonear(150))));
+    }
+
+    #[test]
+    fn test_refund_allowance_unlimited() {
+        let (outcome, permission) =
+            refund_allowance_with_key(allowance_permission(None), Balance::from_yoctonear(50));
+        assert_eq!(outcome, AllowanceRefundOutcome::Unlimited);
+        assert_eq!(permission, allowance_permission(None));
+
+        let (outcome, permission) =
+            refund_allowance_with_key(AccessKeyPermission::FullAccess, Balance::from_yoctonear(50));
+        assert_eq!(outcome, AllowanceRefundOutcome::Unlimited);
+        assert_eq!(permission, AccessKeyPermission::FullAccess);
+    }
+
+    #[test]
+    fn test_refund_allowance_clamped() {
+        let allowance = Balance::MAX.checked_sub(Balance::from_yoctonear(10)).unwrap();
+        let (outcome, permission) = refund_allowance_with_key(
+            allowance_permission(Some(allowance)),
+            Balance::from_yoctonear(50),
+        );
+        assert_eq!(outcome, AllowanceRefundOutcome::Clamped);
+        assert_eq!(permission, allowance_permission(Some(Balance::MAX)));
+    }
+
+    #[test]
+    fn test_refund_allowance_key_not_found() {
+        let account_id: AccountId = "alice.near".parse().unwrap();
+        let access_key = AccessKey::full_access();
+        let mut state_update = setup_account(
+            &account_id,
+            &PublicKey::empty(near_crypto::KeyType::ED25519),
+            &access_key,
+        );
+        let outcome = try_refund_allowance(
+            &mut state_update,
+            &account_id,
+            &PublicKey::empty(near_crypto::KeyType::SECP256K1),
+            Balance::from_yoctonear(50),
+        )
+        .unwrap();
+        assert_eq!(outcome, AllowanceRefundOutcome::KeyNotFound);
+    }
 }
@@ -5,6 +5,7 @@ use near_primitives::account::AccessKeyPermission;
 use near_primitives::action::DeployGlobalContractAction;
 use near_primitives::action::delegate::VersionedDelegateActionRef;
 use near_primitives::errors::IntegerOverflowError;
+use near_primitives::receipt::VersionedActionReceipt;
 // Just re-exporting RuntimeConfig for backwards compatibility.
 use near_parameters::{
     ActionCosts, ExtCosts, ExtCostsConfig, ParameterCost, RuntimeConfig, RuntimeFeesConfig,
@@ -423,37 +424,17 @@ pub fn calculate_tx_cost(
     current_gas_price: Balance,
 ) -> Result<TransactionCost, IntegerOverflowError> {
     let sender_is_receiver = receiver_id == signer_id;
-    let fees = &config.fees;
-    let mut burnt: ParameterCost =
-        fees.fee(ActionCosts::new_action_receipt).send_fee(sender_is_receiver);
-    burnt = burnt.checked_add_result(total_send_fees(
-        config,
-        sender_is_receiver,
-        actions,
-        receiver_id,
-    )?)?;
+    let (mut burnt, gas_remaining) = tx_gas(config, sender_is_receiver, actions, receiver_id)?;
     // Burn the signature-verification cost as part of converting the
     // transaction. This raises the gas the signer must buy (burnt_amount /
     // total_cost below) but never `gas_remaining` (the gas attached to / left
     // for the resulting receipts), so on-chain function-call gas budgets are
     // unaffected.
-    burnt =
-        burnt.checked_add_result(signature_verification_cost(fees, signer_public_key, actions)?)?;
-
-    // Calculate `gas_remaining`, which are all gas costs minus what is already
-    // burnt in the sending step. Compute is not relevant here, as this gas will
-    // be burnt later and has no effect on the current chunk capacity.
-    // Gas attached to function calls
-    let prepaid_gas = total_prepaid_gas(actions)?;
-    // Send/Exec costs for actions inside the receipt
-    let prepaid_send_fee = total_prepaid_send_fees(config, actions)?;
-    let prepaid_exec_fee = total_prepaid_exec_fees(config, actions, receiver_id)?;
-    // Exec cost for the receipt that wraps the actions
-    let receipt_cost = fees.fee(ActionCosts::new_action_receipt).exec_fee();
-    let gas_remaining = prepaid_gas
-        .checked_add_result(prepaid_send_fee.gas)?
-        .checked_add_result(receipt_cost.gas)?
-        .checked_add_result(prepaid_exec_fee.gas)?;
+    burnt = burnt.checked_add_result(signature_verification_cost(
+        &config.fees,
+        signer_public_key,
+        actions,
+    )?)?;
 
     // Gas burned on converting the transaction to a receipt is burned at the current price.
     let burnt_amount = safe_gas_to_balance(current_gas_price, burnt.gas)?;
@@ -478,6 +459,74 @@ pub fn calculate_tx_cost(
     })
 }
 
+/// Returns the gas burnt when converting a transaction with `actions` into a receipt
+/// (excluding the signature verification cost) and the gas remaining for the receipt.
+fn tx_gas(
+    config: &RuntimeConfig,
+    sender_is_receiver: bool,
+    actions: &[Action],
+    receiver_id: &AccountId,
+) -> Result<(ParameterCost, Gas), IntegerOverflowError> {
+    let fees = &config.fees;
+    let burnt = fees
+        .fee(ActionCosts::new_action_receipt)
+        .send_fee(sender_is_receiver)
+        .checked_add_result(total_send_fees(config, sender_is_receiver, actions, receiver_id)?)?;
+
+    // Calculate `gas_remaining`, which are all gas costs minus what is already
+    // burnt in the sending step. Compute is not relevant here, as this gas will
+    // be burnt later and has no effect on the current chunk capacity.
+    // Gas attached to function calls
+    let prepaid_gas = total_prepaid_gas(actions)?;
+    // Send/Exec costs for actions inside the receipt
+    let prepaid_send_fee = total_prepaid_send_fees(config, actions)?;
+    let prepaid_exec_fee = total_prepaid_exec_fees(config, actions, receiver_id)?;
+    // Exec cost for the receipt that wraps the actions
+    let receipt_cost = fees.fee(ActionCosts::new_action_receipt).exec_fee();
+    let gas_remaining = prepaid_gas
+        .checked_add_result(prepaid_send_fee.gas)?
+        .checked_add_result(receipt_cost.gas)?
+        .checked_add_result(prepaid_exec_fee.gas)?;
+    Ok((burnt, gas_remaining))
+}
+
+/// Returns the highest gas price at which a transaction with `actions` sent to
+/// `receiver_id` is still affordable with `account_balance`, i.e. the gas cost
+/// (as computed by [`calculate_tx_cost`]) plus the deposits fit within the
+/// balance. Returns `None` if it is unaffordable at any gas price.
+///
+/// Meant for wallet pre-flight checks. The signer is not known, so it is
+/// assumed to differ from the receiver and to sign with a key that has no
+/// extra signature verification cost.
+pub fn max_affordable_gas_price(
+    actions: &[Action],
+    account_balance: Balance,
+    config: &RuntimeConfig,
+    receiver_id: &AccountId,
+) -> Option<Balance> {
+    let (burnt, gas_remaining) = tx_gas(config, false, actions, receiver_id).ok()?;
+    let burnt_gas = u128::from(burnt.gas.as_gas());
+    let gas_remaining = u128::from(gas_remaining.as_gas());
+    let available = account_balance.checked_sub(total_deposit(actions).ok()?)?.as_yoctonear();
+    let min_gas_purchase_price = config.min_gas_purchase_price.as_yoctonear();
+
+    // At or above `min_gas_purchase_price` all of the gas is purchased at the gas price.
+    let total_gas = burnt_gas.checked_add(gas_remaining)?;
+    if total_gas == 0 {
+        return Some(Balance::from_yoctonear(u128::MAX));
+    }
+    let price = available / total_gas;
+    if price >= min_gas_purchase_price {
+        return Some(Balance::from_yoctonear(price));
+    }
+    // Below it, the remaining gas is still purchased at `min_gas_purchase_price`.
+    let available = available.checked_sub(min_gas_purchase_price.checked_mul(gas_remaining)?)?;
+    if burnt_gas == 0 {
+        return Some(Balance::from_yoctonear(min_gas_purchase_price - 1));
+    }
+    Some(Balance::from_yoctonear(available / burnt_gas))
+}
+
 /// The signature scheme of a signer key, used to key the per-scheme
 /// verification-cost map. Kept as a separate enum (rather than reusing
 /// `KeyType` directly as the map key) so `near-parameters` need not depend on
@@ -573,6 +622,56 @@ pub fn total_deposit(actions: &[Action]) -> Result<Balance, IntegerOverflowError
     Ok(total_balance)
 }
 
+/// Deposits attached to the actions of an action receipt, paid by its predecessor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BalanceEffect {
+    /// Deposits of `Transfer` and `TransferToGasKey` actions.
+    pub transferred: Balance,
+    /// Deposits attached to `FunctionCall` and `DeterministicStateInit` actions.
+    pub attached_to_calls: Balance,
+}
+
+impl BalanceEffect {
+    /// Total balance leaving the predecessor account. Refunds are not taken into account,
+    /// as they are only known after the receipt is executed.
+    pub fn net_outflow(&self) -> Result<Balance, IntegerOverflowError> {
+        safe_add_balance(self.transferred, self.attached_to_calls)
+    }
+
+    fn add_actions(&mut self, actions: &[Action]) -> Result<(), IntegerOverflowError> {
+        for action in actions {
+            match action {
+                Action::Transfer(_) | Action::TransferToGasKey(_) => {
+                    self.transferred =
+                        safe_add_balance(self.transferred, action.get_deposit_balance())?;
+                }
+                Action::FunctionCall(_) | Action::DeterministicStateInit(_) => {
+                    self.attached_to_calls =
+                        safe_add_balance(self.attached_to_calls, action.get_deposit_balance())?;
+                }
+                _ => {
+                    // The deposits of the inner actions are paid by the relayer, same as in
+                    // `total_deposit`.
+                    if let Some(delegate_action) = delegate_inner_action(action) {
+                        self.add_actions(&delegate_action.get_actions())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Computes the deposits that the actions of the receipt take from its predecessor.
+/// This is a purely structural computation, it doesn't depend on any state.
+pub fn action_receipt_balance_effect(
+    action_receipt: &VersionedActionReceipt,
+) -> Result<BalanceEffect, IntegerOverflowError> {
+    let mut effect = BalanceEffect::default();
+    effect.add_actions(action_receipt.actions())?;
+    Ok(effect)
+}
+
 /// Get the total sum of prepaid gas for given actions.
 ///
 /// "Prepaid" in this context means the gas that was attached to function calls
@@ -605,10 +704,11 @@ pub fn total_prepaid_gas(actions: &[Action]) -> Result<Gas, IntegerOverflowError
 mod tests {
     use super::*;
     use near_crypto::SecretKey;
-    use near_primitives::action::TransferAction;
     use near_primitives::action::delegate::{
         DelegateAction, DelegateActionV2, SignedDelegateAction, VersionedSignedDelegateAction,
     };
+    use near_primitives::action::{FunctionCallAction, TransferAction};
+    use near_primitives::receipt::ActionReceipt;
     use near_primitives::transaction::{TransactionNonce, TransactionV0};
     use std::sync::Arc;
 
@@ -791,4 +891,67 @@ mod tests {
         assert_eq!(pq.gas_burnt, ed.gas_burnt);
         assert_eq!(pq.gas_cost, ed.gas_cost);
     }
+
+    /// The returned gas price is the highest one at which the transaction cost
+    /// still fits within the balance.
+    #[test]
+    fn max_affordable_gas_price_threshold() {
+        let config = RuntimeConfig::test();
+        let receiver_id: AccountId = "bob.near".parse().unwrap();
+        let actions = vec![Action::Transfer(TransferAction { deposit: Balance::from_near(1) })];
+        let total_cost = |gas_price: Balance| {
+            let public_key = SecretKey::from_seed(KeyType::ED25519, "signer").public_key();
+            calculate_tx_cost(
+                &receiver_id,
+                &"alice.near".parse().unwrap(),
+                &public_key,
+                &actions,
+                &config,
+                gas_price,
+            )
+            .unwrap()
+            .total_cost
+        };
+
+        let balance = Balance::from_near(2);
+        let price = max_affordable_gas_price(&actions, balance, &config, &receiver_id).unwrap();
+        assert!(total_cost(price) <= balance);
+        assert!(total_cost(price.checked_add(Balance::from_yoctonear(1)).unwrap()) > balance);
+
+        // The deposit alone doesn't fit within the balance.
+        let balance = Balance::from_millinear(999);
+        assert_eq!(max_affordable_gas_price(&actions, balance, &config, &receiver_id), None);
+    }
+
+    #[test]
+    fn action_receipt_balance_effect_sums_deposits() {
+        let action_receipt = ActionReceipt {
+            signer_id: "alice.near".parse().unwrap(),
+            signer_public_key: SecretKey::from_seed(KeyType::ED25519, "signer").public_key(),
+            gas_price: Balance::from_yoctonear(1),
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions: vec![
+                transfer(),
+                Action::FunctionCall(Box::new(FunctionCallAction {
+                    method_name: "ft_transfer".to_string(),
+                    args: vec![],
+                    gas: Gas::from_teragas(30),
+                    deposit: Balance::from_yoctonear(10),
+                })),
+                delegate_with_inner(KeyType::ED25519),
+            ],
+        };
+        let effect =
+            action_receipt_balance_effect(&VersionedActionReceipt::from(&action_receipt)).unwrap();
+        // The transfer inside of the delegate action is paid by the predecessor as well.
+        assert_eq!(
+            effect,
+            BalanceEffect {
+                transferred: Balance::from_yoctonear(2),
+                attached_to_calls: Balance::from_yoctonear(10),
+            }
+        );
+        assert_eq!(effect.net_outflow().unwrap(), Balance::from_yoctonear(12));
+    }
 }
@@ -15,7 +15,11 @@ use near_primitives::receipt::{
 use near_primitives::transaction::FunctionCallAction;
 use near_primitives::trie_key::{SmallKeyVec, TrieKey};
 use near_primitives::types::{AccountId, EpochInfoProvider};
+#[cfg(feature = "test_features")]
+use near_primitives::types::{RawStateChange, RawStateChangesWithTrieKey, StateChangeCause};
 use near_store::trie::AccessOptions;
+#[cfg(feature = "test_features")]
+use near_store::trie::update::TrieKeyValueUpdate;
 use near_store::{
     KeyLookupMode, StorageError, TrieUpdate, enqueue_promise_yield_timeout,
     get_promise_yield_indices, set_promise_yield_indices,
@@ -25,6 +29,8 @@ use near_vm_runner::logic::errors::{
     CompilationError, FunctionCallError, InconsistentStateError, VMRunnerError,
 };
 use near_vm_runner::logic::{VMContext, VMOutcome};
+#[cfg(feature = "test_features")]
+use std::collections::BTreeMap;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -52,11 +58,21 @@ pub(crate) fn action_function_call(
         )
         .into());
     }
+    if !config.function_call_allowlist.is_allowed(account_id, &function_call.method_name) {
+        result.result = Err(ActionErrorKind::MethodNotAllowlisted {
+            account_id: account_id.clone(),
+            method_name: function_call.method_name.clone(),
+        }
+        .into());
+        return Ok(());
+    }
 
     record_contract_call(state_update, contract_id, &apply_state.apply_reason)?;
 
     #[cfg(feature = "test_features")]
     apply_recorded_storage_garbage(function_call, state_update);
+    #[cfg(feature = "test_features")]
+    state_update.start_recording_writes();
 
     let mut receipt_manager = ReceiptManager::default();
     let mut runtime_ext = RuntimeExt::new(
@@ -148,6 +164,9 @@ pub(crate) fn action_function_call(
     // `FunctionCall`s error.
     result.gas_used = result.gas_used.checked_add_result(outcome.used_gas)?;
     result.compute_usage = safe_add_compute(result.compute_usage, outcome.compute_usage)?;
+    if !outcome.gas_distribution.is_empty() {
+        tracing::debug!(target: "runtime", gas_distribution = ?outcome.gas_distribution, "distributed unused gas");
+    }
     result.logs.extend(outcome.logs);
     result.profile.merge(&outcome.profile);
     if execution_succeeded {
@@ -227,6 +246,13 @@ pub(crate) fn action_function_call(
         result.result = Ok(outcome.return_data);
         result.new_receipts.extend(new_receipts);
     }
+    #[cfg(feature = "test_features")]
+    {
+        let writes = state_update.take_recorded_writes();
+        if execution_succeeded {
+            result.captured_state_changes = captured_state_changes(writes, receipt);
+        }
+    }
 
     Ok(())
 }
@@ -326,7 +352,13 @@ pub(crate) fn execute_function_call(
 
     if !context.view_config.is_some() {
         let unused_gas = function_call.gas.saturating_sub(outcome.used_gas);
-        let distributed = runtime_ext.receipt_manager.distribute_gas(unused_gas)?;
+        // The trace is only collected when it is going to be logged.
+        let trace_enabled = tracing::enabled!(target: "runtime", tracing::Level::DEBUG);
+        let distributed = runtime_ext.receipt_manager.distribute_gas(
+            unused_gas,
+            config.gas_distribution_strategy,
+            trace_enabled.then_some(&mut outcome.gas_distribution),
+        )?;
         outcome.used_gas = outcome.used_gas.checked_add_result(distributed)?;
     }
 
@@ -378,6 +410,25 @@ fn record_contract_call(
     Ok(())
 }
 
+/// Groups the recorded writes by trie key, tagged with the cause the receipt
+/// commits them under.
+#[cfg(feature = "test_features")]
+fn captured_state_changes(
+    writes: Vec<TrieKeyValueUpdate>,
+    receipt: &Receipt,
+) -> Vec<RawStateChangesWithTrieKey> {
+    let cause = StateChangeCause::ReceiptProcessing { receipt_hash: receipt.get_hash() };
+    let mut changes = BTreeMap::new();
+    for TrieKeyValueUpdate { trie_key, value } in writes {
+        changes
+            .entry(trie_key.to_vec())
+            .or_insert_with(|| RawStateChangesWithTrieKey { trie_key, changes: Vec::new() })
+            .changes
+            .push(RawStateChange { cause: cause.clone(), data: value });
+    }
+    changes.into_values().collect()
+}
+
 /// See #11703 for more details
 #[cfg(feature = "test_features")]
 fn apply_recorded_storage_garbage(function_call: &FunctionCallAction, state_update: &TrieUpdate) {
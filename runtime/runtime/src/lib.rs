@@ -4,6 +4,7 @@ use crate::access_keys::{
     action_add_key, action_delete_key, action_transfer_to_gas_key, action_withdraw_from_gas_key,
 };
 use crate::actions::*;
+pub use crate::actions::{can_create_top_level_account, estimate_required_gas};
 use crate::config::{
     exec_fee, safe_add_balance, safe_add_compute, safe_gas_to_balance, total_deposit,
     total_prepaid_exec_fees, total_prepaid_gas,
@@ -93,6 +94,7 @@ use std::cmp::max;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::rc::Rc;
+use std::slice;
 use std::sync::Arc;
 use tracing::instrument;
 use verifier::ValidateReceiptMode;
@@ -224,6 +226,25 @@ impl ApplyState {
     ) -> CryptoHash {
         create_receipt_id_from_receipt_id(parent_receipt_id, self.block_height, receipt_index)
     }
+
+    /// Returns a compact one-line summary of the config relevant for debugging apply failures.
+    pub fn debug_summary(&self) -> String {
+        format!(
+            "shard_id={} block_height={} protocol_version={} gas_limit={:?} is_new_chunk={} has_congestion_info={}",
+            self.shard_id,
+            self.block_height,
+            self.current_protocol_version,
+            self.gas_limit,
+            self.is_new_chunk,
+            self.congestion_info.get(&self.shard_id).is_some(),
+        )
+    }
+
+    /// Returns how much gas is left of the chunk gas limit after `burnt` gas, or `None` if the
+    /// apply is not gas limited. The limit is exhausted once this returns `Some(Gas::ZERO)`.
+    pub fn gas_remaining(&self, burnt: Gas) -> Option<Gas> {
+        self.gas_limit.map(|gas_limit| gas_limit.saturating_sub(burnt))
+    }
 }
 
 /// Contains information to update validators accounts at the first block of a new epoch.
@@ -379,6 +400,10 @@ pub struct ActionResult {
     pub current_contract: AccountContract,
     pub tokens_burnt: Balance,
     pub subsidized_amount: Balance,
+    /// State writes made by a successful function call action, tagged with the
+    /// cause they are committed under. Only populated with `test_features`.
+    #[cfg(feature = "test_features")]
+    pub captured_state_changes: Vec<RawStateChangesWithTrieKey>,
 }
 
 impl Default for ActionResult {
@@ -396,6 +421,8 @@ impl Default for ActionResult {
             current_contract: AccountContract::None,
             tokens_burnt: Balance::ZERO,
             subsidized_amount: Balance::ZERO,
+            #[cfg(feature = "test_features")]
+            captured_state_changes: vec![],
         }
     }
 }
@@ -416,6 +443,10 @@ pub struct ActionReceiptResult {
     pub current_contracts: Vec<AccountContract>,
     pub tokens_burnt: Balance,
     pub subsidized_amount: Balance,
+    /// State writes made by the successful function call actions of the receipt.
+    /// Only populated with `test_features`.
+    #[cfg(feature = "test_features")]
+    pub captured_state_changes: Vec<RawStateChangesWithTrieKey>,
 }
 
 impl ActionReceiptResult {
@@ -433,6 +464,8 @@ impl ActionReceiptResult {
             current_contracts: vec![],
             tokens_burnt: Balance::ZERO,
             subsidized_amount: Balance::ZERO,
+            #[cfg(feature = "test_features")]
+            captured_state_changes: vec![],
         }
     }
 
@@ -473,6 +506,8 @@ impl ActionReceiptResult {
                     .subsidized_amount
                     .checked_add(next_result.subsidized_amount)
                     .ok_or(IntegerOverflowError)?;
+                #[cfg(feature = "test_features")]
+                self.captured_state_changes.append(&mut next_result.captured_state_changes);
             }
             Err(err) => self.set_error(err),
         }
@@ -490,6 +525,8 @@ impl ActionReceiptResult {
         self.validator_proposals.clear();
         self.tokens_burnt = Balance::ZERO;
         self.subsidized_amount = Balance::ZERO;
+        #[cfg(feature = "test_features")]
+        self.captured_state_changes.clear();
     }
 }
 
@@ -769,7 +806,76 @@ impl Runtime {
         Ok(result)
     }
 
-    // Executes when all Receipt `input_data_ids` are in the state
+    /// Applies a single `action` of the action `receipt` and returns its result, without
+    /// modifying `state_update` or `account`. The action is applied to copies of both, which
+    /// are discarded afterwards, as if it were the only action of the receipt and there were
+    /// no promise results.
+    ///
+    /// Intended for tooling that simulates the effects of individual actions.
+    pub fn dry_run_action(
+        &self,
+        action: &Action,
+        state_update: &TrieUpdate,
+        apply_state: &ApplyState,
+        account: &Option<Account>,
+        receipt: &Receipt,
+        action_receipt: &VersionedActionReceipt,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<ActionResult, RuntimeError> {
+        let mut state_update = state_update.clone_for_tx_preparation();
+        let mut account = account.clone();
+        self.apply_single_action(
+            action,
+            &mut state_update,
+            apply_state,
+            &mut account,
+            receipt,
+            action_receipt,
+            epoch_info_provider,
+        )
+    }
+
+    /// Applies `action` as if it were the only action of the action `receipt` and there were
+    /// no promise results.
+    fn apply_single_action(
+        &self,
+        action: &Action,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        account: &mut Option<Account>,
+        receipt: &Receipt,
+        action_receipt: &VersionedActionReceipt,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<ActionResult, RuntimeError> {
+        let mut actor_id = receipt.predecessor_id().clone();
+        let preparation_pipeline = ReceiptPreparationPipeline::new(
+            Arc::clone(&apply_state.config),
+            apply_state.next_wasm_config.clone(),
+            apply_state.cache.as_ref().map(|cache| cache.handle()),
+            state_update.contract_storage().clone(),
+            epoch_info_provider.chain_id(),
+            apply_state.shard_id,
+        );
+        let action_hash =
+            create_action_hash_from_receipt_id(receipt.receipt_id(), apply_state.block_height, 0);
+        self.apply_action(
+            action,
+            state_update,
+            apply_state,
+            &preparation_pipeline,
+            account,
+            &mut actor_id,
+            receipt,
+            action_receipt,
+            [].into(),
+            &action_hash,
+            0,
+            slice::from_ref(action),
+            epoch_info_provider,
+            None,
+        )
+    }
+
     fn apply_action_receipt(
         &self,
         state_update: &mut TrieUpdate,
@@ -876,6 +982,19 @@ impl Runtime {
                     new_result.result = Err(ActionErrorKind::NewReceiptValidationError(e).into());
                 }
             }
+            // The action which takes the chunk over the proposals limit fails.
+            let max_validator_proposals = apply_state.config.max_validator_proposals_per_chunk;
+            if new_result.result.is_ok()
+                && (validator_proposals.len()
+                    + result.validator_proposals.len()
+                    + new_result.validator_proposals.len()) as u64
+                    > max_validator_proposals
+            {
+                new_result.result = Err(ActionErrorKind::ValidatorProposalsLimitExceeded {
+                    limit: max_validator_proposals,
+                }
+                .into());
+            }
             result.merge(new_result)?;
             // TODO storage error
             if let Err(ref mut res) = result.result {
@@ -2867,12 +2986,20 @@ fn action_transfer_or_implicit_account_creation(
         }
         action_transfer(account, deposit)?;
         if is_gas_refund {
-            try_refund_allowance(
+            let outcome = try_refund_allowance(
                 state_update,
                 receipt.receiver_id(),
                 &action_receipt.signer_public_key(),
                 deposit,
             )?;
+            if outcome == AllowanceRefundOutcome::Clamped {
+                tracing::debug!(
+                    target: "runtime",
+                    receiver_id = %receipt.receiver_id(),
+                    %deposit,
+                    "allowance refund saturated"
+                );
+            }
         }
     } else {
         debug_assert!(!is_refund);
@@ -1,4 +1,5 @@
 use near_crypto::PublicKey;
+use near_parameters::GasDistributionStrategy;
 use near_primitives::action::{
     Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
     DeployContractAction, DeployGlobalContractAction, DeterministicStateInitAction,
@@ -13,6 +14,7 @@ use near_primitives::receipt::DataReceiver;
 use near_primitives_core::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
 use near_primitives_core::hash::CryptoHash;
 use near_primitives_core::types::{AccountId, Balance, Gas, GasWeight, Nonce, NonceIndex};
+use near_vm_runner::logic::GasDistribution;
 use near_vm_runner::logic::HostError;
 use near_vm_runner::logic::VMLogicError;
 use near_vm_runner::logic::types::{
@@ -647,11 +649,18 @@ impl ReceiptManager {
         );
     }
 
-    /// Distribute the provided `gas` between receipts managed by this `ReceiptManager` according
-    /// to their assigned weights.
+    /// Distribute the provided `gas` between receipts managed by this `ReceiptManager` with a
+    /// non-zero weight, using the given `strategy`.
+    ///
+    /// If `trace` is provided, the gas assigned to every function call is recorded in it.
     ///
     /// Returns the amount of gas distributed (either `0` or `unused_gas`.)
-    pub(super) fn distribute_gas(&mut self, unused_gas: Gas) -> Result<Gas, RuntimeError> {
+    pub(super) fn distribute_gas(
+        &mut self,
+        unused_gas: Gas,
+        strategy: GasDistributionStrategy,
+        mut trace: Option<&mut Vec<GasDistribution>>,
+    ) -> Result<Gas, RuntimeError> {
         let ReceiptManager {
             action_receipts,
             data_receipts: _,
@@ -675,8 +684,15 @@ impl ReceiptManager {
                     "Invalid function call index (promise_index={receipt_index}, action_index={action_index})",
                 );
             };
-            let to_assign =
-                (u128::from(unused_gas.as_gas()) * weight.0 as u128 / gas_weight_sum) as u64;
+            let prepaid_gas = action.gas;
+            let to_assign = match strategy {
+                GasDistributionStrategy::Weighted => {
+                    (u128::from(unused_gas.as_gas()) * weight.0 as u128 / gas_weight_sum) as u64
+                }
+                GasDistributionStrategy::EvenSplit => {
+                    unused_gas.as_gas() / gas_weights.len() as u64
+                }
+            };
             action.gas =
                 action.gas.checked_add(Gas::from_gas(to_assign)).ok_or(IntegerOverflowError)?;
             distributed = distributed
@@ -690,6 +706,13 @@ impl ReceiptManager {
                 action.gas =
                     action.gas.checked_add(Gas::from_gas(remainder)).ok_or(IntegerOverflowError)?;
             }
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(GasDistribution {
+                    receipt_index: receipt_index as u64,
+                    prepaid_gas,
+                    distributed_gas: action.gas.saturating_sub(prepaid_gas),
+                });
+            }
         }
         assert_eq!(unused_gas.as_gas(), distributed);
         Ok(Gas::from_gas(distributed))
@@ -705,11 +728,17 @@ impl ReceiptManager {
 
 #[cfg(test)]
 mod tests {
+    use near_parameters::GasDistributionStrategy;
     use near_primitives::transaction::Action;
     use near_primitives_core::types::{Balance, Gas, GasWeight};
+    use near_vm_runner::logic::GasDistribution;
 
     #[track_caller]
-    fn function_call_weight_verify(function_calls: &[(Gas, u64, Gas)], after_distribute: bool) {
+    fn function_call_weight_verify(
+        function_calls: &[(Gas, u64, Gas)],
+        after_distribute: bool,
+        strategy: GasDistributionStrategy,
+    ) {
         let mut gas_limit = Gas::from_gigagas(10);
 
         // Schedule all function calls
@@ -731,7 +760,7 @@ mod tests {
                 .unwrap();
         }
         let accessor: fn(&(Gas, u64, Gas)) -> Gas = if after_distribute {
-            receipt_manager.distribute_gas(gas_limit).unwrap();
+            receipt_manager.distribute_gas(gas_limit, strategy, None).unwrap();
             |(_, _, expected)| *expected
         } else {
             |(static_gas, _, _)| *static_gas
@@ -759,8 +788,16 @@ mod tests {
 
     #[track_caller]
     fn function_call_weight_check(function_calls: &[(Gas, u64, Gas)]) {
-        function_call_weight_verify(function_calls, false);
-        function_call_weight_verify(function_calls, true);
+        function_call_weight_check_with_strategy(function_calls, GasDistributionStrategy::Weighted);
+    }
+
+    #[track_caller]
+    fn function_call_weight_check_with_strategy(
+        function_calls: &[(Gas, u64, Gas)],
+        strategy: GasDistributionStrategy,
+    ) {
+        function_call_weight_verify(function_calls, false, strategy);
+        function_call_weight_verify(function_calls, true, strategy);
     }
 
     #[test]
@@ -820,4 +857,76 @@ mod tests {
             (Gas::ZERO, 1, Gas::from_gigagas(10)),
         ])
     }
+
+    #[test]
+    fn function_call_weight_trace_test() {
+        let mut receipt_manager = super::ReceiptManager::default();
+        for (static_gas, gas_weight) in
+            [(Gas::from_gas(1_000_000), 3), (Gas::from_gas(3_000_000), 2)]
+        {
+            let index = receipt_manager
+                .create_action_receipt(vec![], vec![], "rick.test".parse().unwrap())
+                .unwrap();
+            receipt_manager
+                .append_action_function_call_weight(
+                    index,
+                    vec![],
+                    vec![],
+                    Balance::ZERO,
+                    static_gas,
+                    GasWeight(gas_weight),
+                )
+                .unwrap();
+        }
+        let mut trace = vec![];
+        let unused_gas = Gas::from_gas(9_996_000_000);
+        let distributed = receipt_manager
+            .distribute_gas(unused_gas, GasDistributionStrategy::Weighted, Some(&mut trace))
+            .unwrap();
+        assert_eq!(distributed, unused_gas);
+        assert_eq!(
+            trace,
+            vec![
+                GasDistribution {
+                    receipt_index: 0,
+                    prepaid_gas: Gas::from_gas(1_000_000),
+                    distributed_gas: Gas::from_gas(5_997_600_000),
+                },
+                GasDistribution {
+                    receipt_index: 1,
+                    prepaid_gas: Gas::from_gas(3_000_000),
+                    distributed_gas: Gas::from_gas(3_998_400_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn function_call_weight_even_split_test() {
+        // Equal weights: both strategies split the gas evenly.
+        let equal_weights =
+            [(Gas::ZERO, 1, Gas::from_gigagas(5)), (Gas::ZERO, 1, Gas::from_gigagas(5))];
+        function_call_weight_check_with_strategy(&equal_weights, GasDistributionStrategy::Weighted);
+        function_call_weight_check_with_strategy(
+            &equal_weights,
+            GasDistributionStrategy::EvenSplit,
+        );
+
+        // Different weights are ignored by the even split.
+        function_call_weight_check_with_strategy(
+            &[(Gas::ZERO, 3, Gas::from_gigagas(5)), (Gas::ZERO, 2, Gas::from_gigagas(5))],
+            GasDistributionStrategy::EvenSplit,
+        );
+
+        // Zero-weight calls don't take part and the remainder goes to the last call.
+        function_call_weight_check_with_strategy(
+            &[
+                (Gas::ZERO, 1, Gas::from_gas(3_333_333_333)),
+                (Gas::ZERO, 0, Gas::ZERO),
+                (Gas::ZERO, 5, Gas::from_gas(3_333_333_333)),
+                (Gas::ZERO, 1, Gas::from_gas(3_333_333_334)),
+            ],
+            GasDistributionStrategy::EvenSplit,
+        );
+    }
 }
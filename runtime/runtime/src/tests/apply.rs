@@ -7,14 +7,15 @@ use crate::tests::{
     set_sha256_cost,
 };
 use crate::{
-    ActionResult, ApplyResult, ApplyState, Runtime, ValidatorAccountsUpdate, action_add_key,
+    ActionReceiptResult, ActionResult, ApplyResult, ApplyState, Runtime, ValidatorAccountsUpdate,
+    action_add_key, action_transfer,
 };
 use crate::{SignedValidPeriodTransactions, total_prepaid_exec_fees};
 use assert_matches::assert_matches;
 use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
 use near_o11y::testonly::init_test_logger;
 use near_parameters::parameter_table::FeeComponent;
-use near_parameters::{ActionCosts, RuntimeConfig};
+use near_parameters::{ActionCosts, FunctionCallAllowlist, GasDistributionStrategy, RuntimeConfig};
 use near_primitives::account::{
     AccessKey, AccessKeyPermission, AccountContract, FunctionCallPermission,
 };
@@ -30,7 +31,9 @@ use near_primitives::errors::{
     InvalidTxError, MissingTrieValue, TxExecutionError,
 };
 use near_primitives::hash::{CryptoHash, hash};
-use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum, ReceiptV0};
+use near_primitives::receipt::{
+    ActionReceipt, Receipt, ReceiptEnum, ReceiptV0, VersionedActionReceipt,
+};
 use near_primitives::shard_layout::{ShardLayout, ShardUId};
 use near_primitives::state::PartialState;
 use near_primitives::stateless_validation::contract_distribution::CodeHash;
@@ -38,7 +41,7 @@ use near_primitives::test_utils::{MockEpochInfoProvider, account_new};
 use near_primitives::transaction::{
     AddKeyAction, CreateAccountAction, DeleteKeyAction, DeployContractAction, ExecutionMetadata,
     ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, FunctionCallAction,
-    SignedTransaction, TransactionNonce, TransferAction,
+    SignedTransaction, StakeAction, TransactionNonce, TransferAction,
 };
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{
@@ -214,6 +217,42 @@ fn test_apply_no_op() {
         .unwrap();
 }
 
+#[test]
+fn test_apply_state_debug_summary() {
+    let (_, _, _, mut apply_state, _, _) = setup_runtime(
+        vec![alice_account()],
+        Balance::from_near(1_000_000),
+        Balance::ZERO,
+        Gas::from_teragas(1000),
+    );
+    let summary = apply_state.debug_summary();
+    assert!(summary.contains(&format!("protocol_version={}", PROTOCOL_VERSION)));
+    assert!(summary.contains("is_new_chunk=true"));
+    assert!(summary.contains("has_congestion_info=true"));
+
+    apply_state.is_new_chunk = false;
+    let summary = apply_state.debug_summary();
+    assert!(summary.contains("is_new_chunk=false"));
+}
+
+#[test]
+fn test_apply_state_gas_remaining() {
+    let (_, _, _, mut apply_state, _, _) = setup_runtime(
+        vec![alice_account()],
+        Balance::from_near(1_000_000),
+        Balance::ZERO,
+        Gas::from_teragas(1000),
+    );
+    assert_eq!(apply_state.gas_remaining(Gas::ZERO), Some(Gas::from_teragas(1000)));
+    assert_eq!(apply_state.gas_remaining(Gas::from_teragas(300)), Some(Gas::from_teragas(700)));
+    assert_eq!(apply_state.gas_remaining(Gas::from_teragas(1000)), Some(Gas::ZERO));
+    // Burning more than the limit saturates at zero.
+    assert_eq!(apply_state.gas_remaining(Gas::from_teragas(1500)), Some(Gas::ZERO));
+
+    apply_state.gas_limit = None;
+    assert_eq!(apply_state.gas_remaining(Gas::from_teragas(1500)), None);
+}
+
 #[test]
 fn test_apply_check_balance_validation_rewards() {
     let initial_locked = Balance::from_near(500_000);
@@ -1252,6 +1291,79 @@ fn test_delete_key_underflow() {
     assert_eq!(final_account_state.storage_usage(), 0);
 }
 
+#[test]
+fn test_validator_proposals_limit() {
+    let (runtime, tries, root, mut apply_state, signers, epoch_info_provider) = setup_runtime(
+        vec![alice_account(), bob_account()],
+        Balance::from_near(1_000_000),
+        Balance::ZERO,
+        Gas::from_teragas(1000),
+    );
+    let mut runtime_config = RuntimeConfig::test();
+    runtime_config.max_validator_proposals_per_chunk = 2;
+    apply_state.config = Arc::new(runtime_config);
+
+    let stake_actions = |signer: &Arc<Signer>, n: u64| {
+        (1..=n)
+            .map(|i| {
+                Action::Stake(Box::new(StakeAction {
+                    stake: Balance::from_near(i),
+                    public_key: signer.public_key(),
+                }))
+            })
+            .collect::<Vec<_>>()
+    };
+    let apply = |receipts: &[Receipt]| {
+        runtime.apply(
+            tries.get_trie_for_shard(ShardUId::single_shard(), root),
+            &None,
+            &apply_state,
+            receipts,
+            SignedValidPeriodTransactions::empty(),
+            &epoch_info_provider,
+            Default::default(),
+        )
+    };
+
+    // Proposals up to the limit are accepted.
+    let receipts = vec![create_receipt_with_actions(
+        alice_account(),
+        signers[0].clone(),
+        stake_actions(&signers[0], 2),
+    )];
+    let apply_result = apply(&receipts).unwrap();
+    assert_eq!(apply_result.validator_proposals.len(), 2);
+
+    // The stake action going over the limit fails its receipt, the chunk still applies.
+    let receipts = vec![
+        create_receipt_with_actions(
+            alice_account(),
+            signers[0].clone(),
+            stake_actions(&signers[0], 2),
+        ),
+        create_receipt_with_actions(
+            bob_account(),
+            signers[1].clone(),
+            stake_actions(&signers[1], 1),
+        ),
+    ];
+    let apply_result = apply(&receipts).unwrap();
+    assert_eq!(apply_result.validator_proposals.len(), 2);
+    assert!(apply_result.validator_proposals.iter().all(|p| p.account_id() == &alice_account()));
+    let status = |receipt: &Receipt| {
+        let outcome = apply_result.outcomes.iter().find(|o| &o.id == receipt.receipt_id()).unwrap();
+        outcome.outcome.status.clone()
+    };
+    assert_eq!(status(&receipts[0]), ExecutionStatus::SuccessValue(vec![]));
+    assert_eq!(
+        status(&receipts[1]),
+        ExecutionStatus::Failure(TxExecutionError::ActionError(ActionError {
+            index: Some(0),
+            kind: ActionErrorKind::ValidatorProposalsLimitExceeded { limit: 2 },
+        }))
+    );
+}
+
 #[test]
 #[cfg(target_arch = "x86_64")]
 fn test_contract_precompilation() {
@@ -1296,6 +1408,120 @@ fn test_contract_precompilation() {
     assert_matches!(cached, Ok(true), "compiled contract should be cached");
 }
 
+#[test]
+#[cfg(feature = "test_features")]
+fn test_function_call_captures_state_changes() {
+    use crate::pipelining::ReceiptPreparationPipeline;
+    use near_primitives::receipt::VersionedReceiptEnum;
+    use near_store::TrieUpdate;
+
+    let (runtime, tries, root, apply_state, signers, epoch_info_provider) = setup_runtime(
+        vec![alice_account()],
+        Balance::from_near(1_000_000),
+        Balance::from_near(500_000),
+        Gas::from_gas(10u64.pow(15)),
+    );
+    let shard_uid = ShardUId::single_shard();
+
+    let deploy_receipt = create_receipt_with_actions(
+        alice_account(),
+        signers[0].clone(),
+        vec![Action::DeployContract(DeployContractAction {
+            code: near_test_contracts::rs_contract().to_vec(),
+        })],
+    );
+    let apply_result = runtime
+        .apply(
+            tries.get_trie_for_shard(shard_uid, root),
+            &None,
+            &apply_state,
+            &[deploy_receipt],
+            SignedValidPeriodTransactions::empty(),
+            &epoch_info_provider,
+            Default::default(),
+        )
+        .unwrap();
+    let mut store_update = tries.store_update();
+    let root = tries.apply_all(&apply_result.trie_changes, shard_uid, &mut store_update);
+    store_update.commit();
+
+    let key = b"captured".to_vec();
+    let value = 42u64.to_le_bytes();
+    let action = Action::FunctionCall(Box::new(FunctionCallAction {
+        method_name: "write_key_value".to_string(),
+        args: [key.as_slice(), &value].concat(),
+        gas: Gas::from_teragas(100),
+        deposit: Balance::ZERO,
+    }));
+    let receipt =
+        create_receipt_with_actions(alice_account(), signers[0].clone(), vec![action.clone()]);
+    let VersionedReceiptEnum::Action(action_receipt) = receipt.versioned_receipt() else {
+        unreachable!("receipt was created with actions");
+    };
+
+    let mut state_update = tries.new_trie_update(shard_uid, root);
+    let pipeline = ReceiptPreparationPipeline::new(
+        Arc::clone(&apply_state.config),
+        apply_state.next_wasm_config.clone(),
+        apply_state.cache.as_ref().map(|c| c.handle()),
+        state_update.contract_storage().clone(),
+        epoch_info_provider.chain_id(),
+        apply_state.shard_id,
+    );
+    let mut account = get_account(&state_update, &alice_account()).unwrap();
+    let mut actor_id = alice_account();
+    let mut call = |state_update: &mut TrieUpdate| {
+        runtime
+            .apply_action(
+                &action,
+                state_update,
+                &apply_state,
+                &pipeline,
+                &mut account,
+                &mut actor_id,
+                &receipt,
+                &action_receipt,
+                Arc::from([]),
+                receipt.receipt_id(),
+                0,
+                action_receipt.actions(),
+                &epoch_info_provider,
+                None,
+            )
+            .unwrap()
+    };
+    // The second call writes the same value again, the write is captured nonetheless.
+    let results = [call(&mut state_update), call(&mut state_update)];
+
+    let data_key = TrieKey::ContractData { account_id: alice_account(), key };
+    for result in &results {
+        assert_matches!(result.result, Ok(_));
+        let captured = result
+            .captured_state_changes
+            .iter()
+            .find(|changes| changes.trie_key == data_key)
+            .expect("contract data write should be captured");
+        assert_eq!(captured.changes.len(), 1);
+        assert_eq!(captured.changes[0].data, Some(value.to_vec()));
+        assert_matches!(
+            captured.changes[0].cause,
+            StateChangeCause::ReceiptProcessing { receipt_hash } if receipt_hash == receipt.get_hash()
+        );
+    }
+
+    // The captured changes are kept in the receipt result.
+    let mut receipt_result = ActionReceiptResult::new();
+    for result in results {
+        receipt_result.merge(result).unwrap();
+    }
+    let captured = receipt_result
+        .captured_state_changes
+        .iter()
+        .filter(|changes| changes.trie_key == data_key)
+        .count();
+    assert_eq!(captured, 2);
+}
+
 #[test]
 fn test_compute_usage_limit() {
     let (runtime, tries, mut root, mut apply_state, signers, epoch_info_provider) = setup_runtime(
@@ -2518,6 +2744,182 @@ fn test_call_account_without_contract() {
     assert_eq!(apply_result.contract_updates.contract_deploy_hashes(), HashSet::new());
 }
 
+fn apply_call_with_allowlist(allowlist: FunctionCallAllowlist) -> ExecutionStatus {
+    let (runtime, tries, root, mut apply_state, signers, epoch_info_provider) = setup_runtime(
+        vec![alice_account()],
+        Balance::from_near(1_000_000),
+        Balance::from_near(500_000),
+        MAX_ATTACHED_GAS,
+    );
+
+    let mut config = RuntimeConfig::free();
+    config.function_call_allowlist = allowlist;
+    apply_state.config = Arc::new(config);
+
+    let receipt = create_receipt_with_actions(
+        alice_account(),
+        signers[0].clone(),
+        vec![
+            Action::DeployContract(DeployContractAction {
+                code: near_test_contracts::rs_contract().to_vec(),
+            }),
+            Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: "ext_sha256".to_string(),
+                args: b"first".to_vec(),
+                gas: MAX_ATTACHED_GAS,
+                deposit: Balance::ZERO,
+            })),
+        ],
+    );
+
+    let apply_result = runtime
+        .apply(
+            tries.get_trie_for_shard(ShardUId::single_shard(), root),
+            &None,
+            &apply_state,
+            &[receipt],
+            SignedValidPeriodTransactions::empty(),
+            &epoch_info_provider,
+            Default::default(),
+        )
+        .unwrap();
+    apply_result.outcomes.last().unwrap().outcome.status.clone()
+}
+
+#[test]
+fn test_function_call_allowlisted() {
+    let status = apply_call_with_allowlist(FunctionCallAllowlist::new([(
+        alice_account(),
+        "ext_sha256".to_string(),
+    )]));
+    assert_matches!(status, ExecutionStatus::SuccessValue(_));
+}
+
+#[test]
+fn test_function_call_not_allowlisted() {
+    let status = apply_call_with_allowlist(FunctionCallAllowlist::new([
+        (alice_account(), "log_something".to_string()),
+        (bob_account(), "ext_sha256".to_string()),
+    ]));
+    let action_error = assert_matches!(
+        status,
+        ExecutionStatus::Failure(TxExecutionError::ActionError(ae)) => ae
+    );
+    assert_eq!(action_error.index, Some(1));
+    assert_eq!(
+        action_error.kind,
+        ActionErrorKind::MethodNotAllowlisted {
+            account_id: alice_account(),
+            method_name: "ext_sha256".to_string(),
+        }
+    );
+}
+
+/// Calls a contract which schedules a function call to bob with each of the
+/// given gas weights, and returns the gas attached to the scheduled calls.
+fn apply_call_with_gas_distribution(
+    strategy: GasDistributionStrategy,
+    gas_weights: &[u64],
+) -> Vec<Gas> {
+    let (runtime, tries, root, mut apply_state, signers, epoch_info_provider) = setup_runtime(
+        vec![alice_account(), bob_account()],
+        Balance::from_near(1_000_000),
+        Balance::from_near(500_000),
+        MAX_ATTACHED_GAS,
+    );
+
+    let mut config = RuntimeConfig::free();
+    config.gas_distribution_strategy = strategy;
+    apply_state.config = Arc::new(config);
+
+    let promises: Vec<_> = gas_weights
+        .iter()
+        .enumerate()
+        .flat_map(|(promise_index, gas_weight)| {
+            [
+                serde_json::json!({
+                    "batch_create": { "account_id": bob_account() },
+                    "id": promise_index,
+                }),
+                serde_json::json!({
+                    "action_function_call_weight": {
+                        "promise_index": promise_index,
+                        "method_name": "noop",
+                        "arguments": [],
+                        "amount": "0",
+                        "gas": 0,
+                        "gas_weight": gas_weight,
+                    },
+                    "id": promise_index,
+                }),
+            ]
+        })
+        .collect();
+    let receipt = create_receipt_with_actions(
+        alice_account(),
+        signers[0].clone(),
+        vec![
+            Action::DeployContract(DeployContractAction {
+                code: near_test_contracts::rs_contract().to_vec(),
+            }),
+            Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: "call_promise".to_string(),
+                args: serde_json::to_vec(&promises).unwrap(),
+                gas: MAX_ATTACHED_GAS,
+                deposit: Balance::ZERO,
+            })),
+        ],
+    );
+
+    let apply_result = runtime
+        .apply(
+            tries.get_trie_for_shard(ShardUId::single_shard(), root),
+            &None,
+            &apply_state,
+            &[receipt],
+            SignedValidPeriodTransactions::empty(),
+            &epoch_info_provider,
+            Default::default(),
+        )
+        .unwrap();
+    assert_matches!(
+        apply_result.outcomes.last().unwrap().outcome.status,
+        ExecutionStatus::SuccessValue(_)
+    );
+    apply_result
+        .outgoing_receipts
+        .iter()
+        .filter(|receipt| receipt.receiver_id() == &bob_account())
+        .map(|receipt| match receipt.receipt() {
+            ReceiptEnum::Action(ActionReceipt { actions, .. }) => match &actions[..] {
+                [Action::FunctionCall(function_call)] => function_call.gas,
+                other => panic!("expected a single function call, got {other:?}"),
+            },
+            other => panic!("expected an action receipt, got {other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_gas_distribution_strategy() {
+    // With equal weights both strategies split the unused gas evenly.
+    for strategy in [GasDistributionStrategy::Weighted, GasDistributionStrategy::EvenSplit] {
+        let gas = apply_call_with_gas_distribution(strategy, &[1, 1]);
+        assert_eq!(gas.len(), 2);
+        let total = gas[0].checked_add(gas[1]).unwrap();
+        assert!(total > Gas::ZERO);
+        assert_eq!(gas[0], Gas::from_gas(total.as_gas() / 2), "{strategy:?}");
+    }
+
+    // The weighted strategy follows the weights, the even split ignores them.
+    let gas = apply_call_with_gas_distribution(GasDistributionStrategy::Weighted, &[1, 3]);
+    let total = gas[0].checked_add(gas[1]).unwrap();
+    assert_eq!(gas[0], Gas::from_gas(total.as_gas() / 4));
+    let gas = apply_call_with_gas_distribution(GasDistributionStrategy::EvenSplit, &[1, 3]);
+    let total = gas[0].checked_add(gas[1]).unwrap();
+    assert_eq!(gas[0], Gas::from_gas(total.as_gas() / 2));
+}
+
 /// Tests that we do not record the contract accesses when validating the chunk.
 #[test]
 fn test_contract_accesses_when_validating_chunk() {
@@ -4528,3 +4930,92 @@ fn test_function_call_after_same_chunk_delete_recreate_resolves_fresh_code() {
         }))
     );
 }
+
+#[test]
+fn test_dry_run_action() {
+    let (runtime, tries, root, apply_state, signers, epoch_info_provider) = setup_runtime(
+        vec![alice_account()],
+        Balance::from_near(1_000_000),
+        Balance::ZERO,
+        Gas::from_teragas(1000),
+    );
+    let shard_layout = epoch_info_provider.shard_layout(&EpochId::default()).unwrap();
+    let shard_uid = shard_layout.shard_uids().next().unwrap();
+    let state_update = tries.new_trie_update(shard_uid, root);
+    let account_id = alice_account();
+    let account = get_account(&state_update, &account_id).unwrap();
+
+    // Runs `action` both as a dry run and directly on a fresh copy of the state, checks that
+    // the results match, and returns the state and account the direct run left behind.
+    let dry_run = |action: Action| {
+        let receipt = create_receipt_with_actions(
+            account_id.clone(),
+            signers[0].clone(),
+            vec![action.clone()],
+        );
+        let ReceiptEnum::Action(action_receipt) = receipt.receipt() else {
+            panic!("expected an action receipt");
+        };
+        let action_receipt = VersionedActionReceipt::from(action_receipt);
+        let result = runtime
+            .dry_run_action(
+                &action,
+                &state_update,
+                &apply_state,
+                &account,
+                &receipt,
+                &action_receipt,
+                &epoch_info_provider,
+            )
+            .unwrap();
+
+        let mut direct_state_update = tries.new_trie_update(shard_uid, root);
+        let mut direct_account = account.clone();
+        let expected = runtime
+            .apply_single_action(
+                &action,
+                &mut direct_state_update,
+                &apply_state,
+                &mut direct_account,
+                &receipt,
+                &action_receipt,
+                &epoch_info_provider,
+            )
+            .unwrap();
+        assert_eq!(result.result, expected.result);
+        assert_eq!(result.gas_burnt, expected.gas_burnt);
+        assert_eq!(result.gas_used, expected.gas_used);
+        assert_eq!(result.compute_usage, expected.compute_usage);
+        assert_eq!(result.tokens_burnt, expected.tokens_burnt);
+        assert_eq!(result.new_receipts, expected.new_receipts);
+
+        // The dry run leaves the original state untouched.
+        assert_eq!(get_account(&state_update, &account_id).unwrap(), account);
+        (result, direct_state_update, direct_account)
+    };
+
+    // Transfer
+    let deposit = Balance::from_near(1);
+    let (result, _, direct_account) = dry_run(Action::Transfer(TransferAction { deposit }));
+    assert_eq!(result.result, ActionResult::default().result);
+    let mut expected_account = account.clone().unwrap();
+    action_transfer(&mut expected_account, deposit).unwrap();
+    assert_eq!(direct_account, Some(expected_account));
+
+    // AddKey of a new key.
+    let new_key = PublicKey::from_seed(KeyType::ED25519, "dry_run_key");
+    let add_key =
+        AddKeyAction { public_key: new_key.clone(), access_key: AccessKey::full_access() };
+    let (result, direct_state_update, _) = dry_run(Action::AddKey(Box::new(add_key)));
+    assert_eq!(result.result, ActionResult::default().result);
+    assert!(get_access_key(&direct_state_update, &account_id, &new_key).unwrap().is_some());
+    assert!(get_access_key(&state_update, &account_id, &new_key).unwrap().is_none());
+
+    // AddKey of the key the account already has.
+    let existing_key =
+        AddKeyAction { public_key: signers[0].public_key(), access_key: AccessKey::full_access() };
+    assert_matches!(
+        dry_run(Action::AddKey(Box::new(existing_key))).0.result,
+        Err(ActionError { kind: ActionErrorKind::AddKeyAlreadyExists { .. }, .. })
+    );
+}
@@ -82,6 +82,14 @@ pub fn check_storage_stake(
     }
 }
 
+/// Returns the balance an account needs to cover its storage usage, saturating
+/// at `Balance::MAX` on overflow.
+///
+/// Unlike [`check_storage_stake`], this does not exempt zero balance accounts.
+pub fn required_storage_balance(account: &Account, runtime_config: &RuntimeConfig) -> Balance {
+    runtime_config.storage_amount_per_byte().saturating_mul(u128::from(account.storage_usage()))
+}
+
 /// Zero Balance Account introduced in NEP 448 https://github.com/near/NEPs/pull/448
 /// An account is a zero balance account if and only if the account uses no more than `ZERO_BALANCE_ACCOUNT_STORAGE_LIMIT` bytes
 fn is_zero_balance_account(account: &Account) -> bool {
@@ -1438,6 +1446,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_required_storage_balance() {
+        let mut config = RuntimeConfig::free();
+        let fees = Arc::make_mut(&mut config.fees);
+        fees.storage_usage_config.storage_amount_per_byte = Balance::from_yoctonear(10_000_000);
+
+        let account = Account::new(Balance::ZERO, Balance::ZERO, AccountContract::None, 1_000);
+        assert_eq!(
+            required_storage_balance(&account, &config),
+            Balance::from_yoctonear(10_000_000_000)
+        );
+
+        let fees = Arc::make_mut(&mut config.fees);
+        fees.storage_usage_config.storage_amount_per_byte = Balance::MAX;
+        assert_eq!(required_storage_balance(&account, &config), Balance::MAX);
+    }
+
     #[test]
     fn test_validate_transaction_invalid_low_balance() {
         let mut config = RuntimeConfig::free();
@@ -2,6 +2,7 @@ use super::peer_manager_actor::NetworkRequestHandler;
 use super::state::NodeExecutionData;
 use crate::utils::network::{
     block_dropper_by_height, chunk_endorsement_dropper, chunk_endorsement_dropper_by_hash,
+    messages_to_account_dropper, random_message_dropper,
 };
 use near_async::messaging::{CanSend, LateBoundSender};
 use near_async::test_loop::data::TestLoopData;
@@ -9,10 +10,14 @@ use near_async::test_loop::sender::TestLoopSender;
 use near_chunks::adapter::ShardsManagerRequestFromClient;
 use near_chunks::shards_manager_actor::ShardsManagerActor;
 use near_epoch_manager::EpochManagerAdapter;
+use near_network::types::NetworkRequestsDiscriminants;
+use near_primitives::hash::CryptoHash;
 use near_primitives::sharding::{ChunkHash, ShardChunkHeader};
 use near_primitives::types::{AccountId, BlockHeight, ShardId, ShardIndex};
 use near_vm_runner::logic::ProtocolVersion;
 use parking_lot::Mutex;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 use std::sync::Arc;
@@ -42,6 +47,33 @@ pub enum DropCondition {
         shards: HashSet<ShardId>,
         epoch_heights: HashSet<u64>,
     },
+    /// Drop all network messages of the given kind sent by the `from` node to
+    /// the `to` account. Messages addressed to multiple accounts are still
+    /// delivered to the other recipients.
+    ///
+    /// The kind is a `NetworkRequests` variant rather than a `TieredMessageBody`
+    /// one, because the test loop intercepts requests before the peer manager
+    /// would turn them into routed message bodies.
+    MessagesBetween {
+        from: AccountId,
+        to: AccountId,
+        kind: NetworkRequestsDiscriminants,
+    },
+    /// Drop each network message sent by any node with probability `ratio`.
+    /// Requests which don't send a message, such as banning a peer, are kept.
+    /// Each node draws from its own RNG derived from `seed` and its account id,
+    /// so the losses are reproducible.
+    ///
+    /// Drop conditions are applied in the reverse order they were added, and a
+    /// message dropped by one of them is not seen by the others. So if this
+    /// condition is added after a targeted one, it also consumes randomness for
+    /// the messages which the targeted condition would drop anyway; if added
+    /// before, it only sees the messages which the targeted condition let
+    /// through.
+    RandomLoss {
+        ratio: f64,
+        seed: u64,
+    },
 }
 
 /// Stores all chunks ever observed on chain. Determines if a chunk can be
@@ -142,6 +174,22 @@ impl NodeExecutionData {
                     epoch_heights.clone(),
                 );
             }
+            DropCondition::MessagesBetween { from, to, kind } => {
+                if &self.account_id == from {
+                    self.register_override_handler(
+                        test_loop_data,
+                        messages_to_account_dropper(to.clone(), *kind),
+                    );
+                }
+            }
+            DropCondition::RandomLoss { ratio, seed } => {
+                assert!((0.0..=1.0).contains(ratio), "loss ratio must be within [0, 1]");
+                let rng_seed = CryptoHash::hash_borsh((seed, &self.account_id));
+                self.register_override_handler(
+                    test_loop_data,
+                    random_message_dropper(*ratio, ChaCha20Rng::from_seed(rng_seed.0)),
+                );
+            }
         }
     }
 
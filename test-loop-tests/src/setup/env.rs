@@ -3,11 +3,13 @@ use super::drop_condition::DropCondition;
 use super::setup::setup_client;
 use super::state::{NodeExecutionData, NodeSetupState, SharedState};
 use crate::utils::account::{archival_account_id, rpc_account_id};
+use crate::utils::get_head_epoch_height;
 use crate::utils::node::{NodeRunner, TestLoopNode, TestLoopNodeMut};
 use near_async::test_loop::TestLoopV2;
-use near_async::test_loop::data::TestLoopData;
+use near_async::test_loop::data::{TestLoopData, TestLoopDataHandle};
 use near_async::time::Duration;
-use near_primitives::types::AccountId;
+use near_client::client_actor::ClientActor;
+use near_primitives::types::{AccountId, EpochHeight};
 use near_store::Store;
 use near_store::adapter::StoreAdapter;
 use near_store::archive::cloud_storage::CloudStorage;
@@ -245,6 +247,22 @@ impl TestLoopEnv {
         self.account_data_idx(&archival_account_id())
     }
 
+    /// Runs the test loop until the head of the given client is in an epoch with height at
+    /// least `target_height`. Panics if this doesn't happen within `max_duration`.
+    pub fn run_until_epoch_height(
+        &mut self,
+        client_handle: &TestLoopDataHandle<ClientActor>,
+        target_height: EpochHeight,
+        max_duration: Duration,
+    ) {
+        self.test_loop.run_until(
+            |test_loop_data| {
+                get_head_epoch_height(&test_loop_data.get(client_handle).client) >= target_height
+            },
+            max_duration,
+        );
+    }
+
     pub fn node_state_builder(&self) -> NodeStateBuilder<'_> {
         let genesis = self.shared_state.genesis.clone();
         let tempdir_path = self.shared_state.tempdir.path().to_path_buf();
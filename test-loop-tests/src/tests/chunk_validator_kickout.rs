@@ -1,10 +1,12 @@
 use crate::setup::builder::TestLoopBuilder;
 use crate::setup::drop_condition::DropCondition;
-use crate::utils::validators::get_epoch_all_validators;
+use crate::utils::get_head_epoch_height;
+use crate::utils::sharding::{missed_chunks_in_block, missed_chunks_since};
+use crate::utils::validators::{ValidatorSetDiff, get_epoch_all_validators};
 use itertools::Itertools;
-use near_async::test_loop::data::TestLoopData;
 use near_async::time::Duration;
 use near_chain_configs::test_genesis::{TestEpochConfigBuilder, ValidatorsSpec};
+use near_network::types::NetworkRequestsDiscriminants;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::{AccountId, Balance};
@@ -21,6 +23,9 @@ enum TestCase {
     DropChunksValidatedBy(AccountId),
     /// Drop endorsements from the account.
     DropEndorsementsFrom(AccountId),
+    /// Drop endorsements sent by the account to each account, one
+    /// `DropCondition::MessagesBetween` per recipient.
+    CensorEndorsementsFrom(AccountId),
 }
 
 impl TestCase {
@@ -28,6 +33,7 @@ impl TestCase {
         match self {
             TestCase::DropChunksValidatedBy(account_id) => account_id,
             TestCase::DropEndorsementsFrom(account_id) => account_id,
+            TestCase::CensorEndorsementsFrom(account_id) => account_id,
         }
     }
 }
@@ -48,7 +54,7 @@ fn run_test_chunk_validator_kickout(accounts: Vec<AccountId>, test_case: TestCas
         TestCase::DropChunksValidatedBy(_) => 1,
         // Target giving a large number of mandates to each chunk validator, so that if we drop all the
         // endorsements from one of the validators, this will not result in missing any chunks.
-        TestCase::DropEndorsementsFrom(_) => 16,
+        TestCase::DropEndorsementsFrom(_) | TestCase::CensorEndorsementsFrom(_) => 16,
     };
 
     // Only chunk validator-only node can be kicked out for low endorsement stats.
@@ -95,55 +101,79 @@ fn run_test_chunk_validator_kickout(accounts: Vec<AccountId>, test_case: TestCas
         TestCase::DropEndorsementsFrom(account_id) => {
             env.drop(DropCondition::EndorsementsFrom(account_id.clone()))
         }
+        // Drop the same endorsements, but separately for each recipient.
+        TestCase::CensorEndorsementsFrom(account_id) => {
+            let kinds = [
+                NetworkRequestsDiscriminants::ChunkEndorsement,
+                NetworkRequestsDiscriminants::SpiceChunkEndorsement,
+            ];
+            accounts.iter().cartesian_product(kinds).fold(env, |env, (to, kind)| {
+                env.drop(DropCondition::MessagesBetween {
+                    from: account_id.clone(),
+                    to: to.clone(),
+                    kind,
+                })
+            })
+        }
     };
     let mut env = env.warmup();
 
-    // Run chain until our targeted chunk validator is (not) kicked out.
     let client_handle = env.node_datas[0].client_sender.actor_handle();
-    let initial_validators =
-        get_epoch_all_validators(&env.test_loop.data.get(&client_handle).client);
+    let client = &env.test_loop.data.get(&client_handle).client;
+    let initial_validators = get_epoch_all_validators(client);
+    let start_height = client.chain.head().unwrap().height;
     assert_eq!(initial_validators.len(), NUM_ACCOUNTS);
     assert!(initial_validators.contains(&test_case.selected_account().to_string()));
-    let success_condition = |test_loop_data: &mut TestLoopData| -> bool {
-        let client = &test_loop_data.get(&client_handle).client;
-        let tip = client.chain.head().unwrap();
-
-        // Check the number of missed chunks for each test case.
-        let block = client.chain.get_block(&tip.last_block_hash).unwrap();
-        let num_missed_chunks = block.header().chunk_mask().iter().filter(|c| !**c).count();
-        match &test_case {
-            TestCase::DropChunksValidatedBy(_) => assert!(
-                num_missed_chunks <= 1,
-                "At most one chunk must be missed when dropping chunks validated by the selected account"
-            ),
-            TestCase::DropEndorsementsFrom(_) => assert_eq!(
-                num_missed_chunks, 0,
-                "No chunk must be missed when dropping endorsements from the selected account"
-            ),
-        }
 
-        let validators = get_epoch_all_validators(client);
-        let epoch_height =
-            client.epoch_manager.get_epoch_height_from_prev_block(&tip.prev_block_hash).unwrap();
-        if let Some(account_id) = &account_to_kickout {
-            assert!(epoch_height < 4);
-            return if validators.len() == NUM_ACCOUNTS - 1 {
-                assert!(!validators.contains(&account_id.to_string()));
+    // Timeout at producing 5 epochs, approximately.
+    let timeout = Duration::seconds((5 * epoch_length) as i64);
+    if let Some(account_id) = &account_to_kickout {
+        // Run chain until our targeted chunk validator is kicked out.
+        env.test_loop.run_until(
+            |test_loop_data| {
+                let client = &test_loop_data.get(&client_handle).client;
+                assert!(get_head_epoch_height(client) < 4);
+                let diff = ValidatorSetDiff::between(
+                    &initial_validators,
+                    &get_epoch_all_validators(client),
+                );
+                if diff.is_empty() {
+                    return false;
+                }
+                assert_eq!(diff, ValidatorSetDiff::only_kicked_out(&[account_id.as_str()]));
                 true
-            } else {
-                false
-            };
-        } else {
-            assert_eq!(validators.len(), NUM_ACCOUNTS, "No kickouts are expected");
-            epoch_height >= 4
-        }
-    };
+            },
+            timeout,
+        );
+    } else {
+        env.run_until_epoch_height(&client_handle, 4, timeout);
+        let client = &env.test_loop.data.get(&client_handle).client;
+        let diff =
+            ValidatorSetDiff::between(&initial_validators, &get_epoch_all_validators(client));
+        assert!(diff.is_empty(), "No kickouts are expected, got {diff:?}");
+    }
 
-    env.test_loop.run_until(
-        success_condition,
-        // Timeout at producing 5 epochs, approximately.
-        Duration::seconds((5 * epoch_length) as i64),
-    );
+    // Check the number of missed chunks in the blocks produced during the test.
+    let client = &env.test_loop.data.get(&client_handle).client;
+    match &test_case {
+        TestCase::DropChunksValidatedBy(_) => {
+            let head_height = client.chain.head().unwrap().height;
+            for height in start_height..=head_height {
+                let Ok(block) = client.chain.get_block_by_height(height) else {
+                    continue;
+                };
+                assert!(
+                    missed_chunks_in_block(client, block.hash()) <= 1,
+                    "At most one chunk must be missed when dropping chunks validated by the selected account"
+                );
+            }
+        }
+        TestCase::DropEndorsementsFrom(_) | TestCase::CensorEndorsementsFrom(_) => assert_eq!(
+            missed_chunks_since(client, start_height),
+            0,
+            "No chunk must be missed when dropping endorsements from the selected account"
+        ),
+    }
 }
 
 /// Checks that chunk validator with low endorsement stats is kicked out when the chunks it would validate are all dropped.
@@ -179,3 +209,12 @@ fn slow_test_block_producer_not_kicked_out_when_endorsements_dropped() {
     let test_case = TestCase::DropEndorsementsFrom(accounts[NUM_PRODUCER_ACCOUNTS - 1].clone());
     run_test_chunk_validator_kickout(accounts, test_case);
 }
+
+/// Checks that chunk validator is kicked out when the endorsements it sends to
+/// every account are censored.
+#[test]
+fn slow_test_chunk_validator_kicked_out_when_endorsements_censored() {
+    let accounts = create_accounts();
+    let test_case = TestCase::CensorEndorsementsFrom(accounts[NUM_PRODUCER_ACCOUNTS + 1].clone());
+    run_test_chunk_validator_kickout(accounts, test_case);
+}
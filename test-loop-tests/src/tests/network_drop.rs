@@ -1,10 +1,24 @@
 use crate::setup::builder::TestLoopBuilder;
+use crate::setup::drop_condition::DropCondition;
 use crate::setup::peer_manager_actor::HandlerResult;
+use crate::utils::account::create_validator_ids;
+use crate::utils::network::{messages_to_account_dropper, random_message_dropper};
+use itertools::Itertools;
+use near_async::messaging::Handler;
 use near_async::time::Duration;
-use near_network::types::NetworkResponses;
+use near_network::types::{
+    NetworkRequests, NetworkRequestsDiscriminants, NetworkResponses, PeerManagerMessageRequest,
+    SnapshotHostEvent,
+};
 use near_o11y::testonly::init_test_logger;
+use near_primitives::hash::CryptoHash;
+use near_primitives::stateless_validation::contract_distribution::SpiceChunkContractAccesses;
+use near_primitives::test_utils::create_test_signer;
+use near_primitives::types::{AccountId, ShardId, SpiceChunkId};
 use parking_lot::RwLock;
 use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 const TARGET_HEIGHT: u64 = 20;
@@ -43,3 +57,141 @@ fn network_drop_random_messages() {
     env.node_runner(0)
         .run_until_head_height_with_timeout(TARGET_HEIGHT, Duration::seconds(TIMEOUT_SECONDS));
 }
+
+#[test]
+fn messages_to_account_dropper_drops_only_the_recipient() {
+    let to: AccountId = "to".parse().unwrap();
+    let other: AccountId = "other".parse().unwrap();
+    let tx_status = |account_id: &AccountId| {
+        NetworkRequests::TxStatus(account_id.clone(), other.clone(), CryptoHash::default())
+    };
+    let is_dropped = |result: HandlerResult| {
+        matches!(result, HandlerResult::Handled(NetworkResponses::NoResponse))
+    };
+
+    // Single recipient: dropped only if it is the `to` account and of the given kind.
+    let dropper = messages_to_account_dropper(to.clone(), NetworkRequestsDiscriminants::TxStatus);
+    assert!(is_dropped(dropper(tx_status(&to))));
+    assert!(!is_dropped(dropper(tx_status(&other))));
+    let dropper = messages_to_account_dropper(to.clone(), NetworkRequestsDiscriminants::ForwardTx);
+    assert!(!is_dropped(dropper(tx_status(&to))));
+
+    // Multiple recipients: only the `to` account is removed.
+    let chunk_id = SpiceChunkId { block_hash: CryptoHash::default(), shard_id: ShardId::new(0) };
+    let accesses =
+        SpiceChunkContractAccesses::new(chunk_id, HashSet::new(), &create_test_signer("test"));
+    let dropper = messages_to_account_dropper(
+        to.clone(),
+        NetworkRequestsDiscriminants::SpiceChunkContractAccesses,
+    );
+    let request = NetworkRequests::SpiceChunkContractAccesses(
+        vec![to.clone(), other.clone()],
+        accesses.clone(),
+    );
+    match dropper(request) {
+        HandlerResult::Unhandled(request) => assert_eq!(
+            request,
+            NetworkRequests::SpiceChunkContractAccesses(vec![other], accesses.clone())
+        ),
+        HandlerResult::Handled(_) => panic!("expected the other recipient to be kept"),
+    }
+    assert!(is_dropped(dropper(NetworkRequests::SpiceChunkContractAccesses(vec![to], accesses))));
+}
+
+#[test]
+fn random_message_dropper_is_reproducible() {
+    let account_id: AccountId = "test".parse().unwrap();
+    let decisions = |seed: u64| {
+        let dropper = random_message_dropper(0.5, ChaCha20Rng::seed_from_u64(seed));
+        (0..64)
+            .map(|_| {
+                let request = NetworkRequests::TxStatus(
+                    account_id.clone(),
+                    account_id.clone(),
+                    CryptoHash::default(),
+                );
+                matches!(dropper(request), HandlerResult::Handled(_))
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(decisions(1), decisions(1));
+    assert_ne!(decisions(1), decisions(2));
+
+    // Requests which don't send a message are never dropped.
+    let dropper = random_message_dropper(1.0, ChaCha20Rng::seed_from_u64(1));
+    let event = SnapshotHostEvent::ChainProgressed { epoch_height: 1 };
+    assert!(matches!(
+        dropper(NetworkRequests::SnapshotHostEvent(event)),
+        HandlerResult::Unhandled(_)
+    ));
+}
+
+/// Sends a `TxStatus` request to each of `recipients` through the first of
+/// three validators, after adding `drop_conditions` in the given order.
+/// Returns the indices of the requests which none of the conditions dropped.
+fn delivered_requests(drop_conditions: Vec<DropCondition>, recipients: &[AccountId]) -> Vec<usize> {
+    let mut env = TestLoopBuilder::new().validators(3, 0).skip_warmup().build();
+    let delivered = Arc::new(RwLock::new(Vec::new()));
+    // Registered before the drop conditions, so it only sees the requests
+    // which they let through.
+    let recorder = {
+        let delivered = delivered.clone();
+        Box::new(move |request: NetworkRequests| match request {
+            NetworkRequests::TxStatus(_, _, hash) => {
+                delivered.write().push(hash);
+                HandlerResult::Handled(NetworkResponses::NoResponse)
+            }
+            request => HandlerResult::Unhandled(request),
+        })
+    };
+    env.node_datas[0].register_override_handler(&mut env.test_loop.data, recorder);
+    let env = drop_conditions.into_iter().fold(env, |env, condition| env.drop(condition));
+
+    let sender = env.node_datas[0].account_id.clone();
+    let peer_actor_handle = env.node_datas[0].peer_manager_sender.actor_handle();
+    let peer_actor = env.test_loop.data.get_mut(&peer_actor_handle);
+    let hashes = (0..recipients.len()).map(|i| CryptoHash::hash_borsh(i as u64)).collect_vec();
+    for (recipient, hash) in recipients.iter().zip(&hashes) {
+        let request = NetworkRequests::TxStatus(recipient.clone(), sender.clone(), *hash);
+        Handler::<PeerManagerMessageRequest>::handle(
+            peer_actor,
+            PeerManagerMessageRequest::NetworkRequests(request),
+        );
+    }
+    let delivered = delivered.read();
+    let indices = delivered.iter().map(|hash| hashes.iter().position(|h| h == hash).unwrap());
+    indices.collect()
+}
+
+/// Checks the documented interaction between `DropCondition::RandomLoss` and a
+/// targeted `DropCondition::MessagesBetween`, depending on the order in which
+/// they are added.
+#[test]
+fn random_loss_composes_with_targeted_drop() {
+    init_test_logger();
+
+    let [from, censored, other]: [AccountId; 3] = create_validator_ids(3).try_into().unwrap();
+    let recipients =
+        (0..64).map(|i| if i % 2 == 0 { &censored } else { &other }).cloned().collect_vec();
+    let random_loss = || DropCondition::RandomLoss { ratio: 0.5, seed: 42 };
+    let targeted = || DropCondition::MessagesBetween {
+        from: from.clone(),
+        to: censored.clone(),
+        kind: NetworkRequestsDiscriminants::TxStatus,
+    };
+
+    // Added last, the random loss is applied first and sees every request, so
+    // the uncensored ones are dropped the same as without the targeted condition.
+    let random_loss_only = delivered_requests(vec![random_loss()], &recipients);
+    let delivered = delivered_requests(vec![targeted(), random_loss()], &recipients);
+    let expected = random_loss_only.into_iter().filter(|i| recipients[*i] == other).collect_vec();
+    assert_eq!(delivered, expected);
+
+    // Added first, the random loss only sees the requests which the targeted
+    // condition let through, the same as if only those were sent.
+    let uncensored = vec![other.clone(); recipients.len() / 2];
+    let random_loss_only = delivered_requests(vec![random_loss()], &uncensored);
+    let delivered = delivered_requests(vec![random_loss(), targeted()], &recipients);
+    let expected = random_loss_only.into_iter().map(|i| 2 * i + 1).collect_vec();
+    assert_eq!(delivered, expected);
+}
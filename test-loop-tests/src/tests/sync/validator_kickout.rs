@@ -19,6 +19,7 @@ use super::util::{assert_near_horizon_sync_sequence, run_until_synced, track_syn
 use crate::setup::builder::TestLoopBuilder;
 use crate::setup::env::TestLoopEnv;
 use crate::utils::account::{create_validators_spec, validators_spec_clients};
+use crate::utils::validators::{ValidatorSetDiff, get_validator_set_diff};
 use near_chain_configs::test_genesis::TestEpochConfigBuilder;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::types::{AccountId, EpochId, ValidatorInfoIdentifier};
@@ -126,7 +127,10 @@ fn test_validator_kickout_after_restart() {
     // Validator set membership: in E and E+1 (decided before E ended), kicked in E+2.
     assert!(is_validator_in_epoch(&env, &epoch_e, &account_id));
     assert!(is_validator_in_epoch(&env, &epoch_e1, &account_id));
-    assert!(!is_validator_in_epoch(&env, &epoch_e2, &account_id));
+    assert_eq!(
+        get_validator_set_diff(env.node(1).client(), &epoch_e1, &epoch_e2),
+        ValidatorSetDiff::only_kicked_out(&[account_id.as_str()]),
+    );
 
     // Block production: produced in E (before kill) and E+1 (restarted, still in
     // validator set), but zero in E+2 (kicked).
@@ -1,6 +1,7 @@
 use crate::setup::builder::TestLoopBuilder;
 use crate::setup::drop_condition::DropCondition;
 use crate::utils::run_for_number_of_blocks;
+use crate::utils::sharding::missed_chunks_per_shard_since;
 use crate::utils::transactions::{TransactionRunner, execute_tx};
 use assert_matches::assert_matches;
 use itertools::Itertools;
@@ -81,6 +82,12 @@ fn slow_test_tx_inclusion_with_missed_chunks() {
 
     run_for_number_of_blocks(&mut env, rpc_id, num_missed_chunks + 2);
 
+    // Only the target shard misses chunks, and no more than were dropped.
+    let client = &env.test_loop.data.get(&env.node_datas[0].client_sender.actor_handle()).client;
+    let missed_chunks = missed_chunks_per_shard_since(client, client.chain.genesis().height());
+    assert_eq!(missed_chunks.keys().collect_vec(), vec![&target_shard_id]);
+    assert!(missed_chunks[&target_shard_id] <= num_missed_chunks);
+
     // Send a tx targeting the stuck shard; it should be accepted because
     // 10 missed chunks is well below the 125 threshold.
     let tx = env.node_for_account(rpc_id).tx_send_money(
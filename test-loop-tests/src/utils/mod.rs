@@ -3,7 +3,8 @@ use crate::setup::state::NodeExecutionData;
 use near_async::test_loop::data::TestLoopData;
 use near_client::Client;
 use near_client::client_actor::ClientActor;
-use near_primitives::types::{AccountId, BlockHeight};
+use near_epoch_manager::EpochManagerAdapter;
+use near_primitives::types::{AccountId, BlockHeight, EpochHeight};
 
 pub(crate) mod account;
 pub(crate) mod cloud_archival;
@@ -37,6 +38,12 @@ pub(crate) fn get_node_head_height(
     get_node_client(env, client_account_id).chain.head().unwrap().height
 }
 
+/// Returns the height of the epoch the client's head block belongs to.
+pub(crate) fn get_head_epoch_height(client: &Client) -> EpochHeight {
+    let tip = client.chain.head().unwrap();
+    client.epoch_manager.get_epoch_height_from_prev_block(&tip.prev_block_hash).unwrap()
+}
+
 pub(crate) fn run_for_number_of_blocks(
     env: &mut TestLoopEnv,
     client_account_id: &AccountId,
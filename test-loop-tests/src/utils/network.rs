@@ -1,11 +1,13 @@
 use crate::setup::drop_condition::TestLoopChunksStorage;
 use crate::setup::peer_manager_actor::HandlerResult;
 use near_epoch_manager::EpochManagerAdapter;
-use near_network::types::NetworkRequests;
 use near_network::types::NetworkResponses;
+use near_network::types::{NetworkRequests, NetworkRequestsDiscriminants};
 use near_primitives::sharding::ShardChunkHeader;
 use near_primitives::types::{AccountId, BlockHeight};
 use parking_lot::Mutex;
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
 use std::collections::HashSet;
 use std::sync::Arc;
 
@@ -109,3 +111,150 @@ pub fn block_dropper_by_height(
         _ => HandlerResult::Unhandled(request),
     })
 }
+
+/// Handler to drop each network message with probability `ratio`. Decisions are
+/// drawn from `rng`, so they are reproducible given the same seed and the same
+/// sequence of messages. Requests which don't send a message to other nodes,
+/// such as banning a peer, are never dropped and don't consume randomness.
+pub fn random_message_dropper(
+    ratio: f64,
+    rng: ChaCha20Rng,
+) -> Box<dyn Fn(NetworkRequests) -> HandlerResult> {
+    let rng = Mutex::new(rng);
+    Box::new(move |request| {
+        let carries_message = !matches!(
+            request,
+            NetworkRequests::BanPeer { .. } | NetworkRequests::SnapshotHostEvent(_)
+        );
+        if carries_message && rng.lock().gen_bool(ratio) {
+            HandlerResult::Handled(NetworkResponses::NoResponse)
+        } else {
+            HandlerResult::Unhandled(request)
+        }
+    })
+}
+
+/// Handler to drop all network messages of the given kind addressed to the `to`
+/// account. Messages addressed to multiple accounts are still delivered to the
+/// other recipients. Messages which are not addressed to accounts, e.g. the ones
+/// sent to peers, are not dropped.
+pub fn messages_to_account_dropper(
+    to: AccountId,
+    kind: NetworkRequestsDiscriminants,
+) -> Box<dyn Fn(NetworkRequests) -> HandlerResult> {
+    Box::new(move |request| {
+        if NetworkRequestsDiscriminants::from(&request) != kind {
+            return HandlerResult::Unhandled(request);
+        }
+        match without_recipient(request, &to) {
+            Some(request) => HandlerResult::Unhandled(request),
+            None => HandlerResult::Handled(NetworkResponses::NoResponse),
+        }
+    })
+}
+
+/// Removes `account_id` from the recipients of `request`. Returns None if no
+/// recipients are left.
+fn without_recipient(request: NetworkRequests, account_id: &AccountId) -> Option<NetworkRequests> {
+    let single_recipient = match &request {
+        NetworkRequests::Approval { approval_message } => Some(&approval_message.target),
+        NetworkRequests::PartialEncodedChunkRequest { target, .. } => target.account_id.as_ref(),
+        NetworkRequests::PartialEncodedChunkMessage { account_id, .. }
+        | NetworkRequests::PartialEncodedChunkForward { account_id, .. }
+        | NetworkRequests::ForwardTx(account_id, _)
+        | NetworkRequests::TxStatus(account_id, _, _)
+        | NetworkRequests::ChunkStateWitnessAck(account_id, _)
+        | NetworkRequests::ChunkEndorsement(account_id, _)
+        | NetworkRequests::ContractCodeRequest(account_id, _)
+        | NetworkRequests::ContractCodeResponse(account_id, _)
+        | NetworkRequests::SpiceChunkEndorsement(account_id, _)
+        | NetworkRequests::SpicePartialDataRequest { producer: account_id, .. }
+        | NetworkRequests::SpiceContractCodeRequest(account_id, _)
+        | NetworkRequests::SpiceContractCodeResponse(account_id, _) => Some(account_id),
+        // Addressed to multiple accounts, handled below.
+        NetworkRequests::OptimisticBlock { .. }
+        | NetworkRequests::PartialEncodedStateWitness(_)
+        | NetworkRequests::PartialEncodedStateWitnessForward(_, _)
+        | NetworkRequests::ChunkContractAccesses(_, _)
+        | NetworkRequests::PartialEncodedContractDeploys(_, _)
+        | NetworkRequests::SpicePartialData { .. }
+        | NetworkRequests::SpiceChunkContractAccesses(_, _) => None,
+        // Not addressed to accounts.
+        NetworkRequests::Block { .. }
+        | NetworkRequests::BlockRequest { .. }
+        | NetworkRequests::BlockHeadersRequest { .. }
+        | NetworkRequests::StateRequestHeader { .. }
+        | NetworkRequests::StateRequestPart { .. }
+        | NetworkRequests::StateRequestAck { .. }
+        | NetworkRequests::BanPeer { .. }
+        | NetworkRequests::AnnounceAccount(_)
+        | NetworkRequests::SnapshotHostEvent(_)
+        | NetworkRequests::PartialEncodedChunkResponse { .. }
+        | NetworkRequests::EpochSyncRequest { .. }
+        | NetworkRequests::EpochSyncResponse { .. } => None,
+    };
+    if single_recipient == Some(account_id) {
+        return None;
+    }
+
+    let request = match request {
+        NetworkRequests::OptimisticBlock { chunk_producers, optimistic_block } => {
+            let chunk_producers: Vec<_> = chunk_producers
+                .iter()
+                .filter(|producer| *producer != account_id)
+                .cloned()
+                .collect();
+            if chunk_producers.is_empty() {
+                return None;
+            }
+            NetworkRequests::OptimisticBlock {
+                chunk_producers: Arc::new(chunk_producers),
+                optimistic_block,
+            }
+        }
+        NetworkRequests::PartialEncodedStateWitness(mut witnesses) => {
+            witnesses.retain(|(target, _)| target != account_id);
+            if witnesses.is_empty() {
+                return None;
+            }
+            NetworkRequests::PartialEncodedStateWitness(witnesses)
+        }
+        NetworkRequests::PartialEncodedStateWitnessForward(mut targets, witness) => {
+            targets.retain(|target| target != account_id);
+            if targets.is_empty() {
+                return None;
+            }
+            NetworkRequests::PartialEncodedStateWitnessForward(targets, witness)
+        }
+        NetworkRequests::ChunkContractAccesses(mut targets, accesses) => {
+            targets.retain(|target| target != account_id);
+            if targets.is_empty() {
+                return None;
+            }
+            NetworkRequests::ChunkContractAccesses(targets, accesses)
+        }
+        NetworkRequests::PartialEncodedContractDeploys(mut targets, deploys) => {
+            targets.retain(|target| target != account_id);
+            if targets.is_empty() {
+                return None;
+            }
+            NetworkRequests::PartialEncodedContractDeploys(targets, deploys)
+        }
+        NetworkRequests::SpicePartialData { partial_data, mut recipients } => {
+            recipients.remove(account_id);
+            if recipients.is_empty() {
+                return None;
+            }
+            NetworkRequests::SpicePartialData { partial_data, recipients }
+        }
+        NetworkRequests::SpiceChunkContractAccesses(mut targets, accesses) => {
+            targets.retain(|target| target != account_id);
+            if targets.is_empty() {
+                return None;
+            }
+            NetworkRequests::SpiceChunkContractAccesses(targets, accesses)
+        }
+        request => request,
+    };
+    Some(request)
+}
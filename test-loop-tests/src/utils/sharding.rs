@@ -4,8 +4,9 @@ use near_epoch_manager::EpochManagerAdapter;
 use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::state_record::StateRecord;
-use near_primitives::types::ShardId;
+use near_primitives::types::{BlockHeight, ShardId};
 use near_store::{ShardUId, Trie};
+use std::collections::HashMap;
 
 // Finds the client who tracks the shard with `shard_id` among the list of `clients`.
 pub fn get_client_tracking_shard<'a>(
@@ -147,3 +148,42 @@ pub fn get_shards_will_care_about(client: &Client, block_hash: &CryptoHash) -> V
     }
     shards_needs_for_next_epoch
 }
+
+/// Returns the number of chunks missing in the block with the given hash, as
+/// indicated by its chunk mask.
+pub fn missed_chunks_in_block(client: &Client, block_hash: &CryptoHash) -> usize {
+    let block = client.chain.get_block(block_hash).unwrap();
+    block.header().chunk_mask().iter().filter(|included| !**included).count()
+}
+
+/// Returns the total number of chunks missing in the canonical blocks from
+/// `from_height` (inclusive) up to the current head. Skipped heights are ignored.
+pub fn missed_chunks_since(client: &Client, from_height: BlockHeight) -> usize {
+    missed_chunks_per_shard_since(client, from_height).values().sum()
+}
+
+/// Returns the number of chunks missing per shard in the canonical blocks from
+/// `from_height` (inclusive) up to the current head. Each block's chunk mask is
+/// mapped to shard ids using the shard layout of that block's epoch, so counts
+/// stay correct across resharding. Shards without missed chunks are absent.
+pub fn missed_chunks_per_shard_since(
+    client: &Client,
+    from_height: BlockHeight,
+) -> HashMap<ShardId, usize> {
+    let head_height = client.chain.head().unwrap().height;
+    let mut missed_chunks = HashMap::new();
+    for height in from_height..=head_height {
+        let Ok(block) = client.chain.get_block_by_height(height) else {
+            continue;
+        };
+        let shard_layout =
+            client.epoch_manager.get_shard_layout(block.header().epoch_id()).unwrap();
+        for (shard_index, included) in block.header().chunk_mask().iter().enumerate() {
+            if !included {
+                let shard_id = shard_layout.get_shard_id(shard_index).unwrap();
+                *missed_chunks.entry(shard_id).or_default() += 1;
+            }
+        }
+    }
+    missed_chunks
+}
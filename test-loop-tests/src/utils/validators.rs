@@ -1,5 +1,7 @@
 use itertools::Itertools;
 use near_client::Client;
+use near_primitives::types::EpochId;
+use std::collections::BTreeSet;
 
 /// Get all validator account names for the latest epoch.
 pub(crate) fn get_epoch_all_validators(client: &Client) -> Vec<String> {
@@ -13,3 +15,48 @@ pub(crate) fn get_epoch_all_validators(client: &Client) -> Vec<String> {
 pub(crate) fn get_epoch_all_validators_sorted(client: &Client) -> Vec<String> {
     get_epoch_all_validators(client).into_iter().sorted().collect()
 }
+
+/// Validators which joined or were kicked out between two validator sets.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ValidatorSetDiff {
+    pub joined: BTreeSet<String>,
+    pub kicked_out: BTreeSet<String>,
+}
+
+impl ValidatorSetDiff {
+    /// Compares two snapshots of validator account names, e.g. taken with
+    /// `get_epoch_all_validators` at different points of the test.
+    pub(crate) fn between(before: &[String], after: &[String]) -> Self {
+        let before: BTreeSet<String> = before.iter().cloned().collect();
+        let after: BTreeSet<String> = after.iter().cloned().collect();
+        Self {
+            joined: after.difference(&before).cloned().collect(),
+            kicked_out: before.difference(&after).cloned().collect(),
+        }
+    }
+
+    /// Expected diff where exactly the given accounts were kicked out and none joined.
+    pub(crate) fn only_kicked_out(accounts: &[&str]) -> Self {
+        Self {
+            joined: BTreeSet::new(),
+            kicked_out: accounts.iter().map(|account| account.to_string()).collect(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.joined.is_empty() && self.kicked_out.is_empty()
+    }
+}
+
+/// Compares the validator sets of the two given epochs.
+pub(crate) fn get_validator_set_diff(
+    client: &Client,
+    from_epoch_id: &EpochId,
+    to_epoch_id: &EpochId,
+) -> ValidatorSetDiff {
+    let get_validators = |epoch_id| {
+        let all_validators = client.epoch_manager.get_epoch_all_validators(epoch_id).unwrap();
+        all_validators.into_iter().map(|vs| vs.account_id().to_string()).collect_vec()
+    };
+    ValidatorSetDiff::between(&get_validators(from_epoch_id), &get_validators(to_epoch_id))
+}
@@ -253,6 +253,13 @@ export type ValidatorKickoutReason =
 
 export interface PeerStoreView {
     peer_states: KnownPeerStateView[];
+    bans: BanView[];
+}
+
+export interface BanView {
+    peer_id: string;
+    reason: string;
+    expires_at: number;
 }
 
 export interface KnownPeerStateView {